@@ -0,0 +1,145 @@
+use arcstr::ArcStr;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use mirafetch::{
+    config::{Config, IpVersion},
+    info::{
+        values::{Cpu, DiskUsage, Memory},
+        Info, OSInfo,
+    },
+};
+
+const OS_RELEASE: &str = r#"NAME="Arch Linux"
+PRETTY_NAME="Arch Linux"
+ID=arch
+BUILD_ID=rolling
+ANSI_COLOR="38;2;23;147;209"
+HOME_URL="https://archlinux.org/"
+DOCUMENTATION_URL="https://wiki.archlinux.org/"
+SUPPORT_URL="https://bbs.archlinux.org/"
+BUG_REPORT_URL="https://gitlab.archlinux.org/groups/archlinux/-/issues"
+PRIVACY_POLICY_URL="https://terms.archlinux.org/docs/privacy-policy/"
+LOGO=archlinux-logo
+"#;
+
+const MEMINFO: &str = "MemTotal:       32779112 kB
+MemFree:         9863296 kB
+MemAvailable:   21474836 kB
+Buffers:          620432 kB
+Cached:         11206144 kB
+SwapCached:            0 kB
+Active:         12318316 kB
+Inactive:        8601268 kB
+SwapTotal:       8388604 kB
+SwapFree:        8388604 kB
+Dirty:               412 kB
+Shmem:            982756 kB
+";
+
+const MOUNTS: &str = "proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0
+sysfs /sys sysfs rw,nosuid,nodev,noexec,relatime 0 0
+devtmpfs /dev devtmpfs rw,nosuid,size=16389556k,nr_inodes=4097389,mode=755 0 0
+/dev/nvme0n1p2 / btrfs rw,relatime,ssd,space_cache=v2,subvolid=256,subvol=/@ 0 0
+/dev/nvme0n1p2 /home btrfs rw,relatime,ssd,space_cache=v2,subvolid=257,subvol=/@home 0 0
+/dev/nvme0n1p1 /boot vfat rw,relatime,fmask=0022,dmask=0022,codepage=437 0 0
+/dev/loop0 /var/lib/snapd/snap/core22/864 squashfs ro,nodev,relatime 0 0
+tmpfs /tmp tmpfs rw,nosuid,nodev,size=16389556k,nr_inodes=1048576 0 0
+rpool/ROOT/ubuntu /rpool/ROOT zfs rw,xattr,posixacl 0 0
+drvfs /mnt/c 9p rw,noatime,aname=drvfs;path=C:\\ 0 0
+";
+
+/// Returns fixed values so collection overhead can be measured without touching the system
+struct MockInfo;
+
+impl OSInfo for MockInfo {
+    fn os(&self) -> Option<ArcStr> {
+        Some(arcstr::literal!("Arch Linux x86_64"))
+    }
+    fn id(&self) -> ArcStr {
+        arcstr::literal!("arch")
+    }
+    fn hostname(&self) -> Option<ArcStr> {
+        Some(arcstr::literal!("bench"))
+    }
+    fn username(&self) -> Option<ArcStr> {
+        Some(arcstr::literal!("user"))
+    }
+    fn kernel(&self) -> Option<ArcStr> {
+        Some(arcstr::literal!("6.6.1-arch1-1"))
+    }
+    fn uptime(&self) -> Option<ArcStr> {
+        Some(arcstr::literal!("3 hours, 14 minutes"))
+    }
+    fn cpu(&self) -> Option<Cpu> {
+        Some(Cpu {
+            cores: Some(16.0),
+            freq_mhz: Some(4850.0),
+            ..Cpu::new("AMD Ryzen 7 5800X")
+        })
+    }
+    fn memory(&self) -> Option<Memory> {
+        Some(Memory {
+            used: 11_596_411_699,
+            total: 33_564_845_834,
+            cgroup: false,
+        })
+    }
+    fn gpus(&self) -> Vec<ArcStr> {
+        vec![arcstr::literal!("AMD Radeon RX 6800 XT")]
+    }
+    fn ip(&self, _: IpVersion) -> Vec<(ArcStr, ArcStr)> {
+        vec![(arcstr::literal!("eth0"), arcstr::literal!("192.168.1.2"))]
+    }
+    fn disks(&self) -> Vec<(ArcStr, DiskUsage)> {
+        vec![(
+            arcstr::literal!("Disk (/)"),
+            DiskUsage {
+                used: 412 << 30,
+                total: 931 << 30,
+            },
+        )]
+    }
+}
+
+fn parsers(c: &mut Criterion) {
+    #[cfg(target_os = "linux")]
+    {
+        use mirafetch::info::linuxinfo::{filter_mounts, parse_meminfo, parse_os_release};
+
+        c.bench_function("parse os-release", |b| {
+            b.iter(|| parse_os_release(black_box(OS_RELEASE)));
+        });
+        c.bench_function("parse meminfo", |b| {
+            b.iter(|| parse_meminfo(black_box(MEMINFO)));
+        });
+        c.bench_function("filter mounts", |b| {
+            b.iter(|| filter_mounts(black_box(MOUNTS)));
+        });
+        c.bench_function("read small file", |b| {
+            b.iter(|| std::fs::read_to_string(black_box("/proc/sys/kernel/pid_max")));
+        });
+        c.bench_function("read small file (shared buffer)", |b| {
+            b.iter(|| {
+                mirafetch::info::sysfs::read_parse::<u64>(black_box("/proc/sys/kernel/pid_max"))
+            });
+        });
+        let paths = vec![std::path::PathBuf::from("/proc/sys/kernel/pid_max"); 64];
+        c.bench_function("read many small files", |b| {
+            b.iter(|| mirafetch::info::sysfs::read_parse_all::<u64>(black_box(&paths)));
+        });
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (c, OS_RELEASE, MEMINFO, MOUNTS);
+}
+
+fn collection(c: &mut Criterion) {
+    let config = Config::default();
+    c.bench_function("collect mocked", |b| {
+        b.iter(|| Info::from_source(&MockInfo, black_box(&config)));
+    });
+    c.bench_function("collect and render mocked", |b| {
+        b.iter(|| Info::from_source(&MockInfo, black_box(&config)).as_vec());
+    });
+}
+
+criterion_group!(benches, parsers, collection);
+criterion_main!(benches);
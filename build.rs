@@ -0,0 +1,32 @@
+use std::{env, process::Command};
+
+fn main() {
+    // Record where this binary came from so bug reports can include it
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|x| x.status.success())
+        .map(|x| String::from_utf8_lossy(&x.stdout).trim().to_string())
+        .unwrap_or_else(|| String::from("unknown"));
+    let features = env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|x| x.to_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    println!("cargo:rustc-env=MIRAFETCH_GIT_COMMIT={commit}");
+    println!(
+        "cargo:rustc-env=MIRAFETCH_PROFILE={}",
+        env::var("PROFILE").unwrap_or_default()
+    );
+    println!(
+        "cargo:rustc-env=MIRAFETCH_TARGET={}",
+        env::var("TARGET").unwrap_or_default()
+    );
+    println!("cargo:rustc-env=MIRAFETCH_FEATURES={features}");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+}
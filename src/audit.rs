@@ -0,0 +1,274 @@
+//! What mirafetch reads, connects to and runs for a configuration, printed by `--audit-sources`
+//! without collecting anything
+//!
+//! Paths may contain `*` for any name and `~` for the home directory, and `{pid}` stands for
+//! the processes looked at, such as mirafetch's parents
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::config::Config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// Files and directories, only ever read unless the source says otherwise
+    File,
+    /// Local sockets, such as the display server's
+    Socket,
+    /// Remote hosts
+    Network,
+    /// Programs that are run
+    Command,
+}
+
+impl Kind {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Socket => "socket",
+            Self::Network => "network",
+            Self::Command => "command",
+        }
+    }
+}
+
+/// Files read by each module on Linux, by label
+#[cfg(target_os = "linux")]
+const MODULES: &[(&str, Kind, &str)] = &[
+    ("Architecture", Kind::File, "/bin/sh"),
+    ("Battery", Kind::File, "/sys/class/power_supply/*"),
+    ("Brightness", Kind::File, "/sys/class/backlight/*"),
+    ("Brightness", Kind::File, "/proc/{pid}/comm"),
+    ("CPU", Kind::File, "/proc/cpuinfo"),
+    (
+        "CPU",
+        Kind::File,
+        "/sys/devices/system/cpu/cpu*/cpufreq/cpuinfo_max_freq",
+    ),
+    (
+        "CPU",
+        Kind::File,
+        "/sys/firmware/devicetree/base/compatible",
+    ),
+    ("CPU", Kind::File, "/proc/self/cgroup"),
+    ("CPU", Kind::File, "/sys/fs/cgroup/*"),
+    ("DE", Kind::File, "/usr/share/gnome/gnome-version.xml"),
+    ("DE", Kind::File, "/usr/share/xsessions/plasma.desktop"),
+    (
+        "DE",
+        Kind::File,
+        "/usr/share/wayland-sessions/plasma*.desktop",
+    ),
+    (
+        "DE",
+        Kind::File,
+        "/usr/share/gtk-doc/html/libxfce4ui/index.html",
+    ),
+    ("Disk", Kind::File, "/proc/mounts"),
+    ("Display", Kind::File, "/sys/class/drm/card*-*"),
+    ("GPU", Kind::File, "/sys/class/drm/card*/device"),
+    ("Host", Kind::File, "/sys/class/dmi/id/*"),
+    ("Host", Kind::File, "/sys/devices/virtual/dmi/id/*"),
+    ("Host", Kind::File, "/sys/firmware/devicetree/base/model"),
+    (
+        "Host",
+        Kind::File,
+        "/sys/firmware/devicetree/base/banner-name",
+    ),
+    ("Host OS", Kind::File, "/run/host/etc/os-release"),
+    ("Host OS", Kind::File, "/run/host/usr/lib/os-release"),
+    ("Host OS", Kind::File, "/dev/.cros_milestone"),
+    ("Host OS", Kind::File, "/opt/google/cros-containers"),
+    ("Host OS", Kind::File, "/etc/apt/sources.list.d/cros.list"),
+    ("Host OS", Kind::File, "/run/.toolboxenv"),
+    ("IP", Kind::File, "/proc/net/if_inet6"),
+    ("Memory", Kind::File, "/proc/meminfo"),
+    ("Memory", Kind::File, "/proc/self/cgroup"),
+    ("Memory", Kind::File, "/sys/fs/cgroup/*"),
+    ("Memory", Kind::File, "/.dockerenv"),
+    ("Memory", Kind::File, "/run/.containerenv"),
+    ("Memory", Kind::File, "/run/systemd/container"),
+    ("Nix", Kind::File, "/nix/store"),
+    ("Nix", Kind::File, "/nix/var/nix/profiles/*"),
+    ("Nix", Kind::File, "~/.local/state/nix/profiles/*"),
+    ("OS", Kind::File, "/etc/os-release"),
+    ("Packages", Kind::File, "/var/lib/rpm/rpmdb.sqlite"),
+    ("Packages", Kind::File, "/usr/lib/sysimage/rpm/rpmdb.sqlite"),
+    ("Packages", Kind::File, "/var/lib/pacman/local"),
+    ("Packages", Kind::File, "/var/lib/dpkg/status"),
+    ("Packages", Kind::File, "/lib/apk/db/installed"),
+    ("Packages", Kind::File, "/var/db/xbps/pkgdb-0.38.plist"),
+    ("Packages", Kind::File, "/nix/var/nix/profiles/*"),
+    ("Packages", Kind::File, "~/.nix-profile"),
+    ("Packages", Kind::File, "/var/lib/flatpak"),
+    ("Packages", Kind::File, "~/.local/share/flatpak"),
+    ("Packages", Kind::File, "/snap"),
+    ("Power", Kind::File, "/sys/class/power_supply/*"),
+    ("Pressure", Kind::File, "/proc/pressure/*"),
+    ("Shell", Kind::File, "/proc/{pid}/comm"),
+    ("Shell Framework", Kind::File, "$ZSH/oh-my-zsh.sh"),
+    ("Shell Framework", Kind::File, "$OSH/oh-my-bash.sh"),
+    ("Shell Framework", Kind::File, "$BASH_IT/bash_it.sh"),
+    ("Shell Framework", Kind::File, "~/.zprezto/init.zsh"),
+    (
+        "Shell Framework",
+        Kind::File,
+        "~/.local/share/zinit/zinit.git/zinit.zsh",
+    ),
+    ("Shell Framework", Kind::File, "~/.antidote/antidote.zsh"),
+    (
+        "Shell Framework",
+        Kind::File,
+        "~/.config/fish/functions/fisher.fish",
+    ),
+    ("Swap", Kind::File, "/proc/meminfo"),
+    ("Terminal", Kind::File, "/proc/{pid}/comm"),
+    ("Terminal", Kind::File, "/proc/{pid}/stat"),
+    ("Terminal", Kind::File, "/proc/{pid}/task/{pid}/children"),
+    ("Terminal", Kind::File, "/proc/{pid}/root/.flatpak-info"),
+    ("Time Sync", Kind::File, "/etc/adjtime"),
+    ("WM", Kind::File, "/proc/{pid}/comm"),
+    ("WM", Kind::Socket, "$XDG_RUNTIME_DIR/$WAYLAND_DISPLAY"),
+];
+
+/// Where each source is used, sorted by kind and then by target
+#[derive(Debug, Default, Clone)]
+pub struct Sources(BTreeMap<(Kind, String), Vec<String>>);
+
+/// A single source, as printed by `--audit-sources --json`
+#[derive(Debug, Clone, Serialize)]
+pub struct Source<'a> {
+    pub kind: Kind,
+    pub target: &'a str,
+    pub used_by: &'a [String],
+}
+
+impl Sources {
+    /// The sources used to collect the system information with `config`
+    #[must_use]
+    // Nothing is added on other platforms without the `public-ip` feature
+    #[cfg_attr(
+        not(any(target_os = "linux", feature = "public-ip")),
+        allow(unused_mut)
+    )]
+    pub fn for_collection(config: &Config) -> Self {
+        let mut res = Self::default();
+        #[cfg(target_os = "linux")]
+        for (module, kind, target) in MODULES {
+            res.add(*kind, *target, module);
+        }
+        #[cfg(target_os = "linux")]
+        {
+            if config.cpu_freq_range.unwrap_or_default() {
+                res.add(
+                    Kind::File,
+                    "/sys/devices/system/cpu/cpu*/cpufreq/*",
+                    "cpu_freq_range",
+                );
+            }
+            if config.cpu_temp.unwrap_or_default() {
+                res.add(Kind::File, "/sys/class/hwmon/hwmon*", "cpu_temp");
+            }
+            if config.pipewire.unwrap_or_default() {
+                res.add(Kind::Socket, "$XDG_RUNTIME_DIR/pipewire-0", "pipewire");
+                res.add(Kind::Command, "pw-dump --no-colors", "pipewire");
+            }
+            if config.realtime.unwrap_or_default() {
+                res.add(Kind::File, "/sys/kernel/realtime", "realtime");
+                res.add(Kind::File, "/proc/self/limits", "realtime");
+                res.add(Kind::File, "/proc/cmdline", "realtime");
+            }
+            if config.accessibility.unwrap_or_default() {
+                res.add(Kind::File, "/proc/{pid}/comm", "accessibility");
+                res.add(Kind::File, "~/.config/kdeglobals", "accessibility");
+                res.add(
+                    Kind::Command,
+                    "gsettings get org.gnome.desktop.a11y.interface high-contrast",
+                    "accessibility",
+                );
+                res.add(
+                    Kind::Command,
+                    "gsettings get org.gnome.desktop.interface enable-animations",
+                    "accessibility",
+                );
+            }
+            if config.accessible.is_none() {
+                res.add(Kind::File, "/proc/{pid}/comm", "accessible");
+            }
+            if config.top.unwrap_or_default() {
+                res.add(Kind::File, "/proc/{pid}/stat", "top");
+                res.add(Kind::File, "/proc/{pid}/comm", "top");
+            }
+            if config.top_gpu.unwrap_or_default() {
+                res.add(Kind::File, "/proc/{pid}/fd/*", "top_gpu");
+                res.add(Kind::File, "/proc/{pid}/fdinfo/*", "top_gpu");
+                res.add(Kind::File, "/proc/{pid}/comm", "top_gpu");
+                res.add(Kind::File, "/proc/driver/nvidia", "top_gpu");
+                res.add(
+                    Kind::Command,
+                    "nvidia-smi --query-compute-apps=process_name,used_memory \
+                     --format=csv,noheader,nounits",
+                    "top_gpu",
+                );
+            }
+            if config.links.unwrap_or_default() {
+                res.add(Kind::File, "/etc/os-release", "links");
+            }
+            #[cfg(feature = "drm")]
+            res.add(Kind::File, "/dev/dri/card*", "Display");
+            #[cfg(feature = "wayland")]
+            res.add(Kind::Socket, "$XDG_RUNTIME_DIR/$WAYLAND_DISPLAY", "Display");
+            #[cfg(feature = "x11")]
+            {
+                res.add(Kind::Socket, "X server at $DISPLAY", "Display");
+                res.add(Kind::Socket, "X server at $DISPLAY", "WM");
+            }
+        }
+        #[cfg(feature = "public-ip")]
+        if config.public_ip.unwrap_or_default() {
+            res.add(
+                Kind::Network,
+                config
+                    .public_ip_url
+                    .as_deref()
+                    .unwrap_or(crate::info::PUBLIC_IP_URL),
+                "Public IP",
+            );
+        }
+        #[cfg(not(feature = "public-ip"))]
+        let _ = config;
+        res
+    }
+
+    /// Record that `target` is used by `used_by`, a module label or a setting
+    pub fn add(&mut self, kind: Kind, target: impl Into<String>, used_by: &str) {
+        let users = self.0.entry((kind, target.into())).or_default();
+        if !users.iter().any(|x| x == used_by) {
+            users.push(used_by.to_string());
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Source<'_>> {
+        self.0.iter().map(|((kind, target), used_by)| Source {
+            kind: *kind,
+            target,
+            used_by,
+        })
+    }
+
+    /// A line for each source, e.g. `file     /proc/meminfo (Memory, Swap)`
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        self.iter()
+            .map(|x| {
+                format!(
+                    "{:<8} {} ({})",
+                    x.kind.name(),
+                    x.target,
+                    x.used_by.join(", ")
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,103 @@
+//! A compact SVG card of the system information for profile READMEs
+use std::fmt::Write;
+
+const WIDTH: usize = 440;
+const PADDING: usize = 16;
+const LINE_HEIGHT: usize = 20;
+const BAND_HEIGHT: usize = 3;
+/// Values longer than this are cut short, as SVG text doesn't wrap
+const MAX_CHARS: usize = 44;
+const FONT: &str = "ui-monospace,SFMono-Regular,Menlo,Consolas,monospace";
+
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    /// Background, border, label and text colors
+    const fn colors(self) -> [&'static str; 4] {
+        match self {
+            Self::Dark => ["#0d1117", "#30363d", "#58a6ff", "#c9d1d9"],
+            Self::Light => ["#ffffff", "#d0d7de", "#0969da", "#24292f"],
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn shorten(text: &str) -> String {
+    if text.chars().count() <= MAX_CHARS {
+        return text.to_string();
+    }
+    let mut res = text.chars().take(MAX_CHARS - 1).collect::<String>();
+    res.push('…');
+    res
+}
+
+/// Card titled `title` with a `label: value` line for each row, under a band of `stripes`
+/// colors such as a flag's
+#[must_use]
+pub fn render(
+    title: &str,
+    rows: &[(&str, String)],
+    stripes: &[(u8, u8, u8)],
+    theme: Theme,
+) -> String {
+    let [background, border, label, text] = theme.colors();
+    let band = stripes.len() * BAND_HEIGHT;
+    let height = band + PADDING * 2 + LINE_HEIGHT * (rows.len() + 1);
+    let label_width = rows
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or_default()
+        + 2;
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{height}" viewBox="0 0 {WIDTH} {height}" role="img" aria-label="{}">
+<clipPath id="card"><rect width="{WIDTH}" height="{height}" rx="8"/></clipPath>
+<g clip-path="url(#card)">
+<rect width="{WIDTH}" height="{height}" fill="{background}"/>
+"#,
+        escape(title)
+    );
+    for (idx, (r, g, b)) in stripes.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            r##"<rect y="{}" width="{WIDTH}" height="{BAND_HEIGHT}" fill="#{r:02x}{g:02x}{b:02x}"/>"##,
+            idx * BAND_HEIGHT
+        );
+    }
+    let _ = write!(
+        svg,
+        r#"</g>
+<rect x="0.5" y="0.5" width="{}" height="{}" rx="8" fill="none" stroke="{border}"/>
+<g font-family="{FONT}" font-size="13" xml:space="preserve">
+<text x="{PADDING}" y="{}" fill="{label}" font-weight="bold">{}</text>
+"#,
+        WIDTH - 1,
+        height - 1,
+        band + PADDING + 13,
+        escape(&shorten(title))
+    );
+    for (idx, (name, value)) in rows.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{PADDING}" y="{}"><tspan fill="{label}">{:label_width$}</tspan><tspan fill="{text}">{}</tspan></text>"#,
+            band + PADDING + 13 + LINE_HEIGHT * (idx + 1),
+            format!("{name}:"),
+            escape(&shorten(value))
+        );
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
@@ -1,7 +1,8 @@
-use std::{ops::Index, sync::Arc};
+use std::sync::Arc;
 
 use crossterm::style::{Color, StyledContent, Stylize};
 use rayon::prelude::*;
+use unicode_width::UnicodeWidthChar;
 
 use crate::{config::Orientation, util::AsciiArt};
 
@@ -33,7 +34,9 @@ pub struct Flag {
 }
 
 impl Flag {
-    fn length_to_colors(&self, length: usize) -> impl Index<usize, Output = Color> {
+    /// The color of each of `length` rows or columns, with the stripes as even as they can be
+    #[must_use]
+    pub fn length_to_colors(&self, length: usize) -> Vec<Color> {
         let preset_len = self.color_scheme.len(); //6
         let center = preset_len / 2; // 4
 
@@ -53,10 +56,7 @@ impl Flag {
         }
         self.weights_to_colors(weights.into_par_iter())
     }
-    fn weights_to_colors(
-        &self,
-        weights: impl IndexedParallelIterator<Item = usize>,
-    ) -> impl Index<usize, Output = Color> {
+    fn weights_to_colors(&self, weights: impl IndexedParallelIterator<Item = usize>) -> Vec<Color> {
         weights
             .enumerate()
             .flat_map(|(idx, weight)| {
@@ -88,10 +88,16 @@ impl Colorizer for Flag {
                 //Requires txt has at least one line and is rectangular
                 let colors = self.length_to_colors(ascii_art.width as usize);
 
+                // Colors go by terminal column, which wide characters take two of
+                let last = (ascii_art.width as usize).saturating_sub(1);
                 txt.par_lines()
-                    .flat_map(|line| {
-                        line.par_char_indices()
-                            .map(|(idx, ch)| ch.to_string().with(colors[idx]))
+                    .flat_map_iter(|line| {
+                        line.chars()
+                            .scan(0, |column, ch| {
+                                let color = colors[(*column).min(last)];
+                                *column += ch.width().unwrap_or_default();
+                                Some(ch.to_string().with(color))
+                            })
                             .chain([String::from("\n").with(Color::Reset)])
                     })
                     .collect()
@@ -99,3 +105,40 @@ impl Colorizer for Flag {
         }
     }
 }
+
+/// `length` colors blending evenly from each of `colors` into the next, to draw a flag as a
+/// gradient with [`Flag`] rather than as stripes
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+#[must_use]
+pub fn gradient(colors: &[Color], length: usize) -> Vec<Color> {
+    let last = colors.len().saturating_sub(1);
+    (0..length.max(1))
+        .map(|idx| {
+            let pos = idx as f32 / length.saturating_sub(1).max(1) as f32 * last as f32;
+            let (from, to) = (colors[pos.floor() as usize], colors[pos.ceil() as usize]);
+            let share = pos.fract();
+            match (from, to) {
+                (
+                    Color::Rgb { r, g, b },
+                    Color::Rgb {
+                        r: r2,
+                        g: g2,
+                        b: b2,
+                    },
+                ) => {
+                    let mix = |x: u8, y: u8| {
+                        (f32::from(y) - f32::from(x))
+                            .mul_add(share, f32::from(x))
+                            .round() as u8
+                    };
+                    Color::Rgb {
+                        r: mix(r, r2),
+                        g: mix(g, g2),
+                        b: mix(b, b2),
+                    }
+                }
+                _ => from,
+            }
+        })
+        .collect()
+}
@@ -1,8 +1,63 @@
-#[derive(Debug, serde::Serialize, serde::Deserialize, Default)]
+use std::{path::PathBuf, time::Duration};
+
+use crossterm::style::Color;
+use rustc_hash::FxHashMap;
+
+use crate::{palette::Hex, transform::Pipeline, util::hostname_color};
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Default, Clone)]
 pub struct Config {
     pub scheme_name: Option<Box<str>>,
     pub orientation: Option<Orientation>,
+    pub flag_style: Option<FlagStyle>,
+    pub flag_text: Option<bool>,
     pub icon_name: Option<Box<str>>,
+    pub prefer_host_icon: Option<bool>,
+    pub cpu_freq_range: Option<bool>,
+    pub cpu_temp: Option<bool>,
+    pub temperature_unit: Option<TemperatureUnit>,
+    pub self_info: Option<bool>,
+    pub links: Option<bool>,
+    pub update_check: Option<UpdateInterval>,
+    pub threads: Option<usize>,
+    pub low_priority: Option<bool>,
+    pub strict: Option<bool>,
+    pub image: Option<PathBuf>,
+    pub image_rows: Option<u16>,
+    pub image_symbols: Option<ImageSymbols>,
+    pub terminal_quirks: Option<TerminalQuirks>,
+    pub logo_dirs: Option<Vec<PathBuf>>,
+    pub verify_logos: Option<bool>,
+    pub logo_source: Option<LogoSource>,
+    pub logo_file: Option<PathBuf>,
+    pub logo_size: Option<LogoSize>,
+    pub modules: Option<Vec<String>>,
+    pub preset: Option<Preset>,
+    pub presets: Option<FxHashMap<Preset, Vec<String>>>,
+    pub labels: Option<FxHashMap<String, String>>,
+    pub transforms: Option<FxHashMap<String, Pipeline>>,
+    pub transliterate: Option<bool>,
+    pub direction: Option<Direction>,
+    pub accessible: Option<bool>,
+    pub accent: Option<Accent>,
+    pub palette: Option<PaletteName>,
+    pub colors: Option<Colors>,
+    pub stats: Option<bool>,
+    pub cache: Option<bool>,
+    pub hardened: Option<bool>,
+    pub top: Option<bool>,
+    pub top_by: Option<TopSort>,
+    pub top_gpu: Option<bool>,
+    pub pipewire: Option<bool>,
+    pub realtime: Option<bool>,
+    pub accessibility: Option<bool>,
+    pub ip_version: Option<IpVersion>,
+    pub ip_interfaces: Option<Vec<String>>,
+    pub public_ip: Option<bool>,
+    pub public_ip_url: Option<String>,
+    pub discover_peers: Option<Vec<String>>,
+    pub publish_to: Option<String>,
+    pub publish_interval: Option<u64>,
 }
 
 impl Config {
@@ -22,7 +77,55 @@ impl Config {
         Self {
             scheme_name: scheme_name.map(|x| x.to_string().into_boxed_str()),
             orientation,
+            flag_style: None,
+            flag_text: None,
             icon_name: icon_name.map(|x| x.to_string().into_boxed_str()),
+            prefer_host_icon: None,
+            cpu_freq_range: None,
+            cpu_temp: None,
+            temperature_unit: None,
+            self_info: None,
+            links: None,
+            update_check: None,
+            threads: None,
+            low_priority: None,
+            strict: None,
+            image: None,
+            image_rows: None,
+            image_symbols: None,
+            terminal_quirks: None,
+            logo_dirs: None,
+            verify_logos: None,
+            logo_source: None,
+            logo_file: None,
+            logo_size: None,
+            modules: None,
+            preset: None,
+            presets: None,
+            labels: None,
+            transforms: None,
+            transliterate: None,
+            direction: None,
+            accessible: None,
+            accent: None,
+            palette: None,
+            colors: None,
+            stats: None,
+            cache: None,
+            hardened: None,
+            top: None,
+            top_by: None,
+            top_gpu: None,
+            pipewire: None,
+            realtime: None,
+            accessibility: None,
+            ip_version: None,
+            ip_interfaces: None,
+            public_ip: None,
+            public_ip_url: None,
+            discover_peers: None,
+            publish_to: None,
+            publish_interval: None,
         }
     }
 }
@@ -32,3 +135,240 @@ pub enum Orientation {
     Horizontal,
     Vertical,
 }
+
+/// How the flag of `scheme_name` is drawn
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagStyle {
+    #[default]
+    Stripes,
+    /// Each stripe's color blends into the next
+    Gradient,
+}
+
+/// Set of modules shown by default, picked from the kind of machine mirafetch runs on
+#[derive(
+    Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq, Hash, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    Server,
+    Laptop,
+    Desktop,
+    Container,
+    #[default]
+    All,
+}
+
+impl Preset {
+    /// Modules shown with this preset, an empty list shows everything
+    #[must_use]
+    pub const fn modules(self) -> &'static [&'static str] {
+        match self {
+            Self::Server => &[
+                "os",
+                "host",
+                "kernel",
+                "architecture",
+                "uptime",
+                "time_sync",
+                "packages",
+                "cpu",
+                "memory",
+                "swap",
+                "disks",
+                "ip",
+            ],
+            Self::Laptop => &[
+                "os",
+                "host",
+                "kernel",
+                "uptime",
+                "packages",
+                "shell",
+                "wm",
+                "de",
+                "cpu",
+                "theme",
+                "terminal",
+                "memory",
+                "battery",
+                "power",
+                "brightness",
+                "displays",
+                "gpus",
+                "disks",
+                "ip",
+            ],
+            Self::Desktop => &[
+                "os",
+                "host",
+                "kernel",
+                "uptime",
+                "packages",
+                "shell",
+                "wm",
+                "de",
+                "cpu",
+                "theme",
+                "system_font",
+                "cursor",
+                "terminal",
+                "terminal_font",
+                "memory",
+                "icon_theme",
+                "displays",
+                "gpus",
+                "disks",
+            ],
+            Self::Container => &["os", "kernel", "packages", "shell", "memory"],
+            Self::All => &[],
+        }
+    }
+}
+
+/// Which way the text output reads
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Ltr,
+    Rtl,
+}
+
+/// Color of the labels
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Accent {
+    #[default]
+    Red,
+    /// A color derived from the hostname, to tell machines apart at a glance
+    Hostname,
+}
+
+impl Accent {
+    /// The color for a machine named `hostname`, red when it has none
+    #[must_use]
+    pub fn color(self, hostname: Option<&str>) -> Color {
+        match (self, hostname) {
+            (Self::Hostname, Some(hostname)) => hostname_color(hostname),
+            _ => Color::Red,
+        }
+    }
+}
+
+/// How temperatures are shown
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+/// What the `top` module ranks processes by
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TopSort {
+    /// Resident memory
+    #[default]
+    Memory,
+    /// CPU use over a fifth of a second
+    Cpu,
+}
+
+/// Which addresses the `ip` module shows
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum IpVersion {
+    #[default]
+    V4,
+    V6,
+    Both,
+}
+
+/// Palettes of the `palette` setting, see [`crate::palette::Palette::named`]
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum PaletteName {
+    Dracula,
+    Nord,
+    Catppuccin,
+    Gruvbox,
+}
+
+/// Colors set one by one, over the palette's
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Colors {
+    pub label: Option<Hex>,
+    pub separator: Option<Hex>,
+    pub value: Option<Hex>,
+    pub logo: Option<Hex>,
+}
+
+/// What the logo shown next to the info is picked for
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogoSource {
+    #[default]
+    Distro,
+    De,
+    Wm,
+    Terminal,
+}
+
+/// Which variant of the logo is shown next to the info
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum LogoSize {
+    #[default]
+    Large,
+    /// The compact variant, named after the logo with `_small` added, if there is one
+    Small,
+    /// No logo at all, only the info
+    None,
+}
+
+/// Overrides for what mirafetch works out the terminal can do
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+pub struct TerminalQuirks {
+    pub truecolor: Option<bool>,
+    pub images: Option<bool>,
+    pub tmux_passthrough: Option<bool>,
+}
+
+/// Characters used to draw images in terminals without an image protocol
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageSymbols {
+    /// Two dots per cell with half blocks, which every font has
+    #[default]
+    Half,
+    /// Six dots per cell, which needs a font with Unicode 13 sextants
+    Sextant,
+}
+
+/// How often to look for a new release of mirafetch
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateInterval {
+    Daily,
+    Weekly,
+    Monthly,
+    Never,
+}
+
+impl UpdateInterval {
+    #[must_use]
+    pub const fn duration(self) -> Option<Duration> {
+        const DAY: u64 = 60 * 60 * 24;
+        match self {
+            Self::Daily => Some(Duration::from_secs(DAY)),
+            Self::Weekly => Some(Duration::from_secs(DAY * 7)),
+            Self::Monthly => Some(Duration::from_secs(DAY * 30)),
+            Self::Never => None,
+        }
+    }
+}
@@ -0,0 +1,377 @@
+use std::{
+    fs,
+    io::{stdout, ErrorKind, IsTerminal, Write},
+    path::Path,
+    process::ExitCode,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use arcstr::ArcStr;
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::{PrintStyledContent, Stylize},
+    terminal::{self, Clear, ClearType::All, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand, QueueableCommand,
+};
+use mirafetch::{
+    colorizer::{Colorizer, Default, Flag},
+    config::{Config, Orientation},
+    output,
+    render::queue_icon,
+    util::{colorscheme_names, get_colorscheme, AsciiArt},
+};
+use toml_edit::{value, Array, Document};
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    collect, config_path, filter_lines, logo_names, pick_logo, preset_modules, relabel, transform,
+    transliterate, Reloader,
+};
+
+/// Columns taken by the module list, left of the preview
+const LIST_WIDTH: u16 = 24;
+const HELP: &str = "↑↓ select  space toggle  J/K move  ←→ theme  o orientation  s save  q quit";
+/// How often to look for changes to the config file while waiting for keys
+const RELOAD_CHECK: Duration = Duration::from_millis(250);
+
+/// Choices being edited, starting from the current settings
+struct Editor {
+    /// Every module in the order it is shown, with whether it is shown
+    modules: Vec<(&'static str, bool)>,
+    cursor: usize,
+    themes: Vec<String>,
+    /// Index into `themes`, `None` for the logo's own colors
+    theme: Option<usize>,
+    orientation: Orientation,
+    /// Why the config file couldn't be reloaded after it last changed
+    notice: Option<String>,
+}
+
+impl Editor {
+    fn new(settings: &Config) -> Self {
+        let current = settings
+            .modules
+            .clone()
+            .unwrap_or_else(|| preset_modules(settings));
+        let known = |name: &str| output::MODULES.iter().find(|(x, ..)| *x == name);
+        let mut modules = current
+            .iter()
+            .filter_map(|name| known(name))
+            .map(|(name, ..)| (*name, true))
+            .collect::<Vec<_>>();
+        // Everything is shown when no modules are picked
+        let all = modules.is_empty();
+        for (name, ..) in output::MODULES {
+            if !modules.iter().any(|(x, _)| x == name) {
+                modules.push((name, all));
+            }
+        }
+        let themes = colorscheme_names();
+        let theme = settings
+            .scheme_name
+            .as_deref()
+            .and_then(|name| themes.iter().position(|x| x == name));
+        Self {
+            modules,
+            cursor: 0,
+            themes,
+            theme,
+            orientation: settings.orientation.unwrap_or(Orientation::Horizontal),
+            notice: None,
+        }
+    }
+
+    /// Start over from `settings`, keeping the selected row
+    fn reset(&mut self, settings: &Config) {
+        *self = Self {
+            cursor: self.cursor,
+            ..Self::new(settings)
+        };
+    }
+
+    fn shown(&self) -> Vec<String> {
+        self.modules
+            .iter()
+            .filter(|(_, shown)| *shown)
+            .map(|(name, _)| (*name).to_string())
+            .collect()
+    }
+
+    /// Handle a key press, returning whether to save when the editor should close
+    fn key(&mut self, key: KeyEvent) -> Option<bool> {
+        let last = self.modules.len() - 1;
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(false)
+            }
+            KeyCode::Char('s') | KeyCode::Enter => return Some(true),
+            KeyCode::Up if shift => self.reorder(false),
+            KeyCode::Down if shift => self.reorder(true),
+            KeyCode::Char('K') => self.reorder(false),
+            KeyCode::Char('J') => self.reorder(true),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor = (self.cursor + 1).min(last),
+            // Hiding every module would show them all, so one is always kept
+            KeyCode::Char(' ') => {
+                let shown = self.modules.iter().filter(|(_, x)| *x).count();
+                let (_, toggled) = &mut self.modules[self.cursor];
+                if !*toggled || shown > 1 {
+                    *toggled = !*toggled;
+                }
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.theme = self.theme.map_or_else(
+                    || self.themes.len().checked_sub(1),
+                    |idx| idx.checked_sub(1),
+                );
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.theme = self.theme.map_or(Some(0), |idx| {
+                    Some(idx + 1).filter(|x| *x < self.themes.len())
+                });
+            }
+            KeyCode::Char('o') => {
+                self.orientation = match self.orientation {
+                    Orientation::Horizontal => Orientation::Vertical,
+                    Orientation::Vertical => Orientation::Horizontal,
+                };
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Move the selected module one place down, or up
+    fn reorder(&mut self, down: bool) {
+        let target = if down {
+            self.cursor + 1
+        } else {
+            self.cursor.wrapping_sub(1)
+        };
+        if target < self.modules.len() {
+            self.modules.swap(self.cursor, target);
+            self.cursor = target;
+        }
+    }
+
+    fn draw(
+        &self,
+        frame: &mut Vec<u8>,
+        settings: &Config,
+        lines: &[(ArcStr, ArcStr)],
+        logo: &AsciiArt,
+    ) -> Result<()> {
+        let (columns, rows) = terminal::size()?;
+        let list_rows = usize::from(rows.saturating_sub(2)).max(1);
+        frame.clear();
+        frame
+            .queue(Clear(All))?
+            .queue(MoveTo(1, 0))?
+            .queue(PrintStyledContent("Modules".bold()))?;
+
+        // Scroll the list to keep the selected module in view
+        let first = self.cursor.saturating_sub(list_rows - 1);
+        for (row, (idx, (name, shown))) in self
+            .modules
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(list_rows)
+            .enumerate()
+        {
+            let check = if *shown { "x" } else { " " };
+            let line = format!(
+                " [{check}] {name:width$}",
+                width = usize::from(LIST_WIDTH) - 6
+            );
+            let line = if idx == self.cursor {
+                line.reverse()
+            } else {
+                line.stylize()
+            };
+            frame
+                .queue(MoveTo(0, u16::try_from(row + 1)?))?
+                .queue(PrintStyledContent(line))?;
+        }
+
+        let colorizer: Box<dyn Colorizer> = match self.theme {
+            Some(idx) => Box::new(Flag {
+                color_scheme: get_colorscheme(self.themes[idx].as_str())?,
+                orientation: self.orientation,
+            }),
+            None => Box::new(Default {}),
+        };
+        queue_icon(frame, colorizer.colorize(logo), LIST_WIDTH + 2, 1)?;
+        let info_column = LIST_WIDTH + 2 + logo.width + 3;
+        let info_width = usize::from(columns.saturating_sub(info_column));
+        let info = transliterate(
+            settings,
+            relabel(
+                settings,
+                transform(settings, filter_lines(lines.to_vec(), &self.shown())),
+            ),
+        );
+        for (row, (label, text)) in info.iter().take(list_rows).enumerate() {
+            frame
+                .queue(MoveTo(info_column, u16::try_from(row + 1)?))?
+                .queue(PrintStyledContent(clip(label, info_width).bold().red()))?;
+            // The palette lines are short, and their escape codes aren't counted
+            if label.is_empty() {
+                frame.queue(PrintStyledContent(text.as_str().reset()))?;
+            } else if !text.is_empty() {
+                let rest = info_width.saturating_sub(label.len() + 2);
+                frame
+                    .queue(PrintStyledContent(": ".bold().red()))?
+                    .queue(PrintStyledContent(clip(text, rest).reset()))?;
+            }
+        }
+
+        let theme = self.theme.map_or_else(
+            || String::from("Theme: none"),
+            |idx| format!("Theme: {} ({:?})", self.themes[idx], self.orientation),
+        );
+        let status = self.notice.as_ref().map_or_else(
+            || clip(&format!(" {theme}   {HELP}"), columns.into()).dark_grey(),
+            |notice| clip(&format!(" {notice}"), columns.into()).red(),
+        );
+        frame
+            .queue(MoveTo(0, rows.saturating_sub(1)))?
+            .queue(PrintStyledContent(status))?;
+        let mut out = stdout().lock();
+        out.write_all(frame)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Write the choices to the config file at `path`, keeping the rest of it as it was
+    fn save(&self, path: &Path) -> Result<()> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let mut config = text
+            .parse::<Document>()
+            .map_err(|err| anyhow!("Invalid config: {err}"))?;
+        config["modules"] = value(self.shown().into_iter().collect::<Array>());
+        if let Some(idx) = self.theme {
+            config["scheme_name"] = value(self.themes[idx].as_str());
+            config["orientation"] = value(format!("{:?}", self.orientation));
+        } else {
+            config.remove("scheme_name");
+            config.remove("orientation");
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, config.to_string())?;
+        Ok(())
+    }
+}
+
+/// The alternate screen in raw mode, left again when dropped
+struct Screen;
+
+impl Screen {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        stdout().execute(EnterAlternateScreen)?.execute(Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = stdout()
+            .execute(Show)
+            .and_then(|x| x.execute(LeaveAlternateScreen));
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// The first line of an error, as TOML errors go on to quote the config file
+fn first_line(err: &anyhow::Error) -> String {
+    err.to_string()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The first characters of `text` that fit in `width` columns
+fn clip(text: &str, width: usize) -> String {
+    let mut used = 0;
+    text.chars()
+        .take_while(|x| {
+            used += x.width().unwrap_or_default();
+            used <= width
+        })
+        .collect()
+}
+
+/// Pick the modules, their order and the theme with a preview of this machine's information
+///
+/// Saving the config file from elsewhere, such as an editor in another window, starts over from
+/// what it now says
+///
+/// # Errors
+///
+/// This function will return an error if stdout isn't a terminal or the config can't be saved
+pub fn run(mut settings: Config, mut reloader: Reloader) -> Result<ExitCode> {
+    if !stdout().is_terminal() {
+        return Err(anyhow!("mirafetch configure needs to run in a terminal"));
+    }
+    let path = config_path()?;
+    let info = collect(&settings, None, None)?;
+    let logo_for = |settings: &Config| {
+        let (id, source) = logo_names(settings, &info);
+        pick_logo(settings, &id, source.as_deref())
+    };
+    let mut logo = logo_for(&settings)?;
+    let lines = info.clone().as_vec();
+
+    let mut editor = Editor::new(&settings);
+    let mut frame = Vec::new();
+    let save = {
+        let _screen = Screen::enter()?;
+        let mut redraw = true;
+        loop {
+            if redraw {
+                editor.draw(&mut frame, &settings, &lines, &logo)?;
+            }
+            redraw = true;
+            if !event::poll(RELOAD_CHECK)? {
+                match reloader.reload() {
+                    Some(Ok(changed)) => match logo_for(&changed) {
+                        Ok(changed_logo) => {
+                            editor.reset(&changed);
+                            settings = changed;
+                            logo = changed_logo;
+                        }
+                        Err(err) => editor.notice = Some(first_line(&err)),
+                    },
+                    Some(Err(err)) => editor.notice = Some(first_line(&err)),
+                    None => redraw = false,
+                }
+                continue;
+            }
+            match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    if let Some(save) = editor.key(key) {
+                        break save;
+                    }
+                }
+                _ => {}
+            }
+        }
+    };
+    if save {
+        editor.save(&path)?;
+        println!("Saved to {}", path.display());
+    }
+    Ok(ExitCode::SUCCESS)
+}
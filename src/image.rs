@@ -0,0 +1,437 @@
+use std::{fmt::Write, io::Cursor, path::Path};
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{imageops::FilterType, DynamicImage, ImageOutputFormat, RgbImage};
+
+use crate::{
+    config::ImageSymbols,
+    quirks::{distance, nearest_ansi, Quirks},
+};
+
+/// Graphics protocols that can draw an image in the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Kitty,
+    Iterm2,
+    /// DEC sixels, with the image dithered to the 256 color palette
+    Sixel,
+    /// Unicode block characters, which every terminal can show
+    Blocks(ImageSymbols),
+}
+
+impl Protocol {
+    /// Guess the image protocol supported by the running terminal from its environment
+    ///
+    /// Sixel support is also asked from the terminal itself, as many terminals that have it
+    /// can't be told apart by their environment
+    #[must_use]
+    pub fn detect() -> Option<Self> {
+        let var = |x| std::env::var(x).unwrap_or_default();
+        let term = var("TERM");
+        if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || matches!(var("TERM_PROGRAM").as_str(), "WezTerm" | "ghostty")
+        {
+            Some(Self::Kitty)
+        } else if var("TERM_PROGRAM") == "iTerm.app" || var("LC_TERMINAL") == "iTerm2" {
+            Some(Self::Iterm2)
+        } else if ["foot", "mlterm", "yaft", "contour"]
+            .iter()
+            .any(|x| term.starts_with(x))
+            || sixel_attribute()
+        {
+            Some(Self::Sixel)
+        } else {
+            None
+        }
+    }
+}
+
+/// An image logo, scaled to exactly fill a whole number of terminal rows
+pub struct ImageLogo {
+    /// Escape sequence drawing the image without moving the cursor
+    pub escape: String,
+    pub columns: u16,
+    pub rows: u16,
+}
+
+/// Width and height of a terminal cell in pixels
+///
+/// Uses the pixel size reported by the tty driver, which terminals with image support
+/// usually fill in, and otherwise asks the terminal with `CSI 16 t`
+#[must_use]
+pub fn cell_size() -> Option<(u32, u32)> {
+    #[cfg(unix)]
+    return winsize_cell_size().or_else(query_cell_size);
+    #[cfg(not(unix))]
+    None
+}
+
+#[cfg(unix)]
+fn winsize_cell_size() -> Option<(u32, u32)> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } != 0
+        || size.ws_col == 0
+        || size.ws_row == 0
+        || size.ws_xpixel == 0
+        || size.ws_ypixel == 0
+    {
+        return None;
+    }
+    Some((
+        u32::from(size.ws_xpixel / size.ws_col),
+        u32::from(size.ws_ypixel / size.ws_row),
+    ))
+}
+
+#[cfg(unix)]
+fn query_cell_size() -> Option<(u32, u32)> {
+    // The reply is CSI 6 ; height ; width t
+    let reply = query_terminal(b"\x1b[16t", b't')?;
+    let (height, width) = reply
+        .strip_prefix("\x1b[6;")?
+        .strip_suffix('t')?
+        .split_once(';')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+/// Whether the terminal lists sixel graphics (attribute 4) in its primary device attributes
+fn sixel_attribute() -> bool {
+    // The reply is CSI ? class ; attributes... c
+    #[cfg(unix)]
+    return query_terminal(b"\x1b[c", b'c').is_some_and(|reply| {
+        reply
+            .strip_prefix("\x1b[?")
+            .and_then(|x| x.strip_suffix('c'))
+            .is_some_and(|x| x.split(';').skip(1).any(|x| x == "4"))
+    });
+    #[cfg(not(unix))]
+    false
+}
+
+/// Send `request` to the terminal and read its reply up to the `end` byte, waiting at most
+/// 100ms for each part of it
+#[cfg(unix)]
+#[allow(clippy::cast_sign_loss)]
+fn query_terminal(request: &[u8], end: u8) -> Option<String> {
+    use std::io::Write;
+
+    if unsafe { libc::isatty(libc::STDIN_FILENO) } != 1 {
+        return None;
+    }
+    crossterm::terminal::enable_raw_mode().ok()?;
+    let res = (|| {
+        let mut out = std::io::stdout();
+        out.write_all(request).ok()?;
+        out.flush().ok()?;
+        let mut buf = [0u8; 64];
+        let mut len = 0;
+        while !buf[..len].contains(&end) && len < buf.len() {
+            let mut fd = libc::pollfd {
+                fd: libc::STDIN_FILENO,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            if unsafe { libc::poll(std::ptr::addr_of_mut!(fd), 1, 100) } <= 0 {
+                return None;
+            }
+            let read = unsafe {
+                libc::read(
+                    libc::STDIN_FILENO,
+                    buf[len..].as_mut_ptr().cast(),
+                    buf.len() - len,
+                )
+            };
+            if read <= 0 {
+                return None;
+            }
+            len += read as usize;
+        }
+        std::str::from_utf8(&buf[..len]).ok().map(String::from)
+    })();
+    let _ = crossterm::terminal::disable_raw_mode();
+    res
+}
+
+/// Load the image at `path`, scaled to be exactly `rows` rows tall
+///
+/// Without a known cell size the image protocols can't be scaled to whole cells,
+/// so the image is drawn with block characters instead. Block characters only use 24-bit
+/// colors when `quirks` says the terminal has them
+///
+/// # Errors
+///
+/// This function will return an error if the image cannot be read or decoded
+pub fn load(path: &Path, rows: u16, protocol: Protocol, quirks: Quirks) -> Result<ImageLogo> {
+    let image = image::open(path)?;
+    if image.height() == 0 {
+        return Err(anyhow!("{} is empty", path.display()));
+    }
+    let cell = cell_size();
+    let (escape, columns) = match (protocol, cell) {
+        (Protocol::Blocks(symbols), _) => blocks(&image, rows, symbols, cell, quirks.truecolor),
+        (_, None) => blocks(
+            &image,
+            rows,
+            ImageSymbols::default(),
+            None,
+            quirks.truecolor,
+        ),
+        (_, Some(cell)) => {
+            let (escape, columns) = graphics(&image, rows, protocol, cell)?;
+            (quirks.passthrough(&escape), columns)
+        }
+    };
+    Ok(ImageLogo {
+        escape,
+        columns,
+        rows,
+    })
+}
+
+/// Draw the image with the kitty, iTerm2 or sixel protocol
+///
+/// Scaling to whole cells here rather than letting the terminal do it keeps the
+/// info column from landing a cell off
+fn graphics(
+    image: &DynamicImage,
+    rows: u16,
+    protocol: Protocol,
+    (cell_width, cell_height): (u32, u32),
+) -> Result<(String, u16)> {
+    let height = u32::from(rows) * cell_height;
+    let width = (u64::from(image.width()) * u64::from(height) / u64::from(image.height()))
+        .try_into()
+        .unwrap_or(u32::MAX)
+        .max(1);
+    let image = image.resize_exact(width, height, FilterType::Lanczos3);
+    let columns = u16::try_from(width.div_ceil(cell_width)).unwrap_or(u16::MAX);
+
+    let escape = if protocol == Protocol::Sixel {
+        sixel(&image)?
+    } else if protocol == Protocol::Iterm2 {
+        let mut png = Vec::new();
+        image.write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)?;
+        format!(
+            "\x1b]1337;File=inline=1;size={};width={width}px;height={height}px;doNotMoveCursor=1:{}\x07",
+            png.len(),
+            STANDARD.encode(&png)
+        )
+    } else {
+        // Sent as raw RGBA in chunks of at most 4096 base64 bytes
+        let data = STANDARD.encode(image.to_rgba8().as_raw());
+        let chunks = data.as_bytes().chunks(4096).collect::<Vec<_>>();
+        let mut escape = String::with_capacity(data.len() + chunks.len() * 16 + 64);
+        for (idx, chunk) in chunks.iter().enumerate() {
+            let more = u8::from(idx + 1 < chunks.len());
+            if idx == 0 {
+                write!(
+                    escape,
+                    "\x1b_Ga=T,f=32,s={width},v={height},C=1,q=2,m={more};"
+                )?;
+            } else {
+                write!(escape, "\x1b_Gm={more};")?;
+            }
+            escape += std::str::from_utf8(chunk)?;
+            escape += "\x1b\\";
+        }
+        escape
+    };
+    Ok((escape, columns))
+}
+
+/// Encode the image as sixels, leaving pixels that are mostly transparent undrawn
+///
+/// Each band of six pixel rows is drawn once per palette color in it, with runs of the same
+/// sixel shortened to a repeat count
+fn sixel(image: &DynamicImage) -> Result<String> {
+    let rgba = image.to_rgba8();
+    let width = rgba.width() as usize;
+    let height = rgba.height() as usize;
+    let pixels = dither(&image.to_rgb8());
+    let visible = rgba.pixels().map(|x| x.0[3] >= 128).collect::<Vec<_>>();
+
+    let mut escape = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+    let mut palette = [None; 256];
+    for ((idx, rgb), _) in pixels.iter().zip(&visible).filter(|(_, x)| **x) {
+        palette[usize::from(*idx)] = Some(*rgb);
+    }
+    for (idx, rgb) in palette.iter().enumerate() {
+        if let Some(rgb) = rgb {
+            // Color registers take percentages
+            let [r, g, b] = rgb.map(|x| u32::from(x) * 100 / 255);
+            write!(escape, "#{idx};2;{r};{g};{b}")?;
+        }
+    }
+    let flush = |escape: &mut String, sixel: u8, count: usize| -> Result<()> {
+        let sixel = char::from(63 + sixel);
+        match count {
+            0 => {}
+            1..=3 => escape.extend(std::iter::repeat_n(sixel, count)),
+            _ => write!(escape, "!{count}{sixel}")?,
+        }
+        Ok(())
+    };
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let mut colors = rows
+            .clone()
+            .flat_map(|y| y * width..(y + 1) * width)
+            .filter(|x| visible[*x])
+            .map(|x| pixels[x].0)
+            .collect::<Vec<_>>();
+        colors.sort_unstable();
+        colors.dedup();
+        for color in colors {
+            write!(escape, "#{color}")?;
+            let (mut run, mut count) = (0, 0);
+            for x in 0..width {
+                let sixel = rows
+                    .clone()
+                    .enumerate()
+                    .filter(|(_, y)| visible[y * width + x] && pixels[y * width + x].0 == color)
+                    .fold(0, |acc, (bit, _)| acc | 1 << bit);
+                if sixel != run {
+                    flush(&mut escape, run, count)?;
+                    (run, count) = (sixel, 0);
+                }
+                count += 1;
+            }
+            // Trailing empty sixels don't need drawing
+            if run != 0 {
+                flush(&mut escape, run, count)?;
+            }
+            escape.push('$');
+        }
+        escape.push('-');
+    }
+    escape += "\x1b\\";
+    Ok(escape)
+}
+
+/// Draw the image with Unicode block characters, each cell showing two colors
+///
+/// Uses 24-bit colors if `truecolor` is set, and otherwise dithers the image to the 256 color
+/// palette
+fn blocks(
+    image: &DynamicImage,
+    rows: u16,
+    symbols: ImageSymbols,
+    cell: Option<(u32, u32)>,
+    truecolor: bool,
+) -> (String, u16) {
+    let (cell_width, cell_height) = cell.unwrap_or((1, 2));
+    let (dots_x, dots_y) = match symbols {
+        ImageSymbols::Half => (1, 2),
+        ImageSymbols::Sextant => (2, 3),
+    };
+    let columns = (u64::from(image.width()) * u64::from(rows) * u64::from(cell_height)
+        / (u64::from(image.height()) * u64::from(cell_width)))
+    .clamp(1, u16::MAX.into());
+    let columns = u16::try_from(columns).unwrap_or(u16::MAX);
+    let width = usize::from(columns) * dots_x;
+    let image = image
+        .resize_exact(
+            u32::from(columns) * u32::try_from(dots_x).unwrap_or(1),
+            u32::from(rows) * u32::try_from(dots_y).unwrap_or(1),
+            FilterType::Triangle,
+        )
+        .to_rgb8();
+
+    let pixels = if truecolor {
+        image.pixels().map(|x| (0, x.0)).collect()
+    } else {
+        dither(&image)
+    };
+    let sgr = |layer: u8, (idx, [r, g, b]): (u8, [u8; 3])| {
+        if truecolor {
+            format!("\x1b[{layer};2;{r};{g};{b}m")
+        } else {
+            format!("\x1b[{layer};5;{idx}m")
+        }
+    };
+    let luma = |(_, [r, g, b]): (u8, [u8; 3])| {
+        299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)
+    };
+
+    let mut escape = String::new();
+    for row in 0..usize::from(rows) {
+        for column in 0..usize::from(columns) {
+            let dots = (0..dots_y)
+                .flat_map(|y| (0..dots_x).map(move |x| (x, y)))
+                .map(|(x, y)| pixels[(row * dots_y + y) * width + column * dots_x + x])
+                .collect::<Vec<_>>();
+            let (fg, bg, symbol) = match symbols {
+                ImageSymbols::Half => (dots[0], dots[1], '▀'),
+                ImageSymbols::Sextant => {
+                    // Split the six dots between the brightest and darkest of them
+                    let fg = *dots.iter().max_by_key(|x| luma(**x)).unwrap_or(&dots[0]);
+                    let bg = *dots.iter().min_by_key(|x| luma(**x)).unwrap_or(&dots[0]);
+                    let pattern = dots
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, x)| distance(x.1, fg.1) < distance(x.1, bg.1))
+                        .fold(0, |acc, (idx, _)| acc | 1 << idx);
+                    (fg, bg, sextant(pattern))
+                }
+            };
+            escape += &sgr(38, fg);
+            escape += &sgr(48, bg);
+            escape.push(symbol);
+        }
+        escape += "\x1b[0m\n";
+    }
+    (escape, columns)
+}
+
+/// Sextant character with the dots set in `pattern`, bit 0 being the top left dot
+fn sextant(pattern: u32) -> char {
+    match pattern {
+        0 => ' ',
+        0b01_0101 => '▌',
+        0b10_1010 => '▐',
+        0b11_1111 => '█',
+        // Block Sextants skip the patterns that already have a half block character
+        x => char::from_u32(0x1FB00 + x - 1 - u32::from(x > 0b01_0101) - u32::from(x > 0b10_1010))
+            .unwrap_or(' '),
+    }
+}
+
+/// Floyd-Steinberg dither `image` to the 256 color palette
+fn dither(image: &RgbImage) -> Vec<(u8, [u8; 3])> {
+    let width = image.width() as usize;
+    let mut buf = image
+        .pixels()
+        .map(|x| x.0.map(f32::from))
+        .collect::<Vec<_>>();
+    let mut res = Vec::with_capacity(buf.len());
+    for idx in 0..buf.len() {
+        let old = buf[idx];
+        let (color, new) = nearest_ansi(old);
+        res.push((color, new));
+        let err = [0, 1, 2].map(|x| old[x] - f32::from(new[x]));
+        let x = idx % width;
+        let mut spread = |idx: usize, weight: f32| {
+            if let Some(pixel) = buf.get_mut(idx) {
+                for (value, err) in pixel.iter_mut().zip(err) {
+                    *value += err * weight;
+                }
+            }
+        };
+        if x + 1 < width {
+            spread(idx + 1, 7.0 / 16.0);
+            spread(idx + width + 1, 1.0 / 16.0);
+        }
+        if x > 0 {
+            spread(idx + width - 1, 3.0 / 16.0);
+        }
+        spread(idx + width, 5.0 / 16.0);
+    }
+    res
+}
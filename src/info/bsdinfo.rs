@@ -0,0 +1,436 @@
+#![cfg(any(
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd"
+))]
+use std::{
+    ffi::{CStr, CString},
+    mem,
+    net::{Ipv4Addr, Ipv6Addr},
+    path::Path,
+    ptr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use arcstr::ArcStr;
+use itertools::Itertools;
+use libc::{c_int, AF_INET, AF_INET6, IFF_LOOPBACK, IFF_RUNNING};
+use platform_info::{PlatformInfo, PlatformInfoAPI, UNameAPI};
+use rustc_hash::FxHashSet;
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+use crate::info::sqlite;
+use crate::{
+    config::IpVersion,
+    info::{
+        dotfiles, ip_addresses,
+        values::{Cpu, DiskUsage, Memory},
+        OSInfo,
+    },
+    util::{bytecount_format, uptime_format},
+};
+
+pub struct BsdInfo {
+    uts: Option<PlatformInfo>,
+}
+
+impl Default for BsdInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BsdInfo {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            uts: PlatformInfo::new().ok(),
+        }
+    }
+}
+
+/// Read a fixed size value with `sysctl`, e.g. `[CTL_HW, HW_NCPU]`
+fn sysctl<T: Copy>(mib: &[c_int]) -> Option<T> {
+    let mut value = mem::MaybeUninit::<T>::uninit();
+    let mut len = mem::size_of::<T>();
+    let res = unsafe {
+        libc::sysctl(
+            mib.as_ptr(),
+            mib.len().try_into().ok()?,
+            value.as_mut_ptr().cast(),
+            &raw mut len,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    // SAFETY: the kernel filled in the whole value
+    (res == 0 && len == mem::size_of::<T>()).then(|| unsafe { value.assume_init() })
+}
+
+/// Read a string with `sysctl`, e.g. `[CTL_HW, HW_MODEL]`
+#[cfg(not(target_os = "netbsd"))]
+fn sysctl_string(mib: &[c_int]) -> Option<String> {
+    let namelen = mib.len().try_into().ok()?;
+    let mut len = 0;
+    let mut buf = unsafe {
+        if libc::sysctl(
+            mib.as_ptr(),
+            namelen,
+            ptr::null_mut(),
+            &raw mut len,
+            ptr::null_mut(),
+            0,
+        ) != 0
+        {
+            return None;
+        }
+        let mut buf = vec![0u8; len];
+        if libc::sysctl(
+            mib.as_ptr(),
+            namelen,
+            buf.as_mut_ptr().cast(),
+            &raw mut len,
+            ptr::null_mut(),
+            0,
+        ) != 0
+        {
+            return None;
+        }
+        buf
+    };
+    buf.truncate(len);
+    let value = CStr::from_bytes_until_nul(&buf).ok()?.to_str().ok()?.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Read a fixed size value with `sysctlbyname`, for values without a MIB constant
+#[cfg(not(target_os = "openbsd"))]
+fn sysctl_by_name<T: Copy>(name: &str) -> Option<T> {
+    let name = CString::new(name).ok()?;
+    let mut value = mem::MaybeUninit::<T>::uninit();
+    let mut len = mem::size_of::<T>();
+    let res = unsafe {
+        libc::sysctlbyname(
+            name.as_ptr(),
+            value.as_mut_ptr().cast(),
+            &raw mut len,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    // SAFETY: the kernel filled in the whole value
+    (res == 0 && len == mem::size_of::<T>()).then(|| unsafe { value.assume_init() })
+}
+
+/// Read a string with `sysctlbyname`, for NetBSD, which has no MIB constants for some strings
+#[cfg(target_os = "netbsd")]
+fn sysctl_string_by_name(name: &str) -> Option<String> {
+    let name = CString::new(name).ok()?;
+    let mut len = 0;
+    let mut buf = unsafe {
+        if libc::sysctlbyname(
+            name.as_ptr(),
+            ptr::null_mut(),
+            &raw mut len,
+            ptr::null_mut(),
+            0,
+        ) != 0
+        {
+            return None;
+        }
+        let mut buf = vec![0u8; len];
+        if libc::sysctlbyname(
+            name.as_ptr(),
+            buf.as_mut_ptr().cast(),
+            &raw mut len,
+            ptr::null_mut(),
+            0,
+        ) != 0
+        {
+            return None;
+        }
+        buf
+    };
+    buf.truncate(len);
+    let value = CStr::from_bytes_until_nul(&buf).ok()?.to_str().ok()?.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+// `c_ulong` is only `u64` on 64-bit targets
+#[allow(clippy::useless_conversion)]
+fn physical_memory() -> Option<u64> {
+    sysctl::<libc::c_ulong>(&[libc::CTL_HW, libc::HW_PHYSMEM]).map(u64::from)
+}
+
+#[cfg(target_os = "openbsd")]
+fn physical_memory() -> Option<u64> {
+    sysctl::<i64>(&[libc::CTL_HW, libc::HW_PHYSMEM64]).and_then(|x| u64::try_from(x).ok())
+}
+
+#[cfg(target_os = "netbsd")]
+fn physical_memory() -> Option<u64> {
+    sysctl_by_name::<u64>("hw.physmem64")
+}
+
+/// Pages that are free or can be reclaimed right away
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+fn free_pages() -> Option<u64> {
+    let free = sysctl_by_name::<u32>("vm.stats.vm.v_free_count")?;
+    // The page cache was folded into the free pages in FreeBSD 12
+    let reclaimable = ["vm.stats.vm.v_inactive_count", "vm.stats.vm.v_cache_count"]
+        .into_iter()
+        .filter_map(sysctl_by_name::<u32>)
+        .map(u64::from)
+        .sum::<u64>();
+    Some(u64::from(free) + reclaimable)
+}
+
+#[cfg(target_os = "openbsd")]
+fn free_pages() -> Option<u64> {
+    let uvmexp = sysctl::<libc::uvmexp>(&[libc::CTL_VM, libc::VM_UVMEXP])?;
+    u64::try_from(uvmexp.free).ok()
+}
+
+// NetBSD only reports its page counts in a struct that changes between releases
+#[cfg(target_os = "netbsd")]
+const fn free_pages() -> Option<u64> {
+    None
+}
+
+/// Product name from the firmware
+#[cfg(target_os = "freebsd")]
+fn product_name() -> Option<String> {
+    let name = CString::new("smbios.system.product").ok()?;
+    let mut buf = [0u8; 128];
+    let len = unsafe {
+        libc::kenv(
+            libc::KENV_GET,
+            name.as_ptr(),
+            buf.as_mut_ptr().cast(),
+            buf.len().try_into().ok()?,
+        )
+    };
+    if len <= 0 {
+        return None;
+    }
+    let value = CStr::from_bytes_until_nul(&buf).ok()?.to_str().ok()?.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(target_os = "dragonfly")]
+const fn product_name() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "openbsd")]
+fn product_name() -> Option<String> {
+    sysctl_string(&[libc::CTL_HW, libc::HW_PRODUCT])
+}
+
+#[cfg(target_os = "netbsd")]
+fn product_name() -> Option<String> {
+    sysctl_string_by_name("machdep.dmi.system-product")
+}
+
+/// Entries of `dir` that are directories, one per installed package
+#[cfg(any(target_os = "openbsd", target_os = "netbsd"))]
+fn count_dirs(dir: &Path) -> Option<u64> {
+    let entries = std::fs::read_dir(dir).ok()?;
+    Some(
+        entries
+            .filter_map(Result::ok)
+            .filter(|x| x.file_type().is_ok_and(|x| x.is_dir()))
+            .count() as u64,
+    )
+}
+
+impl OSInfo for BsdInfo {
+    fn os(&self) -> Option<ArcStr> {
+        let uts = self.uts.as_ref()?;
+        Some(arcstr::format!(
+            "{} {} {}",
+            uts.sysname().to_str()?,
+            uts.release().to_str()?,
+            uts.machine().to_str()?
+        ))
+    }
+
+    fn id(&self) -> ArcStr {
+        self.uts.as_ref().map_or(arcstr::literal!("bsd"), |uts| {
+            ArcStr::from(uts.sysname().to_string_lossy().to_ascii_lowercase())
+        })
+    }
+
+    fn hostname(&self) -> Option<ArcStr> {
+        Some(ArcStr::from(self.uts.as_ref()?.nodename().to_str()?))
+    }
+
+    fn username(&self) -> Option<ArcStr> {
+        unsafe {
+            let pwd = libc::getpwuid(libc::getuid());
+            if pwd.is_null() {
+                return None;
+            }
+            CStr::from_ptr((*pwd).pw_name)
+                .to_str()
+                .ok()
+                .map(ArcStr::from)
+        }
+    }
+
+    fn kernel(&self) -> Option<ArcStr> {
+        Some(ArcStr::from(self.uts.as_ref()?.version().to_str()?.trim()))
+    }
+
+    fn machine(&self) -> Option<ArcStr> {
+        product_name().map(ArcStr::from)
+    }
+
+    fn uptime(&self) -> Option<ArcStr> {
+        let boot = sysctl::<libc::timeval>(&[libc::CTL_KERN, libc::KERN_BOOTTIME])?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let uptime = now.checked_sub(u64::try_from(boot.tv_sec).ok()?)?;
+        Some(ArcStr::from(uptime_format(uptime)))
+    }
+
+    fn shell(&self) -> Option<ArcStr> {
+        let shell = std::env::var("SHELL").ok()?;
+        Some(ArcStr::from(shell.rsplit('/').next()?))
+    }
+
+    fn shell_framework(&self) -> Option<ArcStr> {
+        dotfiles::framework(self.shell().as_deref()).map(ArcStr::from)
+    }
+
+    fn xdg(&self) -> Option<ArcStr> {
+        dotfiles::xdg_overrides().map(ArcStr::from)
+    }
+
+    fn cpu(&self) -> Option<Cpu> {
+        #[cfg(not(target_os = "netbsd"))]
+        let model = sysctl_string(&[libc::CTL_HW, libc::HW_MODEL])?;
+        #[cfg(target_os = "netbsd")]
+        let model = sysctl_string_by_name("hw.model")?;
+        Some(Cpu {
+            cores: sysctl::<c_int>(&[libc::CTL_HW, libc::HW_NCPU]).map(f64::from),
+            ..Cpu::new(model)
+        })
+    }
+
+    fn memory(&self) -> Option<Memory> {
+        let total = physical_memory()?;
+        let page_size = u64::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).ok()?;
+        Some(Memory {
+            used: total.saturating_sub(free_pages()? * page_size),
+            total,
+            cgroup: false,
+        })
+    }
+
+    // The statvfs fields are `u64` on some of the BSDs and smaller types on others
+    #[allow(clippy::useless_conversion)]
+    fn disks(&self) -> Vec<(ArcStr, DiskUsage)> {
+        let mut stat = mem::MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: statvfs fills in the struct on success
+        let stat = unsafe {
+            if libc::statvfs(c"/".as_ptr(), stat.as_mut_ptr()) != 0 {
+                return Vec::new();
+            }
+            stat.assume_init()
+        };
+        let block_size = u64::from(stat.f_frsize);
+        let total = u64::from(stat.f_blocks) * block_size;
+        let used = u64::from(stat.f_blocks).saturating_sub(u64::from(stat.f_bfree)) * block_size;
+        vec![(arcstr::literal!("Disk (/)"), DiskUsage { used, total })]
+    }
+
+    fn ip(&self, version: IpVersion) -> Vec<(ArcStr, ArcStr)> {
+        let mut ipv4_addrs = FxHashSet::<(ArcStr, Ipv4Addr)>::default();
+        let mut ipv6_addrs = FxHashSet::<(ArcStr, Ipv6Addr)>::default();
+        unsafe {
+            let mut addrs = ptr::null_mut();
+            if libc::getifaddrs(&raw mut addrs) != 0 {
+                return Vec::new();
+            }
+            let mut current = addrs.cast_const();
+            while let Some(addr) = current.as_ref() {
+                current = addr.ifa_next;
+                if addr.ifa_addr.is_null()
+                    || addr.ifa_flags & IFF_RUNNING as u32 == 0
+                    || addr.ifa_flags & IFF_LOOPBACK as u32 != 0
+                {
+                    continue;
+                }
+                let name = ArcStr::from(CStr::from_ptr(addr.ifa_name).to_string_lossy());
+                match c_int::from((*addr.ifa_addr).sa_family) {
+                    AF_INET => {
+                        #[allow(clippy::cast_ptr_alignment)]
+                        let ipv4 = (*(addr.ifa_addr).cast::<libc::sockaddr_in>())
+                            .sin_addr
+                            .s_addr;
+                        ipv4_addrs.insert((name.clone(), Ipv4Addr::from(u32::from_be(ipv4))));
+                    }
+                    AF_INET6 => {
+                        #[allow(clippy::cast_ptr_alignment)]
+                        let ipv6 = (*(addr.ifa_addr).cast::<libc::sockaddr_in6>())
+                            .sin6_addr
+                            .s6_addr;
+                        ipv6_addrs.insert((name, Ipv6Addr::from(ipv6)));
+                    }
+                    _ => {}
+                }
+            }
+            libc::freeifaddrs(addrs);
+        }
+
+        ip_addresses(version, ipv4_addrs, ipv6_addrs)
+    }
+
+    fn packages(&self) -> Option<ArcStr> {
+        // pkg keeps its database in sqlite, pkg_add and pkgsrc a directory per package
+        #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+        let counts = [(
+            "pkg",
+            sqlite::count_rows(Path::new("/var/db/pkg/local.sqlite"), "packages"),
+        )];
+        #[cfg(target_os = "openbsd")]
+        let counts = [("pkg_add", count_dirs(Path::new("/var/db/pkg")))];
+        #[cfg(target_os = "netbsd")]
+        let counts = [(
+            "pkgsrc",
+            count_dirs(Path::new("/usr/pkg/pkgdb"))
+                .or_else(|| count_dirs(Path::new("/var/db/pkg"))),
+        )];
+        let res = counts
+            .into_iter()
+            .filter_map(|(name, count)| Some((name, count.filter(|x| *x > 0)?)))
+            .map(|(name, count)| format!("{count} ({name})"))
+            .join(", ");
+        (!res.is_empty()).then(|| ArcStr::from(res))
+    }
+
+    fn locale(&self) -> Option<ArcStr> {
+        std::env::var("LANG")
+            .ok()
+            .filter(|x| !x.is_empty())
+            .or_else(|| std::env::var("LC_ALL").ok().filter(|x| !x.is_empty()))
+            .or_else(|| std::env::var("LC_MESSAGES").ok().filter(|x| !x.is_empty()))
+            .map(ArcStr::from)
+    }
+
+    fn arch(&self) -> Option<ArcStr> {
+        let mut res = vec![self.uts.as_ref()?.machine().to_str()?.to_string()];
+        res.push(String::from(if cfg!(target_endian = "big") {
+            "big-endian"
+        } else {
+            "little-endian"
+        }));
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if let Ok(page_size) = u64::try_from(page_size) {
+            res.push(format!("{} pages", bytecount_format(page_size, 0)));
+        }
+        Some(ArcStr::from(res.join(", ")))
+    }
+}
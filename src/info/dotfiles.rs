@@ -0,0 +1,83 @@
+#![cfg(unix)]
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+/// Frameworks and prompts that export a variable once they're loaded, with a file in the
+/// directory it names when the variable is too generic to go by alone
+const EXPORTED: &[(&str, &str, Option<&str>)] = &[
+    ("oh-my-zsh", "ZSH", Some("oh-my-zsh.sh")),
+    ("oh-my-bash", "OSH", Some("oh-my-bash.sh")),
+    ("bash-it", "BASH_IT", Some("bash_it.sh")),
+    ("oh-my-fish", "OMF_PATH", None),
+    ("starship", "STARSHIP_SHELL", None),
+    ("oh-my-posh", "POSH_THEME", None),
+];
+
+/// XDG base directories and their defaults relative to the home directory
+const XDG_DIRS: &[(&str, &str, &str)] = &[
+    ("config", "XDG_CONFIG_HOME", ".config"),
+    ("data", "XDG_DATA_HOME", ".local/share"),
+    ("state", "XDG_STATE_HOME", ".local/state"),
+    ("cache", "XDG_CACHE_HOME", ".cache"),
+];
+
+fn home() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+/// Shell frameworks, plugin managers and prompts in use, e.g. `oh-my-zsh, starship`
+///
+/// Managers that don't export anything are found from their files, and only for `shell`
+#[must_use]
+pub fn framework(shell: Option<&str>) -> Option<String> {
+    let mut res = EXPORTED
+        .iter()
+        .filter(|(_, var, file)| {
+            env::var_os(var)
+                .is_some_and(|dir| file.is_none_or(|x| Path::new(&dir).join(x).exists()))
+        })
+        .map(|(name, ..)| *name)
+        .collect::<Vec<_>>();
+
+    let home = home()?;
+    let config = env::var_os("XDG_CONFIG_HOME").map_or_else(|| home.join(".config"), PathBuf::from);
+    let zdotdir = env::var_os("ZDOTDIR").map_or_else(|| home.clone(), PathBuf::from);
+    let installed = match shell {
+        Some("zsh") => vec![
+            ("prezto", zdotdir.join(".zprezto/init.zsh")),
+            ("zinit", home.join(".local/share/zinit/zinit.git/zinit.zsh")),
+            ("antidote", zdotdir.join(".antidote/antidote.zsh")),
+        ],
+        Some("fish") => vec![("fisher", config.join("fish/functions/fisher.fish"))],
+        _ => Vec::new(),
+    };
+    res.extend(
+        installed
+            .into_iter()
+            .filter(|(_, path)| path.exists())
+            .map(|(name, _)| name),
+    );
+    (!res.is_empty()).then(|| res.join(", "))
+}
+
+/// XDG base directories moved away from their defaults, e.g. `config ~/dotfiles/config`
+#[must_use]
+pub fn xdg_overrides() -> Option<String> {
+    let home = home()?;
+    let res = XDG_DIRS
+        .iter()
+        .filter_map(|(name, var, default)| {
+            let dir = PathBuf::from(env::var_os(var).filter(|x| !x.is_empty())?);
+            if dir == home.join(default) {
+                return None;
+            }
+            Some(dir.strip_prefix(&home).map_or_else(
+                |_| format!("{name} {}", dir.display()),
+                |rest| format!("{name} ~/{}", rest.display()),
+            ))
+        })
+        .collect::<Vec<_>>();
+    (!res.is_empty()).then(|| res.join(", "))
+}
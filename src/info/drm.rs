@@ -0,0 +1,144 @@
+#![cfg(target_os = "linux")]
+use std::{
+    fs::File,
+    os::fd::{AsFd, BorrowedFd},
+    path::PathBuf,
+};
+
+use drm::control::{connector, property, Device as ControlDevice};
+use glob::glob;
+use rustc_hash::FxHashMap;
+
+use crate::info::sysfs;
+
+struct Card(File);
+
+impl AsFd for Card {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+impl drm::Device for Card {}
+impl ControlDevice for Card {}
+
+/// Color depth advertised in an EDID 1.4 base block
+fn edid_bit_depth(edid: &[u8]) -> Option<u64> {
+    // Byte 20 describes the video input, digital inputs carry the depth in bits 6-4
+    if edid.get(18..20)? != [1, 4] || edid.get(20)? & 0x80 == 0 {
+        return None;
+    }
+    match (edid[20] >> 4) & 0b111 {
+        0 | 7 => None,
+        x => Some(4 + 2 * u64::from(x)),
+    }
+}
+
+/// The open DRM cards with their path, e.g. `/dev/dri/card0`
+fn cards() -> impl Iterator<Item = (PathBuf, Card)> {
+    glob("/dev/dri/card*")
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|path| Some((path.clone(), File::open(path).map(Card).ok()?)))
+}
+
+/// Connectors of `card` with a monitor, with their name, e.g. `DP-1`
+fn connected(card: &Card) -> Vec<(connector::Handle, connector::Info, String)> {
+    let Ok(handles) = card.resource_handles() else {
+        return Vec::new();
+    };
+    handles
+        .connectors()
+        .iter()
+        .filter_map(|handle| Some((*handle, card.get_connector(*handle, false).ok()?)))
+        .filter(|(_, info)| info.state() == connector::State::Connected)
+        .map(|(handle, info)| {
+            let name = format!("{}-{}", info.interface().as_str(), info.interface_id());
+            (handle, info, name)
+        })
+        .collect()
+}
+
+/// Size and refresh rate of the mode each connected output is driven with, keyed by connector
+/// name (e.g. `DP-1`), from the CRTC its encoder is bound to
+pub fn active_modes() -> FxHashMap<String, ((u16, u16), f64)> {
+    let mut res = FxHashMap::default();
+    for (_, card) in cards() {
+        for (_, info, name) in connected(&card) {
+            let mode = info
+                .current_encoder()
+                .and_then(|x| card.get_encoder(x).ok()?.crtc())
+                .and_then(|x| card.get_crtc(x).ok()?.mode());
+            let Some(mode) = mode else {
+                continue;
+            };
+            // vrefresh is rounded, the pixel clock in kHz over the pixels per frame isn't
+            let pixels = u32::from(mode.hsync().2) * u32::from(mode.vsync().2);
+            let refresh = if pixels > 0 {
+                f64::from(mode.clock()) * 1000.0 / f64::from(pixels)
+            } else {
+                f64::from(mode.vrefresh())
+            };
+            res.insert(name, (mode.size(), refresh));
+        }
+    }
+    res
+}
+
+/// Bit depth and HDR state of each connected output, keyed by connector name (e.g. `DP-1`)
+///
+/// The depth is the lower of the driver's `max bpc` and what the monitor advertises,
+/// HDR is considered active when the compositor has set HDR metadata or a BT.2020 colorspace
+pub fn color_info() -> FxHashMap<String, String> {
+    let mut res = FxHashMap::default();
+    for (path, card) in cards() {
+        for (handle, _, name) in connected(&card) {
+            let Ok(props) = card.get_properties(handle) else {
+                continue;
+            };
+
+            let mut max_bpc = None;
+            let mut hdr = false;
+            for (id, value) in &props {
+                let Ok(prop) = card.get_property(*id) else {
+                    continue;
+                };
+                match prop.name().to_str() {
+                    Ok("max bpc") => max_bpc = Some(*value),
+                    Ok("HDR_OUTPUT_METADATA") => hdr |= *value != 0,
+                    Ok("Colorspace") => {
+                        if let property::Value::Enum(Some(colorspace)) =
+                            prop.value_type().convert_value(*value)
+                        {
+                            hdr |= colorspace.name().to_string_lossy().starts_with("BT2020");
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let card_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let edid_depth = sysfs::read_bytes_with(
+                format!("/sys/class/drm/{card_name}-{name}/edid"),
+                edid_bit_depth,
+            )
+            .flatten();
+            let depth = match (max_bpc, edid_depth) {
+                (Some(x), Some(y)) => Some(x.min(y)),
+                (x, y) => x.or(y),
+            };
+            let mut details = Vec::new();
+            if let Some(depth) = depth {
+                details.push(format!("{depth}-bit"));
+            }
+            if hdr {
+                details.push(String::from("HDR"));
+            }
+            if !details.is_empty() {
+                res.insert(name, details.join(" "));
+            }
+        }
+    }
+    res
+}
@@ -0,0 +1,15 @@
+use std::{cell::RefCell, fmt::Display};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Remember why reading a source failed, shown by `--strict` if the module comes back empty
+pub fn record(err: impl Display) {
+    LAST_ERROR.set(Some(err.to_string()));
+}
+
+/// Take the last error recorded on this thread
+pub fn take() -> Option<String> {
+    LAST_ERROR.take()
+}
@@ -1,6 +1,13 @@
 use arcstr::ArcStr;
 
-use crate::info::OSInfo;
+use crate::{
+    config::IpVersion,
+    info::{
+        errors,
+        values::{Cpu, DiskUsage, Memory},
+        OSInfo,
+    },
+};
 
 pub struct IosInfo {}
 impl IosInfo {
@@ -30,11 +37,11 @@ impl OSInfo for IosInfo {
         Vec::new()
     }
 
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         None
     }
 
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    fn disks(&self) -> Vec<(ArcStr, DiskUsage)> {
         Vec::new()
     }
 
@@ -82,7 +89,7 @@ impl OSInfo for IosInfo {
         None
     }
 
-    fn cpu(&self) -> Option<ArcStr> {
+    fn cpu(&self) -> Option<Cpu> {
         None
     }
 
@@ -91,18 +98,21 @@ impl OSInfo for IosInfo {
     }
 
     fn id(&self) -> arcstr::ArcStr {
-        todo!()
+        arcstr::literal!("ios")
     }
 
     fn uptime(&self) -> Option<ArcStr> {
-        todo!()
+        errors::record("not supported on iOS yet");
+        None
     }
 
-    fn ip(&self) -> Vec<arcstr::ArcStr> {
-        todo!()
+    fn ip(&self, _: IpVersion) -> Vec<(arcstr::ArcStr, arcstr::ArcStr)> {
+        errors::record("not supported on iOS yet");
+        Vec::new()
     }
 
     fn hostname(&self) -> Option<arcstr::ArcStr> {
-        todo!()
+        errors::record("not supported on iOS yet");
+        None
     }
 }
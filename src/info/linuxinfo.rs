@@ -1,26 +1,48 @@
 #![cfg(target_os = "linux")]
-use crate::info::OSInfo;
-use crate::util::bytecount_format;
-use anyhow::anyhow;
+#[cfg(feature = "drm")]
+use crate::info::drm;
+#[cfg(feature = "wayland")]
+use crate::info::wayland;
+#[cfg(feature = "x11")]
+use crate::info::x11;
+use crate::util::{bytecount_format, uptime_format};
+use crate::{
+    config::{IpVersion, Preset, TopSort},
+    info::{
+        accessibility_summary, dotfiles, errors, ip_addresses, memo, sqlite, sysfs, top_gpu_users,
+        top_processes,
+        values::{Cpu, DiskUsage, Memory},
+        OSInfo, ProcessSample,
+    },
+    inventory::Drive,
+};
 use arcstr::ArcStr;
 use glob::glob;
 use itertools::Itertools;
-use libc::{
-    getifaddrs, statvfs, timespec, AF_INET, AF_INET6, IFA_F_DEPRECATED, IFF_LOOPBACK, IFF_RUNNING,
-};
+use libc::{getifaddrs, statvfs, timespec, AF_INET, IFF_LOOPBACK, IFF_RUNNING};
+#[cfg(feature = "gpu-names")]
 use pci_ids::Device;
 use platform_info::UNameAPI;
 use platform_info::{PlatformInfo, PlatformInfoAPI};
 use rayon::{
-    prelude::{ParallelExtend, ParallelIterator},
+    prelude::{IntoParallelIterator, ParallelExtend, ParallelIterator},
     str::ParallelString,
 };
-use rustc_hash::{FxHashMap, FxHashSet};
-use std::{sync::OnceLock, alloc::Layout, ffi::{CStr, CString}, fs, mem::{self, MaybeUninit}, net::{Ipv4Addr, Ipv6Addr}};
+use regex::Regex;
+use rustc_hash::FxHashMap;
+use std::{
+    ffi::{CStr, CString},
+    fs, mem,
+    net::{Ipv4Addr, Ipv6Addr},
+    os::unix::{fs::MetadataExt, net::UnixStream},
+    path::{Path, PathBuf},
+    sync::{LazyLock, OnceLock},
+};
 
 pub struct LinuxInfo {
-    uts: PlatformInfo,
+    uts: Option<PlatformInfo>,
     os_release: OnceLock<FxHashMap<ArcStr, ArcStr>>,
+    host_os_release: OnceLock<Option<FxHashMap<ArcStr, ArcStr>>>,
 }
 
 impl Default for LinuxInfo {
@@ -31,161 +53,1526 @@ impl Default for LinuxInfo {
 impl LinuxInfo {
     pub fn new() -> Self {
         Self {
-            uts: PlatformInfo::new().unwrap(),
+            uts: PlatformInfo::new().ok(),
             os_release: OnceLock::default(),
+            host_os_release: OnceLock::default(),
         }
     }
 
     fn os_release(&self) -> &FxHashMap<ArcStr, ArcStr> {
         self.os_release.get_or_init(|| {
-            let mut res = FxHashMap::default();
-            let data = fs::read_to_string("/etc/os-release").ok().unwrap();
-            res.par_extend(data.par_lines().map(|line| {
-                let (x, y) = line.split_once('=').unwrap();
-                (
-                    x.to_owned().into_boxed_str().into(),
-                    y.trim_matches('"').to_owned().into_boxed_str().into(),
-                )
-            }));
-            res
+            // Distributions may only ship the fallback under /usr, and minimal containers neither
+            sysfs::read_to_string("/etc/os-release")
+                .or_else(|| sysfs::read_to_string("/usr/lib/os-release"))
+                .map(|x| parse_os_release(&x))
+                .unwrap_or_default()
         })
     }
-}
-impl OSInfo for LinuxInfo {
-    fn os(&self) -> Option<ArcStr> {
-        // Base name
-        let mut res = String::new();
-        if let Some(name) = self.os_release().get("NAME") {
-            res += name;
-        } else if let Some(name) = self.os_release().get("PRETTY_NAME") {
-            res += name;
-        } else if let Some(name) = self.os_release().get("ID") {
-            res += name;
+
+    fn host_os_release(&self) -> Option<&FxHashMap<ArcStr, ArcStr>> {
+        self.host_os_release
+            .get_or_init(|| {
+                // Toolbox and Distrobox mount the host's root filesystem at /run/host
+                fs::read_to_string("/run/host/etc/os-release")
+                    .or_else(|_| fs::read_to_string("/run/host/usr/lib/os-release"))
+                    .ok()
+                    .map(|data| parse_os_release(&data))
+            })
+            .as_ref()
+    }
+
+    /// Name of the container tool mirafetch is running under, if any
+    fn container_tool() -> Option<&'static str> {
+        if Path::new("/dev/.cros_milestone").exists()
+            || Path::new("/opt/google/cros-containers").exists()
+        {
+            Some("Crostini")
+        } else if Path::new("/run/.toolboxenv").exists() {
+            Some("toolbox")
+        } else if std::env::var_os("DISTROBOX_ENTER_PATH").is_some()
+            || std::env::var_os("CONTAINER_ID").is_some()
+        {
+            Some("distrobox")
         } else {
-            return None;
+            None
         }
+    }
+
+    fn in_container() -> bool {
+        Self::container_tool().is_some()
+            || [
+                "/.dockerenv",
+                "/run/.containerenv",
+                "/run/systemd/container",
+            ]
+            .iter()
+            .any(|x| Path::new(x).exists())
+    }
+}
+
+/// Milestone of the `ChromeOS` host, as recorded by the Crostini container tooling
+fn cros_milestone() -> Option<String> {
+    fs::read_to_string("/dev/.cros_milestone")
+        .ok()
+        .map(|x| x.trim().to_string())
+        .or_else(|| {
+            // e.g. deb https://storage.googleapis.com/cros-packages/120 bookworm main
+            let sources = fs::read_to_string("/etc/apt/sources.list.d/cros.list").ok()?;
+            sources
+                .split_ascii_whitespace()
+                .find_map(|x| x.split_once("/cros-packages/"))
+                .map(|(_, milestone)| milestone.trim_end_matches('/').to_string())
+        })
+        .filter(|x| !x.is_empty())
+}
+
+/// Apple Silicon chip name, from the device tree `compatible` list (e.g. `apple,t8112`)
+fn apple_soc() -> Option<&'static str> {
+    let compatible = fs::read_to_string("/sys/firmware/devicetree/base/compatible").ok()?;
+    compatible
+        .split('\0')
+        .find_map(|x| match x.strip_prefix("apple,")? {
+            "t8103" => Some("M1"),
+            "t6000" => Some("M1 Pro"),
+            "t6001" => Some("M1 Max"),
+            "t6002" => Some("M1 Ultra"),
+            "t8112" => Some("M2"),
+            "t6020" => Some("M2 Pro"),
+            "t6021" => Some("M2 Max"),
+            "t6022" => Some("M2 Ultra"),
+            "t8122" => Some("M3"),
+            _ => None,
+        })
+}
+
+/// Key/value pairs from the contents of an os-release file
+#[must_use]
+pub fn parse_os_release(data: &str) -> FxHashMap<ArcStr, ArcStr> {
+    let mut res = FxHashMap::default();
+    res.par_extend(data.par_lines().filter_map(|line| {
+        let (x, y) = line.split_once('=')?;
+        Some((
+            x.to_owned().into_boxed_str().into(),
+            y.trim_matches('"').to_owned().into_boxed_str().into(),
+        ))
+    }));
+    res
+}
 
-        // Codename
-        if let Some(codename) = self.os_release().get("VERSION_ID") {
-            if !res.contains(codename.as_str()) {
-                res += " ";
-                res += codename;
+/// os-release keys with the URLs shown by the `links` setting, and their labels
+const OS_RELEASE_LINKS: &[(&str, &str)] = &[
+    ("HOME_URL", "Link (Home)"),
+    ("SUPPORT_URL", "Link (Support)"),
+    ("BUG_REPORT_URL", "Link (Bug Reports)"),
+];
+
+/// What vendors leave in DMI fields they don't fill in
+const DMI_PLACEHOLDERS: &[&str] = &[
+    "",
+    "0",
+    "0123456789",
+    "Asset-1234567890",
+    "Chassis Serial Number",
+    "Default string",
+    "Invalid",
+    "No Asset Information",
+    "No Asset Tag",
+    "None",
+    "Not Applicable",
+    "Not Specified",
+    "System Serial Number",
+    "To Be Filled By O.E.M.",
+];
+
+/// The first of the DMI `fields` that was filled in by the vendor
+fn dmi_field(fields: &[&str]) -> Option<ArcStr> {
+    fields.iter().find_map(|field| {
+        sysfs::read_with(format!("/sys/class/dmi/id/{field}"), |x| {
+            (!DMI_PLACEHOLDERS.iter().any(|y| x.eq_ignore_ascii_case(y))).then(|| ArcStr::from(x))
+        })
+        .flatten()
+    })
+}
+
+/// Serial number of a block device, from sysfs or from udev's database for SATA drives
+fn block_serial(dir: &Path) -> Option<ArcStr> {
+    sysfs::read_with(dir.join("device/serial"), |x| ArcStr::from(x))
+        .filter(|x| !x.is_empty())
+        .or_else(|| {
+            let dev = sysfs::read_with(dir.join("dev"), str::to_owned)?;
+            let udev = fs::read_to_string(format!("/run/udev/data/b{dev}")).ok()?;
+            udev.lines()
+                .find_map(|x| x.strip_prefix("E:ID_SERIAL_SHORT="))
+                .map(ArcStr::from)
+        })
+}
+
+/// CPU model, thread count and clock speed from the contents of `/proc/cpuinfo`
+#[must_use]
+pub fn parse_cpuinfo(cpuinfo: &str) -> Option<Cpu> {
+    let (model, threads, mhz) = cpuinfo_fields(cpuinfo)?;
+    Some(Cpu {
+        cores: threads.map(f64::from),
+        freq_mhz: mhz,
+        ..Cpu::new(model)
+    })
+}
+
+/// Model, thread count and MHz from the contents of `/proc/cpuinfo`
+///
+/// Every architecture names these differently, so the first of several keys that is found
+/// is used, and the thread count falls back to counting the per-core blocks
+fn cpuinfo_fields(cpuinfo: &str) -> Option<(String, Option<u32>, Option<f64>)> {
+    let fields = cpuinfo
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            Some((key.trim(), value.trim()))
+        })
+        .collect_vec();
+    let field = |keys: &[&str]| {
+        keys.iter().find_map(|key| {
+            fields
+                .iter()
+                .find(|(x, value)| x.eq_ignore_ascii_case(key) && !value.is_empty())
+                .map(|(_, value)| *value)
+        })
+    };
+
+    let model = field(&["model name", "cpu model"])
+        // POWER, e.g. "POWER9 (architected), altivec supported"
+        .or_else(|| field(&["cpu"]).and_then(|x| x.split(',').next()))
+        // s390x only names the machine type, either per core or in the processor lines
+        .map(String::from)
+        .or_else(|| {
+            let machine = field(&["machine"]).or_else(|| {
+                fields.iter().find_map(|(key, value)| {
+                    key.starts_with("processor ")
+                        .then(|| value.split_once("machine = "))??
+                        .1
+                        .split(',')
+                        .next()
+                })
+            })?;
+            Some(format!("{} {}", field(&["vendor_id"])?, machine.trim()))
+        })
+        .or_else(|| field(&["Hardware"]).map(String::from))?;
+    let threads = field(&["siblings", "# processors"])
+        .and_then(|x| x.parse::<u32>().ok())
+        .or_else(|| {
+            let count = fields.iter().filter(|(key, _)| *key == "processor").count();
+            u32::try_from(count).ok().filter(|x| *x > 0)
+        });
+    // MHz, with a unit after it on POWER
+    let mhz = field(&["cpu MHz", "cpu MHz static", "clock"])
+        .and_then(|x| {
+            let end = x
+                .find(|x: char| !(x.is_ascii_digit() || x == '.'))
+                .unwrap_or(x.len());
+            x[..end].parse::<f64>().ok()
+        })
+        .filter(|x| *x > 0.0);
+    Some((model, threads, mhz))
+}
+
+/// hwmon drivers of CPU temperature sensors, most specific first, with the labels of their package
+/// or die readings in order of preference
+const CPU_SENSORS: &[(&str, &[&str])] = &[
+    ("coretemp", &["Package id 0"]),
+    // Tctl is offset on some Ryzen parts to keep fans running earlier
+    ("zenpower", &["Tdie", "Tctl"]),
+    ("k10temp", &["Tdie", "Tctl"]),
+    ("cpu_thermal", &[]),
+    ("cpu-thermal", &[]),
+    ("soc_thermal", &[]),
+];
+
+/// Global IPv6 addresses and their interface from the contents of `/proc/net/if_inet6`, leaving
+/// out temporary, deprecated and tentative ones and those whose duplicate address detection failed
+#[must_use]
+pub fn parse_if_inet6(if_inet6: &str) -> Vec<(ArcStr, Ipv6Addr)> {
+    const SKIPPED: u32 = libc::IFA_F_TEMPORARY
+        | libc::IFA_F_DEPRECATED
+        | libc::IFA_F_TENTATIVE
+        | libc::IFA_F_DADFAILED;
+    if_inet6
+        .lines()
+        .filter_map(|line| {
+            let [addr, _, _, scope, flags, name] = line.split_whitespace().collect_vec()[..] else {
+                return None;
+            };
+            let flags = u32::from_str_radix(flags, 16).ok()?;
+            (scope == "00" && flags & SKIPPED == 0)
+                .then(|| u128::from_str_radix(addr, 16).ok())
+                .flatten()
+                .map(|x| (ArcStr::from(name), Ipv6Addr::from(x)))
+        })
+        .collect()
+}
+
+/// A cpufreq value in kHz for each core that has `file`
+fn cpufreq(file: &str) -> Vec<u64> {
+    let paths: Vec<PathBuf> = glob(&format!("/sys/devices/system/cpu/cpu[0-9]*/cpufreq/{file}"))
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .collect();
+    sysfs::read_parse_all(&paths)
+}
+
+/// Directory of this process's cgroup for `controller` and the ones above it, innermost first
+///
+/// Controllers mounted on their own cgroup v1 hierarchy are preferred over the unified one.
+/// Inside a cgroup namespace the listed path may not exist, and the root is the container's own
+fn cgroup_dirs(controller: &str) -> Vec<PathBuf> {
+    let Ok(cgroups) = fs::read_to_string("/proc/self/cgroup") else {
+        return Vec::new();
+    };
+    let entries = cgroups
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(':')?;
+            rest.split_once(':')
+        })
+        .collect_vec();
+    let Some((root, path)) = entries
+        .iter()
+        .find(|(controllers, _)| controllers.split(',').any(|x| x == controller))
+        .map(|(_, path)| (Path::new("/sys/fs/cgroup").join(controller), path))
+        .or_else(|| {
+            entries
+                .iter()
+                .find(|(controllers, _)| controllers.is_empty())
+                .map(|(_, path)| (PathBuf::from("/sys/fs/cgroup"), path))
+        })
+    else {
+        return Vec::new();
+    };
+    let dir = root.join(path.trim_start_matches('/'));
+    let dir = if dir.is_dir() { dir } else { root.clone() };
+    dir.ancestors()
+        .take_while(|x| x.starts_with(&root))
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+fn read_number(path: impl AsRef<Path>) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Memory limit and usage of this process's cgroup in bytes, if it has a limit
+///
+/// Inactive page cache is left out of the usage, as the kernel reclaims it before the limit
+fn cgroup_memory() -> Option<(u64, u64)> {
+    let dirs = cgroup_dirs("memory");
+    let limit = dirs
+        .iter()
+        .filter_map(|dir| {
+            // "max" on cgroup v2, and a page-aligned i64::MAX on v1 when there's no limit
+            read_number(dir.join("memory.max"))
+                .or_else(|| read_number(dir.join("memory.limit_in_bytes")))
+                .filter(|x| *x < 1 << 62)
+        })
+        .min()?;
+    let dir = dirs.first()?;
+    let usage = read_number(dir.join("memory.current"))
+        .or_else(|| read_number(dir.join("memory.usage_in_bytes")))?;
+    let inactive = fs::read_to_string(dir.join("memory.stat"))
+        .ok()
+        .and_then(|stat| {
+            stat.lines().find_map(|line| {
+                let (key, value) = line.split_once(' ')?;
+                matches!(key, "inactive_file" | "total_inactive_file")
+                    .then(|| value.parse::<u64>().ok())?
+            })
+        })
+        .unwrap_or_default();
+    Some((limit, usage.saturating_sub(inactive)))
+}
+
+/// CPUs this process's cgroup may use from its CFS quota, if it has one
+#[allow(clippy::cast_precision_loss)]
+fn cgroup_cpus() -> Option<f64> {
+    cgroup_dirs("cpu")
+        .iter()
+        .filter_map(|dir| {
+            // "max 100000" on cgroup v2 and -1 on v1 when there's no quota
+            let (quota, period) = match fs::read_to_string(dir.join("cpu.max")) {
+                Ok(max) => {
+                    let (quota, period) = max.trim().split_once(' ')?;
+                    (quota.parse::<u64>().ok()?, period.parse::<u64>().ok()?)
+                }
+                Err(_) => (
+                    read_number(dir.join("cpu.cfs_quota_us"))?,
+                    read_number(dir.join("cpu.cfs_period_us"))?,
+                ),
+            };
+            (period > 0).then(|| quota as f64 / period as f64)
+        })
+        .min_by(f64::total_cmp)
+}
+
+static MEMINFO: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"Mem(Total|Available):\W*(\d*)").unwrap());
+/// Loop devices, RAM disks, floppies and snap packages aren't filesystems of their own
+static VIRTUAL_MOUNTS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^/dev/(loop|ram|fd))|(/var/snap)").unwrap());
+
+/// Total and available memory in KiB from the contents of `/proc/meminfo`
+#[must_use]
+pub fn parse_meminfo(meminfo: &str) -> Option<(u64, u64)> {
+    MEMINFO
+        .captures_iter(meminfo)
+        .filter_map(|x| x.get(2)?.as_str().parse().ok())
+        .collect_tuple()
+}
+
+/// Mount points of the real filesystems listed in `/proc/mounts`
+#[must_use]
+pub fn filter_mounts(mounts: &str) -> Vec<&str> {
+    mounts
+        .par_lines()
+        .filter(|line| {
+            !VIRTUAL_MOUNTS.is_match(line)
+                && (line.starts_with("/dev/")
+                    || line.starts_with("/rpool/")
+                    || line.starts_with("drvfs"))
+        })
+        .filter_map(|line| line.split_ascii_whitespace().nth(1))
+        .collect()
+}
+
+/// Outputs reported by the running X server or Wayland compositor
+#[allow(clippy::missing_const_for_fn)]
+fn server_outputs() -> Option<Vec<ArcStr>> {
+    #[cfg(feature = "x11")]
+    if std::env::var("XDG_SESSION_TYPE").is_ok_and(|x| x == "x11") {
+        return x11::outputs();
+    }
+    #[cfg(feature = "wayland")]
+    if let Some(outputs) = wayland::outputs() {
+        return Some(outputs);
+    }
+    None
+}
+
+/// Monitor name and preferred mode from an EDID base block
+#[derive(Default)]
+pub struct Edid {
+    pub name: Option<String>,
+    /// Width, height and refresh rate in Hz
+    pub preferred: Option<(u32, u32, f64)>,
+}
+
+#[must_use]
+pub fn parse_edid(edid: &[u8]) -> Option<Edid> {
+    if edid.len() < 128 || edid[..8] != [0, 255, 255, 255, 255, 255, 255, 0] {
+        return None;
+    }
+    let mut res = Edid::default();
+    for (idx, desc) in edid[54..126].chunks_exact(18).enumerate() {
+        let clock = u32::from(u16::from_le_bytes([desc[0], desc[1]])) * 10_000;
+        if clock == 0 {
+            // Display descriptors, where 0xfc holds the monitor name ended by a newline
+            if desc[3] == 0xfc {
+                let text = &desc[5..];
+                let end = text.iter().position(|x| *x == b'\n').unwrap_or(text.len());
+                let name = String::from_utf8_lossy(&text[..end]).trim().to_string();
+                res.name = (!name.is_empty()).then_some(name);
             }
-        } else if let Some(codename) = self.os_release().get("VERSION_CODENAME") {
-            if !res.contains(codename.as_str()) {
-                res += " ";
-                res += codename;
+        } else if idx == 0 {
+            // The first detailed timing is the preferred mode
+            let width = u32::from(desc[2]) | u32::from(desc[4] & 0xf0) << 4;
+            let h_blank = u32::from(desc[3]) | u32::from(desc[4] & 0x0f) << 8;
+            let height = u32::from(desc[5]) | u32::from(desc[7] & 0xf0) << 4;
+            let v_blank = u32::from(desc[6]) | u32::from(desc[7] & 0x0f) << 8;
+            let frame = (width + h_blank) * (height + v_blank);
+            let mut refresh = f64::from(clock) / f64::from(frame.max(1));
+            if desc[17] & 0x80 != 0 {
+                refresh *= 2.0;
             }
+            res.preferred = Some((width, height, refresh));
         }
+    }
+    Some(res)
+}
 
-        // Version
-        if let Some(version) = self.os_release().get("VERSION_ID") {
-            if !res.contains(version.as_str()) {
-                res += " ";
-                res += version;
-            }
-        } else if let Some(version) = self.os_release().get("VERSION") {
-            if !res.contains(version.as_str()) {
-                res += " ";
-                res += version;
-            }
+/// Enabled DRM connectors with a monitor, by name (e.g. `DP-1`) with its EDID and the first,
+/// preferred, mode the kernel lists
+fn drm_connectors() -> Vec<(String, Option<Edid>, Option<String>)> {
+    let Ok(paths) = glob("/sys/class/drm/card*-*") else {
+        return Vec::new();
+    };
+    paths
+        .filter_map(Result::ok)
+        .filter(|dir| {
+            fs::read_to_string(dir.join("status")).is_ok_and(|x| x.trim() == "connected")
+                && fs::read_to_string(dir.join("enabled")).map_or(true, |x| x.trim() == "enabled")
+        })
+        .filter_map(|dir| {
+            // card0-DP-1
+            let (_, name) = dir.file_name()?.to_str()?.split_once('-')?;
+            let edid = fs::read(dir.join("edid")).ok().and_then(|x| parse_edid(&x));
+            let mode = fs::read_to_string(dir.join("modes"))
+                .ok()
+                .and_then(|x| Some(x.lines().next()?.to_string()));
+            Some((name.to_string(), edid, mode))
+        })
+        .collect()
+}
+
+/// Build a display name like `Ubuntu 23.10 x86_64` from os-release fields
+fn format_os(os_release: &FxHashMap<ArcStr, ArcStr>, arch: &str) -> Option<ArcStr> {
+    // Base name
+    let mut res = String::new();
+    if let Some(name) = os_release.get("NAME") {
+        res += name;
+    } else if let Some(name) = os_release.get("PRETTY_NAME") {
+        res += name;
+    } else if let Some(name) = os_release.get("ID") {
+        res += name;
+    } else {
+        return None;
+    }
+
+    // Codename
+    if let Some(codename) = os_release.get("VERSION_ID") {
+        if !res.contains(codename.as_str()) {
+            res += " ";
+            res += codename;
+        }
+    } else if let Some(codename) = os_release.get("VERSION_CODENAME") {
+        if !res.contains(codename.as_str()) {
+            res += " ";
+            res += codename;
         }
+    }
 
-        let arch = self.uts.machine();
-        if !res.contains(arch.to_str().unwrap()) {
+    // Version
+    if let Some(version) = os_release.get("VERSION_ID") {
+        if !res.contains(version.as_str()) {
+            res += " ";
+            res += version;
+        }
+    } else if let Some(version) = os_release.get("VERSION") {
+        if !res.contains(version.as_str()) {
             res += " ";
-            res += arch.to_str().unwrap();
+            res += version;
         }
-        Some(ArcStr::from(res.trim()))
     }
 
-    fn hostname(&self) -> Option<ArcStr> {
-        Some(ArcStr::from(self.uts.nodename().to_str()?))
+    if !res.contains(arch) {
+        res += " ";
+        res += arch;
     }
+    Some(ArcStr::from(res.trim()))
+}
 
-    fn displays(&self) -> Vec<ArcStr> {
-        || -> anyhow::Result<Vec<ArcStr>> {
-            let mut res = Vec::new();
-            let mut paths = glob("/sys/class/drm/card*-*/modes")?;
-            while let Some(Ok(path)) = paths.next() {
-                res.push(match fs::read_to_string(path)?.split_once('\n') {
-                    Some(x) => ArcStr::from(x.0),
-                    None => continue,
-                });
+/// Processes that sit between mirafetch and the terminal emulator
+const TERMINAL_SKIP: &[&str] = &[
+    "sh",
+    "bash",
+    "zsh",
+    "fish",
+    "dash",
+    "ksh",
+    "mksh",
+    "tcsh",
+    "csh",
+    "nu",
+    "elvish",
+    "xonsh",
+    "oil",
+    "ion",
+    "sudo",
+    "doas",
+    "su",
+    "login",
+    "script",
+    "mirafetch",
+    "cargo",
+];
+
+/// Flatpak IDs of terminal emulators and their display names
+const FLATPAK_TERMINALS: &[(&str, &str)] = &[
+    ("com.raggesilver.BlackBox", "Black Box"),
+    ("app.devsuite.Ptyxis", "Ptyxis"),
+    ("org.gnome.Ptyxis", "Ptyxis"),
+    ("org.gnome.Console", "GNOME Console"),
+    ("org.gnome.Terminal", "GNOME Terminal"),
+    ("com.gexperts.Tilix", "Tilix"),
+    ("org.kde.konsole", "Konsole"),
+    ("org.wezfurlong.wezterm", "WezTerm"),
+    ("com.mitchellh.ghostty", "Ghostty"),
+    ("io.elementary.terminal", "elementary Terminal"),
+    ("org.contourterminal.Contour", "Contour"),
+];
+
+fn process_name(pid: u32) -> Option<String> {
+    fs::read_to_string(format!("/proc/{pid}/comm"))
+        .ok()
+        .map(|x| x.trim().to_string())
+}
+
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The process name may contain spaces, so start after its closing paren
+    stat.rsplit_once(')')?
+        .1
+        .split_ascii_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+/// Device number of a process's controlling terminal, 0 when it has none
+fn tty_nr(pid: u32) -> Option<u32> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    stat.rsplit_once(')')?
+        .1
+        .split_ascii_whitespace()
+        .nth(4)?
+        .parse()
+        .ok()
+}
+
+/// IDs of every process visible in `/proc`
+fn process_ids() -> impl Iterator<Item = u32> {
+    fs::read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+}
+
+/// IDs of the processes of the user running mirafetch
+fn own_process_ids() -> impl Iterator<Item = u32> {
+    let uid = unsafe { libc::getuid() };
+    process_ids()
+        .filter(move |pid| fs::metadata(format!("/proc/{pid}")).is_ok_and(|x| x.uid() == uid))
+}
+
+/// Name, resident memory and CPU time of every process, from `/proc/<pid>/stat`
+fn process_samples() -> ProcessSample {
+    let page_size = u64::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).unwrap_or(4096);
+    let tick = 1_000_000_000
+        / u64::try_from(unsafe { libc::sysconf(libc::_SC_CLK_TCK) })
+            .unwrap_or(100)
+            .max(1);
+    process_ids()
+        .filter_map(|pid| {
+            let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+            let (name, rest) = stat.split_once(" (")?.1.rsplit_once(')')?;
+            // Counting from the state after the name, utime and stime are 11 and 12, rss 21
+            let fields = rest.split_ascii_whitespace().collect_vec();
+            let field = |idx: usize| fields.get(idx)?.parse::<u64>().ok();
+            Some((
+                pid,
+                (
+                    name.to_string(),
+                    field(21)? * page_size,
+                    (field(11)? + field(12)?) * tick,
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// Bytes of video memory held by a DRM client, from the fdinfo of an open GPU device
+///
+/// The device and client ID are returned as well to tell clients apart, as every file descriptor
+/// a client has open reports the same numbers. Only the VRAM regions (`vram`, or `local` on
+/// Intel's discrete GPUs) are counted, not system memory mapped for the GPU like `gtt`, `cpu` and
+/// `system0`, preferring `drm-resident-` to the older `drm-memory-` keys that amdgpu still sends
+#[must_use]
+pub fn parse_drm_fdinfo(fdinfo: &str) -> Option<((String, u64), u64)> {
+    let mut device = String::new();
+    let mut client = None;
+    let (mut resident, mut memory) = (0, 0);
+    for (key, value) in fdinfo.lines().filter_map(|x| x.split_once(':')) {
+        let value = value.trim();
+        let bytes = || {
+            let (number, unit) = value.split_once(' ').unwrap_or((value, ""));
+            let scale = match unit.trim() {
+                "" => 1,
+                "KiB" => 1 << 10,
+                "MiB" => 1 << 20,
+                "GiB" => 1 << 30,
+                _ => return 0,
+            };
+            number.parse::<u64>().map_or(0, |x| x * scale)
+        };
+        match key {
+            "drm-pdev" => device = value.to_string(),
+            "drm-client-id" => client = value.parse().ok(),
+            _ => {
+                let vram = |region: &str| region.starts_with("vram") || region.starts_with("local");
+                if key.strip_prefix("drm-resident-").is_some_and(vram) {
+                    resident += bytes();
+                } else if key.strip_prefix("drm-memory-").is_some_and(vram) {
+                    memory += bytes();
+                }
             }
-            Ok(res)
-        }()
+        }
+    }
+    Some((
+        (device, client?),
+        if resident > 0 { resident } else { memory },
+    ))
+}
+
+/// GPU memory of each process with a GPU open, by the processes' names
+///
+/// Only processes that can be looked into are counted, usually those of the same user
+fn drm_clients() -> FxHashMap<String, u64> {
+    let clients = process_ids()
+        .collect_vec()
+        .into_par_iter()
+        .flat_map_iter(|pid| {
+            let fds = fs::read_dir(format!("/proc/{pid}/fd"))
+                .into_iter()
+                .flatten();
+            fds.filter_map(Result::ok)
+                .filter(|fd| fs::read_link(fd.path()).is_ok_and(|x| x.starts_with("/dev/dri")))
+                .filter_map(move |fd| {
+                    let fdinfo = format!("/proc/{pid}/fdinfo/{}", fd.file_name().to_str()?);
+                    let (client, bytes) = parse_drm_fdinfo(&fs::read_to_string(fdinfo).ok()?)?;
+                    Some((client, (pid, bytes)))
+                })
+        })
+        .collect::<FxHashMap<_, _>>();
+    let mut res = FxHashMap::<String, u64>::default();
+    for (pid, bytes) in clients.into_values() {
+        if let Ok(name) = fs::read_to_string(format!("/proc/{pid}/comm")) {
+            *res.entry(name.trim().to_string()).or_default() += bytes;
+        }
+    }
+    res
+}
+
+/// GPU memory of each process using an NVIDIA GPU, whose driver doesn't report DRM clients
+///
+/// nvidia-smi only lists processes using CUDA or another compute API
+fn nvidia_clients() -> FxHashMap<String, u64> {
+    let mut res = FxHashMap::<String, u64>::default();
+    if !Path::new("/proc/driver/nvidia").exists() {
+        return res;
+    }
+    let Ok(out) = std::process::Command::new("nvidia-smi")
+        .args([
+            "--query-compute-apps=process_name,used_memory",
+            "--format=csv,noheader,nounits",
+        ])
+        .output()
+        .map_err(|err| errors::record(format_args!("{err} running nvidia-smi")))
+    else {
+        return res;
+    };
+    for line in String::from_utf8_lossy(&out.stdout).lines() {
+        let Some((name, mib)) = line.rsplit_once(',') else {
+            continue;
+        };
+        let name = name.trim().rsplit('/').next().unwrap_or_default();
+        if let Ok(mib) = mib.trim().parse::<u64>() {
+            *res.entry(name.to_string()).or_default() += mib << 20;
+        }
+    }
+    res
+}
+
+/// `PipeWire`'s version, sample rate, quantum and running streams from the output of `pw-dump`,
+/// e.g. `1.2.7, 48 kHz, quantum 1024 (21.3 ms), 3 active streams`
+///
+/// The rate and quantum are the ones forced with `pw-metadata` if any, or the defaults
+#[must_use]
+pub fn parse_pw_dump(dump: &str) -> Option<String> {
+    let objects: Vec<serde_json::Value> = serde_json::from_str(dump).ok()?;
+    let of_type = |kind: &'static str| {
+        objects
+            .iter()
+            .filter(move |x| x["type"] == format!("PipeWire:Interface:{kind}").as_str())
+    };
+    let version = of_type("Core").find_map(|x| x["info"]["version"].as_str())?;
+    let settings = of_type("Metadata")
+        .find(|x| x["props"]["metadata.name"] == "settings")
+        .and_then(|x| x["metadata"].as_array());
+    let setting = |key: &str| {
+        settings?
+            .iter()
+            .find(|x| x["key"] == key)?
+            .get("value")?
+            .as_u64()
+            .filter(|x| *x > 0)
+    };
+    let streams = of_type("Node")
+        .filter(|x| {
+            x["info"]["state"] == "running"
+                && x["info"]["props"]["media.class"]
+                    .as_str()
+                    .is_some_and(|x| x.starts_with("Stream/"))
+        })
+        .count();
+    let mut res = vec![version.to_string()];
+    let rate = setting("clock.force-rate").or_else(|| setting("clock.rate"));
+    if let Some(rate) = rate {
+        res.push(format!("{} kHz", rate as f64 / 1000.0));
+    }
+    if let Some(quantum) = setting("clock.force-quantum").or_else(|| setting("clock.quantum")) {
+        res.push(rate.map_or_else(
+            || format!("quantum {quantum}"),
+            |rate| {
+                let ms = quantum as f64 / rate as f64 * 1000.0;
+                format!("quantum {quantum} ({ms:.1} ms)")
+            },
+        ));
+    }
+    res.push(match streams {
+        1 => String::from("1 active stream"),
+        x => format!("{x} active streams"),
+    });
+    Some(res.join(", "))
+}
+
+/// Screen readers by process name
+const SCREEN_READERS: &[(&str, &str)] = &[
+    ("orca", "Orca"),
+    ("fenrir", "Fenrir"),
+    ("espeakup", "Speakup"),
+    ("odilia", "Odilia"),
+    ("yasr", "YASR"),
+];
+
+/// A GNOME setting, as `gsettings get` prints it
+fn gsetting(schema: &str, key: &str) -> Option<String> {
+    let out = std::process::Command::new("gsettings")
+        .args(["get", schema, key])
+        .output()
         .ok()
-        .unwrap_or_default()
+        .filter(|x| x.status.success())?;
+    Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+/// Whether KDE's animations are turned off, with the animation speed slider all the way up
+fn kde_animations_off() -> bool {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(std::env::var_os("HOME")?).join(".config")));
+    config
+        .and_then(|x| fs::read_to_string(x.join("kdeglobals")).ok())
+        .is_some_and(|x| {
+            x.lines()
+                .filter_map(|x| x.strip_prefix("AnimationDurationFactor="))
+                .any(|x| x.trim().parse::<f64>().is_ok_and(|x| x == 0.0))
+        })
+}
+
+/// Readiness for low latency work, e.g. `PREEMPT_RT, rtprio 95, threaded IRQs`
+///
+/// The preemption model comes from the kernel's version string, or is `PREEMPT_RT` when
+/// `realtime` says so, with the mode picked with `preempt=` on a `PREEMPT_DYNAMIC` kernel. The
+/// limit is the soft one of `/proc/self/limits`, and interrupts are threaded on `PREEMPT_RT`
+/// or with `threadirqs` on the kernel command line
+#[must_use]
+pub fn realtime_summary(realtime: bool, version: &str, limits: &str, cmdline: &str) -> String {
+    let has = |text: &str, word: &str| text.split_whitespace().any(|x| x == word);
+    let realtime = realtime || has(version, "PREEMPT_RT");
+    let model = if realtime {
+        String::from("PREEMPT_RT")
+    } else if has(version, "PREEMPT_DYNAMIC") {
+        cmdline
+            .split_whitespace()
+            .find_map(|x| x.strip_prefix("preempt="))
+            .map_or_else(
+                || String::from("PREEMPT_DYNAMIC"),
+                |mode| format!("PREEMPT_DYNAMIC ({mode})"),
+            )
+    } else if has(version, "PREEMPT") {
+        String::from("PREEMPT")
+    } else {
+        String::from("not preemptible")
+    };
+    let rtprio = limits
+        .lines()
+        .find_map(|x| x.strip_prefix("Max realtime priority"))
+        .and_then(|x| x.split_whitespace().next())
+        .unwrap_or("unknown");
+    let irqs = if realtime || has(cmdline, "threadirqs") {
+        "threaded IRQs"
+    } else {
+        "IRQs not threaded"
+    };
+    format!("{model}, rtprio {rtprio}, {irqs}")
+}
+
+/// Read the application ID from the Flatpak metadata visible through a process's root
+fn flatpak_app_id(pid: u32) -> Option<String> {
+    let info = fs::read_to_string(format!("/proc/{pid}/root/.flatpak-info")).ok()?;
+    info.lines()
+        .skip_while(|line| line.trim() != "[Application]")
+        .find_map(|line| line.strip_prefix("name="))
+        .map(|x| x.trim().to_string())
+}
+
+/// Find the terminal behind a Flatpak sandbox boundary
+///
+/// The sandboxed children of `pid` are checked first; if none of them is a
+/// known terminal, the user's sandboxed processes on the same terminal are
+/// considered instead
+fn flatpak_terminal(pid: u32) -> Option<ArcStr> {
+    let known = |id: &str| {
+        FLATPAK_TERMINALS
+            .iter()
+            .find(|(app, _)| *app == id)
+            .map(|(_, name)| ArcStr::from(*name))
+    };
+    let children =
+        fs::read_to_string(format!("/proc/{pid}/task/{pid}/children")).unwrap_or_default();
+    if let Some(name) = children
+        .split_ascii_whitespace()
+        .filter_map(|x| flatpak_app_id(x.parse().ok()?))
+        .find_map(|id| known(&id))
+    {
+        return Some(name);
     }
+    let tty = tty_nr(std::process::id()).filter(|x| *x != 0)?;
+    own_process_ids()
+        .filter(|pid| tty_nr(*pid) == Some(tty))
+        .filter_map(flatpak_app_id)
+        .find_map(|id| known(&id))
+}
 
-    fn machine(&self) -> Option<ArcStr> {
-        fs::read_to_string("/sys/class/dmi/id/product_name")
+/// Desktop environments as named in `XDG_CURRENT_DESKTOP`, and their display names
+const DESKTOPS: &[(&str, &str)] = &[
+    ("GNOME", "GNOME"),
+    ("KDE", "KDE Plasma"),
+    ("plasma", "KDE Plasma"),
+    ("XFCE", "Xfce"),
+    ("X-Cinnamon", "Cinnamon"),
+    ("Cinnamon", "Cinnamon"),
+    ("MATE", "MATE"),
+    ("LXQt", "LXQt"),
+    ("LXDE", "LXDE"),
+    ("Budgie", "Budgie"),
+    ("Deepin", "Deepin"),
+    ("Pantheon", "Pantheon"),
+    ("Unity", "Unity"),
+    ("COSMIC", "COSMIC"),
+    ("Enlightenment", "Enlightenment"),
+];
+
+/// Process names of window managers and compositors, and their display names
+const WINDOW_MANAGERS: &[(&str, &str)] = &[
+    ("gnome-shell", "Mutter"),
+    ("kwin_wayland", "KWin"),
+    ("kwin_x11", "KWin"),
+    ("xfwm4", "Xfwm4"),
+    ("muffin", "Muffin"),
+    ("marco", "Marco"),
+    ("budgie-wm", "Budgie WM"),
+    ("cosmic-comp", "cosmic-comp"),
+    ("sway", "Sway"),
+    ("Hyprland", "Hyprland"),
+    ("river", "river"),
+    ("niri", "niri"),
+    ("wayfire", "Wayfire"),
+    ("labwc", "labwc"),
+    ("weston", "Weston"),
+    ("i3", "i3"),
+    ("bspwm", "bspwm"),
+    ("dwm", "dwm"),
+    ("awesome", "awesome"),
+    ("openbox", "Openbox"),
+    ("herbstluftwm", "herbstluftwm"),
+    ("qtile", "Qtile"),
+    ("xmonad", "xmonad"),
+    ("fluxbox", "Fluxbox"),
+    ("icewm", "IceWM"),
+    ("enlightenment", "Enlightenment"),
+];
+
+/// Display name of a window manager found by its process or X11 name
+fn pretty_wm_name(name: &str) -> ArcStr {
+    WINDOW_MANAGERS
+        .iter()
+        .find(|(x, _)| x.eq_ignore_ascii_case(name))
+        .map_or_else(|| ArcStr::from(name), |(_, pretty)| ArcStr::from(*pretty))
+}
+
+/// The connection to the `WAYLAND_DISPLAY` socket opened by [`preconnect`]
+static WAYLAND_SOCKET: OnceLock<UnixStream> = OnceLock::new();
+
+fn connect_wayland_socket() -> Option<UnixStream> {
+    let display = std::env::var_os("WAYLAND_DISPLAY")?;
+    let socket = std::env::var_os("XDG_RUNTIME_DIR").map_or_else(
+        || PathBuf::from(&display),
+        |dir| Path::new(&dir).join(&display),
+    );
+    UnixStream::connect(socket).ok()
+}
+
+/// Connect to the Wayland compositor now rather than when it's first asked
+pub fn preconnect() {
+    if let Some(stream) = connect_wayland_socket() {
+        let _ = WAYLAND_SOCKET.set(stream);
+    }
+}
+
+/// Process name of the Wayland compositor, the peer of the `WAYLAND_DISPLAY` socket
+fn wayland_compositor() -> Option<String> {
+    let connected;
+    let stream = if let Some(stream) = WAYLAND_SOCKET.get() {
+        stream
+    } else {
+        connected = connect_wayland_socket()?;
+        &connected
+    };
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = libc::socklen_t::try_from(std::mem::size_of::<libc::ucred>()).ok()?;
+    let res = unsafe {
+        libc::getsockopt(
+            std::os::fd::AsRawFd::as_raw_fd(stream),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            std::ptr::addr_of_mut!(cred).cast(),
+            std::ptr::addr_of_mut!(len),
+        )
+    };
+    // The peer is hidden behind pid 0 when the compositor is in another pid namespace
+    if res != 0 || cred.pid <= 0 {
+        return None;
+    }
+    process_name(cred.pid.try_into().ok()?)
+}
+
+/// Version of the GNOME, KDE Plasma or Xfce desktop that is installed
+fn desktop_version(name: &str) -> Option<String> {
+    match name {
+        "GNOME" => {
+            let xml = fs::read_to_string("/usr/share/gnome/gnome-version.xml").ok()?;
+            let tag = |tag: &str| {
+                let (_, rest) = xml.split_once(&format!("<{tag}>"))?;
+                let (value, _) = rest.split_once('<')?;
+                Some(value.trim().to_string()).filter(|x| !x.is_empty())
+            };
+            Some(
+                [tag("platform")?, tag("minor")?]
+                    .into_iter()
+                    .chain(tag("micro"))
+                    .join("."),
+            )
+        }
+        "KDE Plasma" => [
+            "/usr/share/xsessions/plasma.desktop",
+            "/usr/share/wayland-sessions/plasma.desktop",
+            "/usr/share/wayland-sessions/plasmawayland.desktop",
+        ]
+        .into_iter()
+        .find_map(|path| {
+            let session = fs::read_to_string(path).ok()?;
+            session
+                .lines()
+                .find_map(|x| x.strip_prefix("X-KDE-PluginInfo-Version="))
+                .map(|x| x.trim().to_string())
+        }),
+        // Xfce has no version file of its own, only the documentation of its libraries
+        "Xfce" => {
+            let docs = fs::read_to_string("/usr/share/gtk-doc/html/libxfce4ui/index.html").ok()?;
+            let (_, rest) = docs.split_once("class=\"releaseinfo\">Version ")?;
+            let version = rest
+                .split(|x: char| !(x.is_ascii_digit() || x == '.'))
+                .next()?;
+            (!version.is_empty()).then(|| version.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Programs that tint the screen for night time use
+const NIGHT_LIGHT: &[&str] = &[
+    "redshift",
+    "redshift-gtk",
+    "gammastep",
+    "gammastep-indicator",
+    "wlsunset",
+    "hyprsunset",
+    "sct",
+];
+
+/// Generation number of a Nix profile symlink such as `profile-42-link`
+fn nix_generation(profile: &Path) -> Option<(u32, PathBuf)> {
+    let link = fs::read_link(profile).ok()?;
+    let generation = link
+        .file_name()?
+        .to_str()?
+        .strip_suffix("-link")?
+        .rsplit_once('-')?
+        .1
+        .parse()
+        .ok()?;
+    Some((generation, profile.with_file_name(link)))
+}
+
+/// Counts the packages of one package manager, `None` if it isn't installed
+type CountPackages = fn() -> Option<u64>;
+
+/// Package managers counted by `packages`, in the order they're shown, with how to count each
+const PACKAGE_MANAGERS: [(&str, CountPackages); 10] = [
+    ("pacman", || {
+        count_dirs(Path::new("/var/lib/pacman/local"), &[])
+    }),
+    ("dpkg", || {
+        count_lines("/var/lib/dpkg/status", |x| {
+            x == "Status: install ok installed"
+        })
+    }),
+    ("rpm", count_rpm),
+    ("apk", || {
+        count_lines("/lib/apk/db/installed", |x| x.starts_with("P:"))
+    }),
+    ("xbps", || {
+        count_lines("/var/db/xbps/pkgdb-0.38.plist", |x| {
+            x.trim() == "<string>installed</string>"
+        })
+    }),
+    ("nix-user", || {
+        std::env::var_os("HOME")
+            .map(|x| Path::new(&x).join(".nix-profile"))
+            .into_iter()
+            .chain(user_name().map(|user| {
+                Path::new("/nix/var/nix/profiles/per-user")
+                    .join(user.as_str())
+                    .join("profile")
+            }))
+            .find_map(|x| count_nix_profile(&x))
+    }),
+    ("nix-default", || {
+        count_nix_profile(Path::new("/nix/var/nix/profiles/default"))
+    }),
+    ("flatpak-system", || {
+        count_flatpaks(Path::new("/var/lib/flatpak"))
+    }),
+    ("flatpak-user", || {
+        count_flatpaks(&Path::new(&std::env::var_os("HOME")?).join(".local/share/flatpak"))
+    }),
+    ("snap", || count_dirs(Path::new("/snap"), &["bin"])),
+];
+
+/// Lines of `path` that `f` matches, for package databases with one stanza per package
+fn count_lines(path: &str, f: impl Fn(&str) -> bool) -> Option<u64> {
+    let sources = [PathBuf::from(path)];
+    memo::memoized(path, &sources, || {
+        let data = fs::read_to_string(path).ok()?;
+        Some(data.lines().filter(|x| f(x)).count() as u64)
+    })
+}
+
+/// Packages in the rpm database, which moved to `/usr` on newer distributions
+fn count_rpm() -> Option<u64> {
+    let path = [
+        "/var/lib/rpm/rpmdb.sqlite",
+        "/usr/lib/sysimage/rpm/rpmdb.sqlite",
+    ]
+    .map(PathBuf::from)
+    .into_iter()
+    .find(|x| x.exists())?;
+    memo::memoized("rpm", std::slice::from_ref(&path), || {
+        sqlite::count_rows(&path, "Packages")
+    })
+}
+
+/// Name of the user running mirafetch, from the password database
+fn user_name() -> Option<ArcStr> {
+    unsafe {
+        let pwd = libc::getpwuid(libc::getuid());
+        if pwd.is_null() {
+            return None;
+        }
+        CStr::from_ptr((*pwd).pw_name)
+            .to_str()
             .ok()
-            .or_else(|| fs::read_to_string("/sys/devices/virtual/dmi/id/product_name").ok())
-            .or_else(|| fs::read_to_string("/sys/firmware/devicetree/base/model").ok())
-            .or_else(|| fs::read_to_string("/sys/firmware/devicetree/base/banner-name").ok())
-            .or_else(|| fs::read_to_string("/sys/devices/virtual/dmi/id/product_family").ok())
-            .or_else(|| fs::read_to_string("/sys/class/dmi/id/product_family").ok())
-            .or_else(|| fs::read_to_string("/sys/devices/virtual/dmi/id/product_version").ok())
-            .or_else(|| fs::read_to_string("/sys/class/dmi/id/product_version").ok())
-            .or_else(|| fs::read_to_string("/sys/devices/virtual/dmi/id/product_sku").ok())
-            .or_else(|| fs::read_to_string("/sys/class/dmi/id/product_sku").ok())
-            .or_else(|| fs::read_to_string("/sys/devices/virtual/dmi/id/sys_vendor").ok())
-            .or_else(|| fs::read_to_string("/sys/class/dmi/id/sys_vendor").ok())
-            .or_else(|| {
-                std::env::var("WSL_DISTRO_NAME")
-                    .or_else(|_| std::env::var("WSL_DISTRO"))
-                    .or_else(|_| std::env::var("WSL_INTEROP"))
-                    .ok()
-                    .map(|_| "Windows Subsystem for Linux".to_string())
-            })
-            .map(|f| {
-                if f.starts_with("Standard PC") {
-                    String::from("KVM/QEMU") + &f
-                } else {
-                    f
+            .map(ArcStr::from)
+    }
+}
+
+/// Entries of `dir` that are directories, except those named in `skip`
+fn count_dirs(dir: &Path, skip: &[&str]) -> Option<u64> {
+    let entries = fs::read_dir(dir).ok()?;
+    Some(
+        entries
+            .filter_map(Result::ok)
+            .filter(|x| x.file_type().is_ok_and(|x| x.is_dir()))
+            .filter(|x| !skip.iter().any(|name| x.file_name() == *name))
+            .count() as u64,
+    )
+}
+
+/// Installed flatpak apps and runtimes, each branch of which has an `active` deployment
+fn count_flatpaks(dir: &Path) -> Option<u64> {
+    let count = ["app", "runtime"]
+        .iter()
+        .filter_map(|kind| glob(&format!("{}/{kind}/*/*/*/active", dir.display())).ok())
+        .flatten()
+        .flatten()
+        .count() as u64;
+    dir.exists().then_some(count)
+}
+
+/// Packages in a Nix profile, listed as `elements` of its `manifest.json`
+fn count_nix_profile(profile: &Path) -> Option<u64> {
+    let manifest = fs::read_to_string(profile.join("manifest.json")).ok()?;
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).ok()?;
+    match manifest.get("elements")? {
+        // An object keyed by name since version 3, a list before
+        serde_json::Value::Object(x) => Some(x.len() as u64),
+        serde_json::Value::Array(x) => Some(x.len() as u64),
+        _ => None,
+    }
+}
+
+/// An age in its largest unit, like `3 days`, or in seconds when under a minute
+fn pretty_age(age: std::time::Duration) -> String {
+    let secs = age.as_secs();
+    let unit = [86400, 3600, 60]
+        .into_iter()
+        .find(|x| secs >= *x)
+        .unwrap_or(1);
+    uptime_format(secs - secs % unit)
+}
+
+fn pretty_terminal_name(name: &str) -> ArcStr {
+    ArcStr::from(match name {
+        "gnome-terminal-" | "gnome-terminal-server" => "GNOME Terminal",
+        "kgx" => "GNOME Console",
+        "konsole" => "Konsole",
+        "xfce4-terminal" => "Xfce Terminal",
+        "alacritty" => "Alacritty",
+        "wezterm-gui" => "WezTerm",
+        "ptyxis-agent" | "ptyxis" => "Ptyxis",
+        "blackbox" => "Black Box",
+        _ => name,
+    })
+}
+
+/// Names of the GPUs behind `cards`, the `device` directories of DRM cards
+fn gpu_names(cards: &[PathBuf]) -> Vec<String> {
+    let mut res = Vec::new();
+    for card in cards {
+        let path = card.join("vendor");
+        if !path.exists() {
+            // Apple Silicon GPUs are platform devices without PCI IDs
+            if let Some(soc) = apple_soc() {
+                let driver = fs::read_link(card.join("driver")).ok();
+                if driver.as_deref().and_then(Path::file_name) == Some("asahi".as_ref()) {
+                    res.push(format!("Apple {soc} GPU (asahi)"));
                 }
+            }
+            continue;
+        }
+        let hex = |path: PathBuf| {
+            sysfs::read_with(path, |x| {
+                u16::from_str_radix(x.strip_prefix("0x")?, 16).ok()
             })
-            .map(|x| ArcStr::from(x.trim()))
+            .flatten()
+        };
+        let (Some(vid), Some(pid)) = (hex(path), hex(card.join("device"))) else {
+            continue;
+        };
+        #[cfg(feature = "gpu-names")]
+        let name = {
+            // Cards newer than the bundled PCI ID database only have their IDs
+            Device::from_vid_pid(vid, pid).map_or_else(
+                || format!("GPU [{vid:04x}:{pid:04x}]"),
+                |device| {
+                    let vendor = device
+                        .vendor()
+                        .name()
+                        .replace("Advanced Micro Devices, Inc. [AMD/ATI]", "AMD")
+                        .replace("Intel Corporation", "Intel");
+                    format!("{vendor} {}", device.name())
+                },
+            )
+        };
+        #[cfg(not(feature = "gpu-names"))]
+        let name = format!("GPU [{vid:04x}:{pid:04x}]");
+        // Only amdgpu reports the size of dedicated memory
+        let details = [
+            sysfs::read_parse::<u64>(card.join("mem_info_vram_total"))
+                .filter(|x| *x > 0)
+                .map(|x| bytecount_format(x, 0)),
+            fs::read_link(card.join("driver"))
+                .ok()
+                .and_then(|x| Some(x.file_name()?.to_string_lossy().into_owned())),
+        ]
+        .into_iter()
+        .flatten()
+        .collect_vec();
+        if details.is_empty() {
+            res.push(name);
+        } else {
+            res.push(format!("{name} ({})", details.join(", ")));
+        }
+    }
+    if res.is_empty() {
+        if let Some(soc) = apple_soc() {
+            res.push(format!("Apple {soc} GPU (no driver)"));
+        }
+    }
+    res
+}
+
+impl OSInfo for LinuxInfo {
+    fn os(&self) -> Option<ArcStr> {
+        format_os(self.os_release(), self.uts.as_ref()?.machine().to_str()?)
+    }
+
+    fn links(&self) -> Vec<(ArcStr, ArcStr)> {
+        let os_release = self.os_release();
+        OS_RELEASE_LINKS
+            .iter()
+            .filter_map(|(key, label)| {
+                let url = os_release.get(*key).filter(|x| !x.is_empty())?;
+                Some((ArcStr::from(*label), url.clone()))
+            })
+            .collect()
+    }
+
+    fn serial_number(&self) -> Option<ArcStr> {
+        dmi_field(&["product_serial", "board_serial"]).or_else(|| {
+            // Boards without DMI, like the Raspberry Pi, have it in the device tree
+            sysfs::read_with("/sys/firmware/devicetree/base/serial-number", |x| {
+                ArcStr::from(x.trim_matches('\0'))
+            })
+        })
+    }
+
+    fn asset_tag(&self) -> Option<ArcStr> {
+        dmi_field(&["chassis_asset_tag", "board_asset_tag"])
+    }
+
+    fn os_build(&self) -> Option<ArcStr> {
+        let os_release = self.os_release();
+        ["BUILD_ID", "IMAGE_VERSION", "VERSION_ID"]
+            .iter()
+            .find_map(|key| os_release.get(*key).filter(|x| !x.is_empty()))
+            .cloned()
+    }
+
+    fn mac_addresses(&self) -> Vec<(ArcStr, ArcStr)> {
+        let Ok(paths) = glob("/sys/class/net/*") else {
+            return Vec::new();
+        };
+        paths
+            .flatten()
+            // Virtual interfaces, like bridges and VPN tunnels, have no device
+            .filter(|dir| dir.join("device").exists())
+            .filter_map(|dir| {
+                let name = dir.file_name()?.to_str()?;
+                let mac = sysfs::read_with(dir.join("address"), |x| ArcStr::from(x))?;
+                (mac != "00:00:00:00:00:00").then(|| (ArcStr::from(name), mac))
+            })
+            .sorted()
+            .collect()
+    }
+
+    fn disk_serials(&self) -> Vec<Drive> {
+        let Ok(paths) = glob("/sys/block/*") else {
+            return Vec::new();
+        };
+        paths
+            .flatten()
+            // Loop devices, RAM disks and device mapper volumes have no device
+            .filter(|dir| dir.join("device").exists())
+            .filter_map(|dir| {
+                Some(Drive {
+                    name: ArcStr::from(dir.file_name()?.to_str()?),
+                    model: sysfs::read_with(dir.join("device/model"), |x| ArcStr::from(x))
+                        .filter(|x| !x.is_empty()),
+                    serial: block_serial(&dir)?,
+                })
+            })
+            .sorted_by(|x, y| x.name.cmp(&y.name))
+            .collect()
+    }
+
+    fn host_os(&self) -> Option<ArcStr> {
+        let tool = Self::container_tool()?;
+        let host = if tool == "Crostini" {
+            arcstr::format!("ChromeOS {}", cros_milestone()?)
+        } else {
+            format_os(
+                self.host_os_release()?,
+                self.uts.as_ref()?.machine().to_str()?,
+            )?
+        };
+        Some(arcstr::format!("{host} ({tool})"))
+    }
+
+    fn host_id(&self) -> Option<ArcStr> {
+        if Self::container_tool()? == "Crostini" {
+            return Some(arcstr::literal!("chrom"));
+        }
+        self.host_os_release()?.get("ID").cloned()
+    }
+
+    fn role(&self) -> Option<Preset> {
+        if Self::in_container() {
+            return Some(Preset::Container);
+        }
+        let has_battery =
+            glob("/sys/class/power_supply/BAT*").is_ok_and(|mut x| x.next().is_some());
+        // SMBIOS chassis types, VMs usually report 1 (Other)
+        match sysfs::read_parse::<u8>("/sys/class/dmi/id/chassis_type") {
+            _ if has_battery => Some(Preset::Laptop),
+            Some(8 | 9 | 10 | 11 | 14 | 30 | 31 | 32) => Some(Preset::Laptop),
+            Some(17 | 23 | 25 | 28 | 29) => Some(Preset::Server),
+            Some(3..=7 | 13 | 15 | 16 | 24 | 34..=36) => Some(Preset::Desktop),
+            _ if std::env::var_os("DISPLAY").is_none()
+                && std::env::var_os("WAYLAND_DISPLAY").is_none() =>
+            {
+                Some(Preset::Server)
+            }
+            _ => None,
+        }
+    }
+
+    fn hostname(&self) -> Option<ArcStr> {
+        Some(ArcStr::from(self.uts.as_ref()?.nodename().to_str()?))
+    }
+
+    fn displays(&self) -> Vec<ArcStr> {
+        let connectors = drm_connectors();
+        // The display server knows the active mode, unlike the DRM mode list
+        let outputs = server_outputs()
+            .filter(|x| !x.is_empty())
+            .unwrap_or_else(|| {
+                #[cfg(feature = "drm")]
+                let active = drm::active_modes();
+                connectors
+                    .iter()
+                    .filter_map(|(name, edid, mode)| {
+                        // Without a display server the CRTC has the mode the output is driven with
+                        #[cfg(feature = "drm")]
+                        if let Some(((width, height), refresh)) = active.get(name) {
+                            return Some(arcstr::format!(
+                                "{name}: {width}x{height} @ {refresh:.0}Hz"
+                            ));
+                        }
+                        let mode = mode.as_ref()?;
+                        // The EDID's preferred timing has the refresh rate the mode list lacks
+                        let refresh = edid
+                            .as_ref()
+                            .and_then(|x| x.preferred)
+                            .filter(|(width, height, _)| *mode == format!("{width}x{height}"));
+                        Some(match refresh {
+                            Some((.., refresh)) => {
+                                arcstr::format!("{name}: {mode} @ {refresh:.0}Hz")
+                            }
+                            None => arcstr::format!("{name}: {mode}"),
+                        })
+                    })
+                    .collect()
+            });
+
+        #[cfg(feature = "drm")]
+        let color = drm::color_info();
+        #[cfg(not(feature = "drm"))]
+        let color = FxHashMap::<String, String>::default();
+        outputs
+            .into_iter()
+            .map(|output| {
+                let name = output.split_once(": ").map(|(name, _)| name);
+                let model = connectors
+                    .iter()
+                    .find(|(x, ..)| Some(x.as_str()) == name)
+                    .and_then(|(_, edid, _)| edid.as_ref()?.name.as_deref());
+                let color = name.and_then(|x| color.get(x)).map(String::as_str);
+                ArcStr::from(
+                    [Some(output.as_str()), color, model]
+                        .into_iter()
+                        .flatten()
+                        .join(", "),
+                )
+            })
+            .collect()
+    }
+
+    fn machine(&self) -> Option<ArcStr> {
+        let sources = [
+            "/sys/class/dmi/id/product_name",
+            "/sys/firmware/devicetree/base/model",
+        ]
+        .map(PathBuf::from);
+        memo::memoized("machine", &sources, || {
+            fs::read_to_string("/sys/class/dmi/id/product_name")
+                .ok()
+                .or_else(|| fs::read_to_string("/sys/devices/virtual/dmi/id/product_name").ok())
+                .or_else(|| fs::read_to_string("/sys/firmware/devicetree/base/model").ok())
+                .or_else(|| fs::read_to_string("/sys/firmware/devicetree/base/banner-name").ok())
+                .or_else(|| fs::read_to_string("/sys/devices/virtual/dmi/id/product_family").ok())
+                .or_else(|| fs::read_to_string("/sys/class/dmi/id/product_family").ok())
+                .or_else(|| fs::read_to_string("/sys/devices/virtual/dmi/id/product_version").ok())
+                .or_else(|| fs::read_to_string("/sys/class/dmi/id/product_version").ok())
+                .or_else(|| fs::read_to_string("/sys/devices/virtual/dmi/id/product_sku").ok())
+                .or_else(|| fs::read_to_string("/sys/class/dmi/id/product_sku").ok())
+                .or_else(|| fs::read_to_string("/sys/devices/virtual/dmi/id/sys_vendor").ok())
+                .or_else(|| fs::read_to_string("/sys/class/dmi/id/sys_vendor").ok())
+                .or_else(|| {
+                    std::env::var("WSL_DISTRO_NAME")
+                        .or_else(|_| std::env::var("WSL_DISTRO"))
+                        .or_else(|_| std::env::var("WSL_INTEROP"))
+                        .ok()
+                        .map(|_| "Windows Subsystem for Linux".to_string())
+                })
+                .map(|f| {
+                    if f.starts_with("Standard PC") {
+                        String::from("KVM/QEMU") + &f
+                    } else {
+                        f
+                    }
+                })
+                .map(|f| {
+                    // Device tree strings are NUL terminated
+                    let f = f.trim().trim_matches('\0');
+                    match apple_soc() {
+                        Some(soc) if !f.contains(soc) => format!("{f} ({soc})"),
+                        _ => f.to_string(),
+                    }
+                })
+        })
+        .map(ArcStr::from)
     }
 
     fn kernel(&self) -> Option<ArcStr> {
         //.utsname.machine()
-        Some(ArcStr::from(self.uts.release().to_string_lossy()))
+        Some(ArcStr::from(self.uts.as_ref()?.release().to_string_lossy()))
     }
 
     #[allow(clippy::similar_names)]
     fn gpus(&self) -> Vec<ArcStr> {
-        || -> anyhow::Result<Vec<ArcStr>> {
-            let mut res: Vec<ArcStr> = Vec::new();
-            let mut paths = glob("/sys/class/drm/card?/device")?;
-            while let Some(Ok(card)) = paths.next() {
-                let path = card.join("vendor");
-                if !path.exists() {
-                    continue;
-                }
-                let vid = u16::from_str_radix(&fs::read_to_string(path).unwrap().trim()[2..], 16)
-                    .unwrap();
-
-                let path = card.join("device");
-                if !path.exists() {
-                    continue;
-                }
-                let pid = u16::from_str_radix(&fs::read_to_string(path).unwrap().trim()[2..], 16)
-                    .unwrap();
-                let device = &Device::from_vid_pid(vid, pid).unwrap();
-                let vendor = device
-                    .vendor()
-                    .name()
-                    .replace("Advanced Micro Devices, Inc. [AMD/ATI]", "AMD")
-                    .replace("Intel Corporation", "Intel");
-                res.push(arcstr::format!("{vendor} {}", device.name()));
-            }
-            Ok(res)
-        }()
-        .ok()
-        .unwrap_or_default()
+        let cards: Vec<PathBuf> = glob("/sys/class/drm/card?/device")
+            .map(|x| x.filter_map(Result::ok).collect())
+            .unwrap_or_default();
+        // Names only change with the cards, or with the PCI ID database in the binary
+        let sources: Vec<PathBuf> = cards
+            .iter()
+            .flat_map(|x| {
+                [
+                    x.join("vendor"),
+                    x.join("device"),
+                    x.join("driver"),
+                    x.join("mem_info_vram_total"),
+                ]
+            })
+            .chain(std::env::current_exe())
+            .collect();
+        memo::memoized("gpus", &sources, || gpu_names(&cards))
+            .into_iter()
+            .map(ArcStr::from)
+            .collect()
     }
 
     // TODO
@@ -193,14 +1580,45 @@ impl OSInfo for LinuxInfo {
         None
     }
 
-    // TODO
     fn wm(&self) -> Option<ArcStr> {
-        None
+        let name = wayland_compositor();
+        #[cfg(feature = "x11")]
+        let name = name.or_else(|| {
+            std::env::var_os("DISPLAY")?;
+            x11::wm_name().map(|x| x.to_string())
+        });
+        name.map(|x| pretty_wm_name(&x)).or_else(|| {
+            // Without a display to ask, look for a window manager among the processes
+            process_ids()
+                .filter_map(process_name)
+                .find_map(|name| WINDOW_MANAGERS.iter().find(|(x, _)| *x == name))
+                .map(|(_, pretty)| ArcStr::from(*pretty))
+        })
     }
 
-    // TODO
     fn de(&self) -> Option<ArcStr> {
-        None
+        // e.g. "ubuntu:GNOME", where the first known desktop is the one shown
+        let desktops = std::env::var("XDG_CURRENT_DESKTOP")
+            .ok()
+            .filter(|x| !x.is_empty())
+            .or_else(|| std::env::var("DESKTOP_SESSION").ok())?;
+        let name = desktops
+            .split(':')
+            .find_map(|x| DESKTOPS.iter().find(|(id, _)| id.eq_ignore_ascii_case(x)))
+            .map(|(_, name)| *name)
+            .or_else(|| {
+                // Window managers such as sway set it to their own name
+                let first = desktops.split(':').next()?;
+                (!WINDOW_MANAGERS
+                    .iter()
+                    .any(|(x, _)| x.eq_ignore_ascii_case(first)))
+                .then_some(first)
+            })
+            .filter(|x| !x.is_empty())?;
+        Some(desktop_version(name).map_or_else(
+            || ArcStr::from(name),
+            |version| arcstr::format!("{name} {version}"),
+        ))
     }
 
     fn shell(&self) -> Option<ArcStr> {
@@ -210,46 +1628,78 @@ impl OSInfo for LinuxInfo {
             .map(|x| ArcStr::from(x.trim()))
     }
 
-    fn cpu(&self) -> Option<ArcStr> {
-        let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
-        let model = cpuinfo
-            .lines()
-            .find(|x| x.starts_with("model name"))?
-            .split_once(':')?
-            .1
-            .trim();
-        let cores = cpuinfo
-            .lines()
-            .find(|x| x.starts_with("siblings"))?
-            .split_once(':')?
-            .1
-            .trim();
+    fn shell_framework(&self) -> Option<ArcStr> {
+        dotfiles::framework(self.shell().as_deref()).map(ArcStr::from)
+    }
 
-        let freq: f32 = cpuinfo
-            .lines()
-            .find(|x| x.starts_with("cpu MHz"))?
-            .split_once(':')?
+    fn xdg(&self) -> Option<ArcStr> {
+        dotfiles::xdg_overrides().map(ArcStr::from)
+    }
+
+    fn cpu(&self) -> Option<Cpu> {
+        let mut cpu = parse_cpuinfo(&sysfs::read_to_string("/proc/cpuinfo")?)?;
+        // ARM and some AMD parts don't list a clock, but cpufreq still knows the fastest one
+        #[allow(clippy::cast_precision_loss)]
+        if cpu.freq_mhz.is_none() {
+            cpu.freq_mhz = cpufreq("cpuinfo_max_freq")
+                .into_iter()
+                .max()
+                .map(|max| max as f64 / 1000.0)
+                .filter(|x| *x > 0.0);
+        }
+        // A CPU quota smaller than the machine is what a container can actually use
+        if let Some(cpus) = Self::in_container()
+            .then(cgroup_cpus)
+            .flatten()
+            .filter(|x| cpu.cores.is_none_or(|threads| *x < threads))
+        {
+            cpu.cores = Some(cpus);
+            cpu.cgroup = true;
+        }
+        Some(cpu)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn cpu_freq_range(&self) -> Option<(f64, f64)> {
+        // Take the widest range across clusters for big.LITTLE parts
+        let max = cpufreq("cpuinfo_max_freq").into_iter().max()?;
+        // intel_pstate and amd-pstate expose the base clock, others only the minimum
+        let min = cpufreq("base_frequency")
+            .into_iter()
+            .min()
+            .or_else(|| cpufreq("cpuinfo_min_freq").into_iter().min())?;
+        Some((min as f64 / 1000.0, max as f64 / 1000.0))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn cpu_temp(&self) -> Option<f64> {
+        let (rank, dir) = glob("/sys/class/hwmon/hwmon*")
+            .ok()?
+            .filter_map(Result::ok)
+            .filter_map(|dir| {
+                let name = sysfs::read_with(dir.join("name"), str::to_owned)?;
+                let rank = CPU_SENSORS.iter().position(|(x, _)| *x == name)?;
+                Some((rank, dir))
+            })
+            .min_by_key(|(rank, _)| *rank)?;
+        // The package or die reading where there is one per core as well, else the first
+        let input = CPU_SENSORS[rank]
             .1
-            .trim()
-            .parse()
-            .ok()?;
-        Some(arcstr::format!(
-            "{} ({}) @ {:.2} GHz)",
-            model,
-            cores,
-            freq / 1000.0
-        ))
+            .iter()
+            .find_map(|label| {
+                glob(&format!("{}/temp*_label", dir.display()))
+                    .ok()?
+                    .filter_map(Result::ok)
+                    .find(|path| sysfs::read_with(path, |x| x == *label) == Some(true))
+            })
+            .and_then(|path| Some(PathBuf::from(path.to_str()?.replace("_label", "_input"))))
+            .unwrap_or_else(|| dir.join("temp1_input"));
+        // In millidegrees Celsius
+        sysfs::read_parse::<i64>(input).map(|x| x as f64 / 1000.0)
     }
 
     fn username(&self) -> Option<ArcStr> {
-        unsafe {
-            let uid = libc::getuid();
-            let pwd = libc::getpwuid(uid);
-            CStr::from_ptr((*pwd).pw_name)
-                .to_str()
-                .ok()
-                .map(ArcStr::from)
-        }
+        user_name()
     }
 
     // TODO
@@ -262,8 +1712,24 @@ impl OSInfo for LinuxInfo {
         None
     }
 
-    // TODO
     fn terminal(&self) -> Option<ArcStr> {
+        let mut pid = std::os::unix::process::parent_id();
+        while pid > 1 {
+            let name = process_name(pid)?;
+            match name.as_str() {
+                // Flatpak terminals spawn host shells through the session helper, and
+                // sandboxed ones show up as bwrap, so look inside the sandbox instead
+                "bwrap" | "flatpak-session-helper" | "flatpak-spawn" => {
+                    if let Some(app) = flatpak_terminal(pid) {
+                        return Some(app);
+                    }
+                }
+                x if TERMINAL_SKIP.contains(&x) => {}
+                "systemd" | "init" => return None,
+                _ => return Some(pretty_terminal_name(&name)),
+            }
+            pid = parent_pid(pid)?;
+        }
         None
     }
 
@@ -272,159 +1738,264 @@ impl OSInfo for LinuxInfo {
         None
     }
 
-    fn memory(&self) -> Option<ArcStr> {
-        let re = regex::Regex::new(r"Mem(Total|Available):\W*(\d*)").unwrap();
-        let mem = fs::read_to_string("/proc/meminfo").ok()?;
-        let caps: (u64, u64) = re
-            .captures_iter(&mem)
-            .map(|x| str::parse::<u64>(x.get(2).unwrap().as_str()).unwrap())
-            .collect_tuple()?;
+    fn pressure(&self) -> Option<ArcStr> {
+        // Kernels without CONFIG_PSI, or with psi=0, have no /proc/pressure
+        let res = [("cpu", "CPU"), ("memory", "Memory"), ("io", "I/O")]
+            .into_iter()
+            .filter_map(|(file, name)| {
+                let avg10 = sysfs::read_with(format!("/proc/pressure/{file}"), |x| {
+                    x.lines()
+                        .find_map(|line| line.strip_prefix("some "))?
+                        .split_ascii_whitespace()
+                        .find_map(|x| x.strip_prefix("avg10="))?
+                        .parse::<f64>()
+                        .ok()
+                })??;
+                Some(format!("{name} {avg10:.2}%"))
+            })
+            .join(", ");
+        (!res.is_empty()).then(|| ArcStr::from(res))
+    }
+
+    fn memory(&self) -> Option<Memory> {
+        let mem = sysfs::read_to_string("/proc/meminfo")?;
+        let caps = parse_meminfo(&mem)?;
+        if let Some((limit, used)) = Self::in_container()
+            .then(cgroup_memory)
+            .flatten()
+            .filter(|(limit, _)| *limit < caps.0 << 10)
+        {
+            return Some(Memory {
+                used,
+                total: limit,
+                cgroup: true,
+            });
+        }
+
+        Some(Memory {
+            used: (caps.0 - caps.1) << 10,
+            total: caps.0 << 10,
+            cgroup: false,
+        })
+    }
+
+    fn swap(&self) -> Option<Memory> {
+        let mem = sysfs::read_to_string("/proc/meminfo")?;
+        let field = |name: &str| -> Option<u64> {
+            mem.lines()
+                .find_map(|x| x.strip_prefix(name)?.strip_prefix(':'))?
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse()
+                .ok()
+        };
+        let total = field("SwapTotal").filter(|x| *x > 0)?;
+        let free = field("SwapFree")?;
+        Some(Memory {
+            used: total.saturating_sub(free) << 10,
+            total: total << 10,
+            cgroup: false,
+        })
+    }
+
+    fn time_sync(&self) -> Option<ArcStr> {
+        // Only reads the kernel's NTP state, setting it takes a mode and CAP_SYS_TIME. Refused
+        // in hardened mode, which can't tell the two apart
+        let mut timex = unsafe { mem::zeroed::<libc::timex>() };
+        let state = unsafe { libc::adjtimex(std::ptr::addr_of_mut!(timex)) };
+        if state < 0 {
+            return None;
+        }
+        let mut res = if state == libc::TIME_ERROR {
+            String::from("Not synchronized")
+        } else {
+            // Microseconds, unless the kernel's PLL is in nanosecond mode
+            #[allow(clippy::cast_precision_loss)]
+            let offset = if timex.status & libc::STA_NANO == 0 {
+                timex.offset as f64 / 1000.0
+            } else {
+                timex.offset as f64 / 1_000_000.0
+            };
+            format!("Synchronized, offset {offset:+.3} ms")
+        };
+        // Windows keeps the hardware clock in local time, so dual-boot machines often do too
+        let adjtime = fs::read_to_string("/etc/adjtime").unwrap_or_default();
+        if adjtime.lines().nth(2).is_some_and(|x| x.trim() == "LOCAL") {
+            res.push_str(", RTC in local time");
+        }
+        Some(res.into())
+    }
+
+    fn top(&self, by: TopSort) -> Option<ArcStr> {
+        top_processes(by, process_samples).map(ArcStr::from)
+    }
+
+    fn pipewire(&self) -> Option<ArcStr> {
+        // Checked first so pw-dump isn't run for nothing, as it waits for a server to appear
+        let runtime_dir = std::env::var_os("PIPEWIRE_RUNTIME_DIR")
+            .or_else(|| std::env::var_os("XDG_RUNTIME_DIR"))?;
+        let socket = Path::new(&runtime_dir).join("pipewire-0");
+        if !socket.exists() {
+            errors::record(format_args!("no PipeWire server at {}", socket.display()));
+            return None;
+        }
+        let out = std::process::Command::new("pw-dump")
+            .arg("--no-colors")
+            .output()
+            .map_err(|err| errors::record(format_args!("{err} running pw-dump")))
+            .ok()?;
+        parse_pw_dump(&String::from_utf8_lossy(&out.stdout)).map(ArcStr::from)
+    }
+
+    fn realtime(&self) -> Option<ArcStr> {
+        let version = self.uts.as_ref()?.version().to_string_lossy();
+        // Only there on kernels built with PREEMPT_RT
+        let realtime = fs::read_to_string("/sys/kernel/realtime").is_ok_and(|x| x.trim() == "1");
+        let limits = sysfs::read_to_string("/proc/self/limits")?;
+        let cmdline = sysfs::read_to_string("/proc/cmdline").unwrap_or_default();
+        Some(realtime_summary(realtime, &version, &limits, &cmdline).into())
+    }
+
+    fn screen_reader(&self) -> Option<ArcStr> {
+        // Another user's screen reader, like the one on the login screen, isn't reading to us
+        own_process_ids()
+            .filter_map(process_name)
+            .find_map(|name| SCREEN_READERS.iter().find(|(x, _)| *x == name))
+            .map(|(_, pretty)| ArcStr::from(*pretty))
+    }
 
-        Some(arcstr::format!(
-            "{} / {}",
-            bytecount_format((caps.0 - caps.1) << 10, 2),
-            bytecount_format(caps.0 << 10, 2),
+    fn accessibility(&self) -> Option<ArcStr> {
+        let high_contrast = gsetting("org.gnome.desktop.a11y.interface", "high-contrast")
+            .is_some_and(|x| x == "true")
+            || std::env::var("GTK_THEME").is_ok_and(|x| x.contains("HighContrast"));
+        let reduced_motion = gsetting("org.gnome.desktop.interface", "enable-animations")
+            .is_some_and(|x| x == "false")
+            || kde_animations_off();
+        Some(accessibility_summary(
+            self.screen_reader().as_deref(),
+            high_contrast,
+            reduced_motion,
         ))
     }
 
-    fn ip(&self) -> Vec<ArcStr> {
-        let mut ipv4_addrs = FxHashSet::<Ipv4Addr>::default();
-        let mut ipv6_addrs = FxHashSet::<Ipv6Addr>::default();
+    fn top_gpu(&self) -> Option<ArcStr> {
+        let mut users = drm_clients();
+        for (name, bytes) in nvidia_clients() {
+            *users.entry(name).or_default() += bytes;
+        }
+        top_gpu_users(users)
+    }
+
+    fn ip(&self, version: IpVersion) -> Vec<(ArcStr, ArcStr)> {
+        let mut ipv4_addrs = Vec::new();
         unsafe {
-            let mut addrs = mem::MaybeUninit::<*mut libc::ifaddrs>::uninit();
-            getifaddrs(addrs.as_mut_ptr());
-            while let Some(addr) = addrs.assume_init().as_ref() {
-                if addr.ifa_addr.is_null() {
-                    addrs = MaybeUninit::new(addr.ifa_next);
-                    continue;
-                }
-                if addr.ifa_flags & IFF_RUNNING as u32 == 0 {
-                    addrs = MaybeUninit::new(addr.ifa_next);
-                    continue;
-                }
-                if addr.ifa_flags & IFF_LOOPBACK as u32 != 0 {
-                    addrs = MaybeUninit::new(addr.ifa_next);
-                    continue;
-                }
-                if addr.ifa_flags & IFA_F_DEPRECATED != 0 {
-                    addrs = MaybeUninit::new(addr.ifa_next);
-                    continue;
-                }
-                if i32::from((*addr.ifa_addr).sa_family) == AF_INET {
+            let mut addrs = std::ptr::null_mut();
+            if getifaddrs(&raw mut addrs) == 0 {
+                let mut current = addrs.cast_const();
+                while let Some(addr) = current.as_ref() {
+                    current = addr.ifa_next;
+                    if addr.ifa_addr.is_null()
+                        || addr.ifa_flags & IFF_RUNNING as u32 == 0
+                        || addr.ifa_flags & IFF_LOOPBACK as u32 != 0
+                        || i32::from((*addr.ifa_addr).sa_family) != AF_INET
+                    {
+                        continue;
+                    }
                     let ipv4 = (*(addr.ifa_addr).cast::<libc::sockaddr_in>())
                         .sin_addr
-                        .s_addr
-                        .swap_bytes();
-                    ipv4_addrs.insert(Ipv4Addr::from(ipv4));
-                }
-                if i32::from((*addr.ifa_addr).sa_family) == AF_INET6 {
-                    let ipv6 = (*(addr.ifa_addr).cast::<libc::sockaddr_in6>())
-                        .sin6_addr
-                        .s6_addr;
-                    if !ipv6.starts_with(&[0xfe, 0x80]) {
-                        ipv6_addrs.insert(Ipv6Addr::from(ipv6));
-                    }
+                        .s_addr;
+                    let name = CStr::from_ptr(addr.ifa_name).to_string_lossy();
+                    ipv4_addrs.push((ArcStr::from(name), Ipv4Addr::from(u32::from_be(ipv4))));
                 }
-                // if addr.ifa_next.is_null() {
-                //     break;
-                // }
-                addrs = MaybeUninit::new(addr.ifa_next);
+                libc::freeifaddrs(addrs);
             }
+        }
+        // getifaddrs doesn't say which IPv6 addresses are temporary or deprecated
+        let ipv6_addrs = if version == IpVersion::V4 {
+            Vec::new()
+        } else {
+            sysfs::read_to_string("/proc/net/if_inet6")
+                .map(|x| parse_if_inet6(&x))
+                .unwrap_or_default()
         };
-
-        vec![
-            ArcStr::from(
-                ipv4_addrs
-                    .iter()
-                    .map(std::string::ToString::to_string)
-                    .collect_vec()
-                    .join(", "),
-            ),
-            /*ipv6_addrs.iter().fold(ArcStr::new(), |x, y| {
-                (if x.is_empty() { x } else { x + ", " }) + &y.to_string()
-            }),*/
-        ]
+        ip_addresses(version, ipv4_addrs, ipv6_addrs)
     }
 
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
-        (|| -> Option<Vec<(ArcStr, ArcStr)>> {
-            let mnt = fs::read_to_string("/proc/mounts").ok()?;
-            let re = regex::Regex::new(r#"(^/dev/(loop|ram|fd))|(/var/snap)"#).unwrap();
+    fn disks(&self) -> Vec<(ArcStr, DiskUsage)> {
+        (|| -> Option<Vec<(ArcStr, DiskUsage)>> {
+            let mnt = sysfs::read_to_string("/proc/mounts")?;
             Some(
-                mnt.par_lines()
-                    .filter_map(|line| -> Option<std::str::SplitAsciiWhitespace<'_>> {
-                        if re.is_match(line) {
-                            return None;
-                        }
-
-                        if line.starts_with("/rpool/") || line.starts_with("drvfs") {
-                            return Some(line.split_ascii_whitespace());
-                        }
-                        if !line.starts_with("/dev/") {
-                            return None;
-                        }
-                        return Some(line.split_ascii_whitespace());
-                    })
-                    .filter_map(|mut x| -> Option<(ArcStr, ArcStr)> {
-                        let (Some(_name), Some(mount), Some(_filesystemm)) =
-                            (x.next(), x.next(), x.next())
-                        else {
-                            return None;
-                        };
-                        unsafe {
-                            let buf: *mut statvfs =
-                                std::alloc::alloc(Layout::new::<statvfs>()).cast();
-
-                            statvfs(CString::new(mount).ok().unwrap().as_ptr(), buf);
-                            let total = (*buf).f_blocks;
-                            let size_used = total.checked_sub((*buf).f_bavail)?;
-                            let block_size = (*buf).f_bsize;
-                            if size_used == 0 {
+                filter_mounts(&mnt)
+                    .into_par_iter()
+                    .filter_map(|mount| -> Option<(ArcStr, DiskUsage)> {
+                        let path = CString::new(mount).ok()?;
+                        let buf = unsafe {
+                            let mut buf: statvfs = mem::zeroed();
+                            // Mounts that went away or can't be reached, such as a stale NFS share
+                            if statvfs(path.as_ptr(), &raw mut buf) != 0 {
+                                errors::record(format_args!(
+                                    "{} reading {mount}",
+                                    std::io::Error::last_os_error()
+                                ));
                                 return None;
                             }
-                            // println!("Size Used: {size_used}, Block Size {block_size}");
-                            size_used.checked_mul(block_size).map(|bytes| {
-                                (
-                                    arcstr::format!("Disk ({mount})"),
-                                    arcstr::format!(
-                                        "{}/ {}",
-                                        bytecount_format(bytes, 0),
-                                        bytecount_format(total * block_size, 0)
-                                    ),
-                                )
-                            })
+                            buf
+                        };
+                        let total = buf.f_blocks;
+                        let size_used = total.checked_sub(buf.f_bavail)?;
+                        let block_size = buf.f_bsize;
+                        if size_used == 0 {
+                            return None;
                         }
+                        Some((
+                            arcstr::format!("Disk ({mount})"),
+                            DiskUsage {
+                                used: size_used.checked_mul(block_size)?,
+                                total: total.checked_mul(block_size)?,
+                            },
+                        ))
                     })
-                    .collect::<Vec<(ArcStr, ArcStr)>>(),
+                    .collect::<Vec<(ArcStr, DiskUsage)>>(),
             )
         })()
         .unwrap_or_default()
     }
 
     fn battery(&self) -> Option<ArcStr> {
-        //TODO refactor this into a vector
-        Some(ArcStr::from(
-            glob::glob("/sys/class/power_supply/BAT*/")
-                .ok()?
-                .filter_map(|x| {
-                    x.map_err(|op| anyhow!(op))
-                        .and_then(|path| {
-                            let mut bat = fs::read_to_string(path.join("capacity"))?
-                                .trim()
-                                .to_string()
-                                + "% ";
-                            if let Ok(status) = fs::read_to_string(path.join("status")) {
-                                if !status.contains("Unknown") {
-                                    bat += &status;
-                                }
-                            }
-                            Ok(bat.trim().to_string())
-                        })
-                        .ok()
+        let number = |dir: &Path, file: &str| -> Option<u64> { sysfs::read_parse(dir.join(file)) };
+        let batteries = glob("/sys/class/power_supply/*")
+            .ok()?
+            .filter_map(Result::ok)
+            .sorted()
+            // Mice, keyboards and controllers report their batteries with the "Device" scope
+            .filter(|dir| {
+                sysfs::read_with(dir.join("type"), |x| x == "Battery").unwrap_or_default()
+                    && !fs::read_to_string(dir.join("scope")).is_ok_and(|x| x.trim() == "Device")
+            })
+            .filter_map(|dir| {
+                // Some batteries only report their charge in µWh or µAh
+                let capacity = number(&dir, "capacity").or_else(|| {
+                    let [now, full] =
+                        [("energy_now", "energy_full"), ("charge_now", "charge_full")]
+                            .into_iter()
+                            .find_map(|(now, full)| {
+                                Some([number(&dir, now)?, number(&dir, full)?])
+                            })?;
+                    (full > 0).then(|| (now * 100 / full).min(100))
+                })?;
+                let status = sysfs::read_with(dir.join("status"), |x| {
+                    (!x.is_empty() && x != "Unknown").then(|| x.to_string())
                 })
-                .collect_vec()
-                .join(", "),
-        ))
+                .flatten();
+                Some(status.map_or_else(
+                    || format!("{capacity}%"),
+                    |status| format!("{capacity}% ({status})"),
+                ))
+            })
+            .join(", ");
+        // Desktops and servers only have mains power
+        (!batteries.is_empty()).then(|| ArcStr::from(batteries))
     }
 
     fn locale(&self) -> Option<ArcStr> {
@@ -437,25 +2008,191 @@ impl OSInfo for LinuxInfo {
     }
 
     fn uptime(&self) -> Option<ArcStr> {
-        unsafe {
-            let time: *mut timespec = std::alloc::alloc(Layout::new::<timespec>()).cast();
-            libc::clock_gettime(libc::CLOCK_BOOTTIME, time);
-            Some(ArcStr::from(
-                (
-                    time::Duration::seconds(time.as_ref().unwrap().tv_sec)
-                    // + time::Duration::nanoseconds(time.as_ref().unwrap().tv_nsec)
-                )
-                .to_string(),
-            ))
+        // Unlike CLOCK_MONOTONIC this keeps counting while suspended
+        let mut time = timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        let res =
+            unsafe { libc::clock_gettime(libc::CLOCK_BOOTTIME, std::ptr::addr_of_mut!(time)) };
+        if res != 0 {
+            return None;
         }
+        Some(ArcStr::from(uptime_format(time.tv_sec.try_into().ok()?)))
     }
 
     // TODO
     fn icons(&self) -> Option<ArcStr> {
         None
     }
+    fn brightness(&self) -> Option<ArcStr> {
+        let backlight = glob("/sys/class/backlight/*")
+            .ok()
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .find_map(|dir| {
+                let read = |file| -> Option<u64> { sysfs::read_parse(dir.join(file)) };
+                let max = read("max_brightness").filter(|x| *x != 0)?;
+                Some(format!("{}%", read("brightness")? * 100 / max))
+            });
+        let night_light = process_ids()
+            .filter_map(process_name)
+            .find(|x| NIGHT_LIGHT.contains(&x.as_str()))
+            .map(|x| format!("night light ({x})"));
+        match (backlight, night_light) {
+            (Some(x), Some(y)) => Some(arcstr::format!("{x}, {y}")),
+            (x, y) => x.or(y).map(ArcStr::from),
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn power(&self) -> Option<ArcStr> {
+        let supplies = glob("/sys/class/power_supply/*")
+            .ok()?
+            .filter_map(Result::ok)
+            .collect_vec();
+        let is = |dir: &Path, file: &str, values: &[&str]| {
+            sysfs::read_with(dir.join(file), |x| values.contains(&x)).unwrap_or_default()
+        };
+        let number = |dir: &Path, file: &str| -> Option<u64> { sysfs::read_parse(dir.join(file)) };
+        // Values are reported in µW, µV and µA
+        let watts = |dir: &Path| {
+            number(dir, "power_now")
+                .map(|x| x as f64 / 1e6)
+                .or_else(|| {
+                    let voltage =
+                        number(dir, "voltage_now").or_else(|| number(dir, "voltage_max"))?;
+                    let current =
+                        number(dir, "current_now").or_else(|| number(dir, "current_max"))?;
+                    Some(voltage as f64 * current as f64 / 1e12)
+                })
+                .filter(|x| *x > 0.0)
+        };
+
+        let adapter = supplies
+            .iter()
+            .find(|dir| is(dir, "type", &["Mains", "USB"]) && number(dir, "online") == Some(1));
+        if let Some(dir) = adapter {
+            // The active USB charging mode is bracketed, e.g. "C [PD] PD_PPS"
+            let kind = if !is(dir, "type", &["USB"]) {
+                None
+            } else if sysfs::read_with(dir.join("usb_type"), |x| x.contains("[PD"))
+                .unwrap_or_default()
+            {
+                Some("USB-C")
+            } else {
+                Some("USB")
+            };
+            let details = watts(dir)
+                .map(|x| format!("{x:.0}W"))
+                .into_iter()
+                .chain(kind.map(String::from))
+                .join(" ");
+            if details.is_empty() {
+                return Some(arcstr::literal!("AC"));
+            }
+            return Some(arcstr::format!("AC ({details})"));
+        }
+
+        let battery = supplies.iter().find(|dir| is(dir, "type", &["Battery"]))?;
+        Some(watts(battery).map_or_else(
+            || arcstr::literal!("Battery"),
+            |x| arcstr::format!("Battery ({x:.1}W)"),
+        ))
+    }
+
+    fn packages(&self) -> Option<ArcStr> {
+        // Each database is read on its own, so a slow disk only holds up one of them
+        let counts = PACKAGE_MANAGERS
+            .into_par_iter()
+            .map(|(name, count)| (name, count()))
+            .collect::<Vec<_>>();
+        let res = counts
+            .into_iter()
+            .filter_map(|(name, count)| Some((name, count.filter(|x| *x > 0)?)))
+            .map(|(name, count)| format!("{count} ({name})"))
+            .join(", ");
+        (!res.is_empty()).then(|| ArcStr::from(res))
+    }
+
+    fn arch(&self) -> Option<ArcStr> {
+        let machine = self.uts.as_ref()?.machine().to_str()?.to_string();
+        let mut res = vec![machine.clone()];
+        // The ELF header of a system binary describes the userspace ABI
+        let elf = fs::File::open("/bin/sh").ok().and_then(|mut file| {
+            let mut header = [0u8; 6];
+            std::io::Read::read_exact(&mut file, &mut header).ok()?;
+            header.starts_with(b"\x7fELF").then_some(header)
+        });
+        match elf.map(|x| x[5]) {
+            Some(2) => res.push(String::from("big-endian")),
+            Some(1) => res.push(String::from("little-endian")),
+            _ if cfg!(target_endian = "big") => res.push(String::from("big-endian")),
+            _ => res.push(String::from("little-endian")),
+        }
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if let Ok(page_size) = u64::try_from(page_size) {
+            res.push(format!("{} pages", bytecount_format(page_size, 0)));
+        }
+        if machine.ends_with("64") && elf.is_some_and(|x| x[4] == 1) {
+            res.push(String::from("32-bit userspace"));
+        }
+        Some(ArcStr::from(res.join(", ")))
+    }
+
+    fn nix(&self) -> Option<ArcStr> {
+        if !Path::new("/nix/store").exists() {
+            return None;
+        }
+        // The store path of the nix binary carries its version, e.g. /nix/store/<hash>-nix-2.18.1
+        let version = std::env::var_os("PATH").and_then(|path| {
+            std::env::split_paths(&path)
+                .map(|dir| dir.join("nix"))
+                .find(|bin| bin.exists())
+                .and_then(|bin| fs::canonicalize(bin).ok())
+                .and_then(|bin| {
+                    bin.components()
+                        .filter_map(|x| x.as_os_str().to_str())
+                        .find_map(|x| x.split_once("-nix-").map(|(_, v)| v.to_string()))
+                })
+        });
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let user = self.username().unwrap_or_default();
+        let profile = |name: &str| {
+            home.iter()
+                .map(|home| home.join(".local/state/nix/profiles").join(name))
+                .chain([Path::new("/nix/var/nix/profiles/per-user")
+                    .join(user.as_str())
+                    .join(name)])
+                .find_map(|x| nix_generation(&x))
+        };
+
+        let mut details = Vec::new();
+        if let Some((generation, _)) = profile("profile") {
+            details.push(format!("profile gen {generation}"));
+        }
+        if let Some((generation, path)) = profile("home-manager") {
+            let age = fs::symlink_metadata(path)
+                .and_then(|x| x.modified())
+                .ok()
+                .and_then(|x| x.elapsed().ok())
+                .map(|x| format!(", {} old", pretty_age(x)))
+                .unwrap_or_default();
+            details.push(format!("home-manager gen {generation}{age}"));
+        }
+        let version = version.unwrap_or_else(|| String::from("Nix"));
+        if details.is_empty() {
+            return Some(ArcStr::from(version));
+        }
+        Some(arcstr::format!("{version} ({})", details.join(", ")))
+    }
+
     fn id(&self) -> ArcStr {
-        self.os_release();
-        self.os_release().get("ID").unwrap().clone()
+        self.os_release()
+            .get("ID")
+            .cloned()
+            // What os-release(5) says to assume when ID is missing
+            .unwrap_or(arcstr::literal!("linux"))
     }
 }
@@ -1,66 +1,332 @@
-#[cfg(target_os = "macos")]
 use arcstr::ArcStr;
 
-#[cfg(target_os = "macos")]
 use sysctl::Sysctl;
 
-use platform_info::*;
-
-use rustc_hash::FxHashSet;
+use platform_info::{PlatformInfo, PlatformInfoAPI, UNameAPI};
 
 use itertools::Itertools;
 
 use std::{
-    alloc::Layout,
     mem::{self, MaybeUninit},
     net::{Ipv4Addr, Ipv6Addr},
 };
 
 use libc::timespec;
 
-use crate::info::OSInfo;
+use crate::{
+    config::{IpVersion, TopSort},
+    info::{
+        accessibility_summary, errors, ip_addresses, top_processes,
+        values::{Cpu, DiskUsage, Memory},
+        OSInfo, ProcessSample,
+    },
+    util::uptime_format,
+};
 
 pub struct MacInfo {
-    uts: PlatformInfo,
+    uts: Option<PlatformInfo>,
+}
+
+impl Default for MacInfo {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MacInfo {
+    #[must_use]
     pub fn new() -> Self {
-        MacInfo {
-            uts: PlatformInfo::new().unwrap(),
+        Self {
+            uts: PlatformInfo::new().ok(),
+        }
+    }
+}
+
+type CGDisplayModeRef = *mut std::ffi::c_void;
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGGetActiveDisplayList(max: u32, displays: *mut u32, count: *mut u32) -> i32;
+    fn CGDisplayIsMain(display: u32) -> u32;
+    fn CGDisplayIsBuiltin(display: u32) -> u32;
+    fn CGDisplayCopyDisplayMode(display: u32) -> CGDisplayModeRef;
+    fn CGDisplayModeGetWidth(mode: CGDisplayModeRef) -> usize;
+    fn CGDisplayModeGetPixelWidth(mode: CGDisplayModeRef) -> usize;
+    fn CGDisplayModeGetPixelHeight(mode: CGDisplayModeRef) -> usize;
+    fn CGDisplayModeGetRefreshRate(mode: CGDisplayModeRef) -> f64;
+    fn CGDisplayModeRelease(mode: CGDisplayModeRef);
+}
+
+/// Resolution, refresh rate and scaling factor of an active display, e.g.
+/// `3024x1964 @ 120Hz, scale 2`
+fn display_mode(display: u32) -> Option<String> {
+    let mode = unsafe { CGDisplayCopyDisplayMode(display) };
+    if mode.is_null() {
+        return None;
+    }
+    let (points, width, height, refresh) = unsafe {
+        (
+            CGDisplayModeGetWidth(mode),
+            CGDisplayModeGetPixelWidth(mode),
+            CGDisplayModeGetPixelHeight(mode),
+            CGDisplayModeGetRefreshRate(mode),
+        )
+    };
+    unsafe { CGDisplayModeRelease(mode) };
+    let mut details = vec![if refresh > 0.0 {
+        format!("{width}x{height} @ {refresh:.0}Hz")
+    } else {
+        // Built-in panels without a fixed rate, like older MacBooks, report zero
+        format!("{width}x{height}")
+    }];
+    if points != 0 && points != width {
+        details.push(format!("scale {}", width as f64 / points as f64));
+    }
+    if unsafe { CGDisplayIsMain(display) } != 0 {
+        details.push(String::from("primary"));
+    }
+    Some(details.join(", "))
+}
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOServiceMatching(name: *const std::ffi::c_char) -> *mut std::ffi::c_void;
+    fn IOServiceGetMatchingService(main_port: u32, matching: *mut std::ffi::c_void) -> u32;
+    fn IOServiceOpen(service: u32, task: u32, kind: u32, connection: *mut u32) -> i32;
+    fn IOServiceClose(connection: u32) -> i32;
+    fn IOObjectRelease(object: u32) -> i32;
+    fn IOConnectCallStructMethod(
+        connection: u32,
+        selector: u32,
+        input: *const std::ffi::c_void,
+        input_size: usize,
+        output: *mut std::ffi::c_void,
+        output_size: *mut usize,
+    ) -> i32;
+}
+
+/// The SMC's request and reply, as laid out by `AppleSMC`, padding included
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+#[allow(dead_code)]
+struct SmcKeyData {
+    key: u32,
+    version: SmcVersion,
+    limits: SmcLimits,
+    info: SmcKeyInfo,
+    result: u8,
+    status: u8,
+    command: u8,
+    data32: u32,
+    bytes: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+#[allow(dead_code)]
+struct SmcVersion {
+    major: u8,
+    minor: u8,
+    build: u8,
+    reserved: u8,
+    release: u16,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+#[allow(dead_code)]
+struct SmcLimits {
+    version: u16,
+    length: u16,
+    cpu: u32,
+    gpu: u32,
+    memory: u32,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+#[allow(dead_code)]
+struct SmcKeyInfo {
+    data_size: u32,
+    data_type: u32,
+    attributes: u8,
+}
+
+const SMC_CALL_METHOD: u32 = 2;
+const SMC_READ_KEY: u8 = 5;
+const SMC_GET_KEY_INFO: u8 = 9;
+/// CPU temperature keys, for Intel Macs (proximity and die) and then Apple silicon (P and E cores)
+const SMC_CPU_KEYS: [[u8; 4]; 6] = [*b"TC0P", *b"TC0D", *b"Tp09", *b"Tp0T", *b"Tp01", *b"Tp05"];
+
+/// The `AppleSMC` driver, closed when dropped
+struct Smc(u32);
+
+impl Smc {
+    fn open() -> Option<Self> {
+        unsafe {
+            let service = IOServiceGetMatchingService(0, IOServiceMatching(c"AppleSMC".as_ptr()));
+            if service == 0 {
+                return None;
+            }
+            let mut connection = 0;
+            #[allow(deprecated)]
+            let res = IOServiceOpen(service, libc::mach_task_self(), 0, &raw mut connection);
+            IOObjectRelease(service);
+            (res == 0).then_some(Self(connection))
         }
     }
+
+    fn call(&self, input: &SmcKeyData) -> Option<SmcKeyData> {
+        let mut output = SmcKeyData::default();
+        let mut size = mem::size_of::<SmcKeyData>();
+        let res = unsafe {
+            IOConnectCallStructMethod(
+                self.0,
+                SMC_CALL_METHOD,
+                std::ptr::from_ref(input).cast(),
+                mem::size_of::<SmcKeyData>(),
+                std::ptr::from_mut(&mut output).cast(),
+                &raw mut size,
+            )
+        };
+        (res == 0 && output.result == 0).then_some(output)
+    }
+
+    /// A temperature key's value in degrees Celsius, which is fixed point `sp78` on Intel Macs
+    /// and a float on Apple silicon
+    fn temperature(&self, key: [u8; 4]) -> Option<f64> {
+        let key = u32::from_be_bytes(key);
+        let info = self.call(&SmcKeyData {
+            key,
+            command: SMC_GET_KEY_INFO,
+            ..SmcKeyData::default()
+        })?;
+        let value = self.call(&SmcKeyData {
+            key,
+            info: SmcKeyInfo {
+                data_size: info.info.data_size,
+                ..SmcKeyInfo::default()
+            },
+            command: SMC_READ_KEY,
+            ..SmcKeyData::default()
+        })?;
+        let bytes = value.bytes;
+        match &info.info.data_type.to_be_bytes() {
+            b"sp78" => Some(f64::from(i16::from_be_bytes([bytes[0], bytes[1]])) / 256.0),
+            b"flt " => Some(f64::from(f32::from_le_bytes([
+                bytes[0], bytes[1], bytes[2], bytes[3],
+            ]))),
+            _ => None,
+        }
+        // Missing sensors read as zero, and some as a placeholder far out of range
+        .filter(|x| (1.0..150.0).contains(x))
+    }
+}
+
+impl Drop for Smc {
+    fn drop(&mut self) {
+        unsafe { IOServiceClose(self.0) };
+    }
+}
+
+/// Name, resident memory and CPU time of every process the user may inspect, from libproc
+#[allow(deprecated)]
+fn process_samples() -> ProcessSample {
+    let count = unsafe { libc::proc_listallpids(std::ptr::null_mut(), 0) };
+    let mut pids = vec![0; usize::try_from(count).unwrap_or_default() + 16];
+    let size = i32::try_from(pids.len() * mem::size_of::<libc::pid_t>()).unwrap_or(i32::MAX);
+    let count = unsafe { libc::proc_listallpids(pids.as_mut_ptr().cast(), size) };
+    pids.truncate(usize::try_from(count).unwrap_or_default());
+
+    // CPU times are in Mach ticks, which aren't nanoseconds on Apple Silicon
+    let mut timebase = libc::mach_timebase_info { numer: 1, denom: 1 };
+    unsafe {
+        libc::mach_timebase_info(std::ptr::addr_of_mut!(timebase));
+    }
+    let info_size = i32::try_from(mem::size_of::<libc::proc_taskinfo>()).unwrap_or_default();
+    pids.into_iter()
+        .filter_map(|pid| {
+            let mut info = MaybeUninit::<libc::proc_taskinfo>::uninit();
+            let res = unsafe {
+                libc::proc_pidinfo(
+                    pid,
+                    libc::PROC_PIDTASKINFO,
+                    0,
+                    info.as_mut_ptr().cast(),
+                    info_size,
+                )
+            };
+            if res != info_size {
+                return None;
+            }
+            let info = unsafe { info.assume_init() };
+            let mut name = [0u8; 256];
+            let len = unsafe { libc::proc_name(pid, name.as_mut_ptr().cast(), 256) };
+            let name = String::from_utf8_lossy(name.get(..usize::try_from(len).ok()?)?);
+            let ticks = info.pti_total_user + info.pti_total_system;
+            Some((
+                u32::try_from(pid).ok()?,
+                (
+                    name.into_owned(),
+                    info.pti_resident_size,
+                    ticks * u64::from(timebase.numer) / u64::from(timebase.denom.max(1)),
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// Whether a setting of the Accessibility pane is turned on
+fn universal_access(key: &str) -> bool {
+    std::process::Command::new("defaults")
+        .args(["read", "com.apple.universalaccess", key])
+        .output()
+        .is_ok_and(|x| x.status.success() && String::from_utf8_lossy(&x.stdout).trim() == "1")
 }
 
 impl OSInfo for MacInfo {
     fn os(&self) -> Option<ArcStr> {
-        let system_version = plist::Value::from_file(std::path::Path::new(
-            "/System/Library/CoreServices/SystemVersion.plist",
-        ))
-        .unwrap();
-        let system_version = system_version.as_dictionary().unwrap();
-
-        Some(ArcStr::from(format!(
+        const PATH: &str = "/System/Library/CoreServices/SystemVersion.plist";
+        let system_version = plist::Value::from_file(PATH)
+            .map_err(|err| errors::record(format_args!("{err} reading {PATH}")))
+            .ok()?;
+        let system_version = system_version.as_dictionary()?;
+        let field = |key: &str| system_version.get(key)?.as_string();
+
+        Some(arcstr::format!(
             "{} {}",
-            system_version
-                .get("ProductName")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            system_version
-                .get("ProductVersion")
-                .unwrap()
-                .as_string()
-                .unwrap()
-        )))
+            field("ProductName")?,
+            field("ProductVersion")?
+        ))
     }
 
     fn hostname(&self) -> Option<ArcStr> {
-        Some(ArcStr::from(whoami::hostname()))
+        Some(ArcStr::from(self.uts.as_ref()?.nodename().to_str()?))
     }
 
     fn displays(&self) -> Vec<ArcStr> {
-        vec![]
+        let mut count = 0;
+        if unsafe { CGGetActiveDisplayList(0, std::ptr::null_mut(), &raw mut count) } != 0 {
+            return Vec::new();
+        }
+        let mut displays = vec![0; count as usize];
+        if unsafe { CGGetActiveDisplayList(count, displays.as_mut_ptr(), &raw mut count) } != 0 {
+            return Vec::new();
+        }
+        displays.truncate(count as usize);
+        let mut external = 0;
+        displays
+            .into_iter()
+            .filter_map(|display| {
+                let name = if unsafe { CGDisplayIsBuiltin(display) } != 0 {
+                    String::from("Built-in")
+                } else {
+                    external += 1;
+                    format!("External {external}")
+                };
+                Some(arcstr::format!("{name}: {}", display_mode(display)?))
+            })
+            .collect()
     }
 
     fn machine(&self) -> Option<ArcStr> {
@@ -68,7 +334,7 @@ impl OSInfo for MacInfo {
     }
 
     fn kernel(&self) -> Option<ArcStr> {
-        Some(ArcStr::from(self.uts.release().to_string_lossy()))
+        Some(ArcStr::from(self.uts.as_ref()?.release().to_string_lossy()))
     }
 
     #[allow(clippy::similar_names)]
@@ -92,20 +358,30 @@ impl OSInfo for MacInfo {
     }
 
     fn shell(&self) -> Option<ArcStr> {
-        Some(ArcStr::from(std::env!("SHELL")))
+        std::env::var("SHELL").ok().map(ArcStr::from)
     }
 
-    fn cpu(&self) -> Option<ArcStr> {
-        let model = sysctl::Ctl::new("machdep.cpu.brand_string")
-            .unwrap()
-            .value_string()
-            .unwrap();
-        let core_count = sysctl::Ctl::new("machdep.cpu.core_count")
-            .unwrap()
-            .value_string()
-            .unwrap();
+    fn cpu(&self) -> Option<Cpu> {
+        let read = |name: &str| {
+            sysctl::Ctl::new(name)
+                .and_then(|ctl| ctl.value_string())
+                .map_err(|err| errors::record(format_args!("{err} reading {name}")))
+                .ok()
+        };
+        let model = read("machdep.cpu.brand_string")?;
+        let core_count = read("machdep.cpu.core_count").unwrap_or_default();
 
-        Some(arcstr::format!("{} ({})", model, core_count))
+        Some(Cpu {
+            cores: core_count.parse().ok(),
+            ..Cpu::new(model)
+        })
+    }
+
+    fn cpu_temp(&self) -> Option<f64> {
+        let smc = Smc::open()?;
+        SMC_CPU_KEYS
+            .into_iter()
+            .find_map(|key| smc.temperature(key))
     }
 
     fn username(&self) -> Option<ArcStr> {
@@ -132,88 +408,103 @@ impl OSInfo for MacInfo {
         None
     }
 
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         None
     }
 
-    fn ip(&self) -> Vec<ArcStr> {
+    fn swap(&self) -> Option<Memory> {
+        let mut usage = MaybeUninit::<libc::xsw_usage>::uninit();
+        let mut len = mem::size_of::<libc::xsw_usage>();
+        let res = unsafe {
+            libc::sysctlbyname(
+                c"vm.swapusage".as_ptr(),
+                usage.as_mut_ptr().cast(),
+                &raw mut len,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        if res != 0 {
+            return None;
+        }
+        let usage = unsafe { usage.assume_init() };
+        (usage.xsu_total > 0).then_some(Memory {
+            used: usage.xsu_used,
+            total: usage.xsu_total,
+            cgroup: false,
+        })
+    }
+
+    fn top(&self, by: TopSort) -> Option<ArcStr> {
+        top_processes(by, process_samples).map(ArcStr::from)
+    }
+
+    fn ip(&self, version: IpVersion) -> Vec<(ArcStr, ArcStr)> {
         use libc::{getifaddrs, AF_INET, AF_INET6, IFF_LOOPBACK, IFF_RUNNING};
-        let mut ipv4_addrs = FxHashSet::<Ipv4Addr>::default();
-        let mut ipv6_addrs = FxHashSet::<Ipv6Addr>::default();
+        use std::ffi::CStr;
+        let mut ipv4_addrs = Vec::new();
+        let mut ipv6_addrs = Vec::new();
         unsafe {
-            let mut addrs = mem::MaybeUninit::<*mut libc::ifaddrs>::uninit();
-            getifaddrs(addrs.as_mut_ptr());
-            while let Some(addr) = addrs.assume_init().as_ref() {
-                if addr.ifa_addr.is_null() {
-                    addrs = MaybeUninit::new(addr.ifa_next);
-                    continue;
-                }
-                if addr.ifa_flags & IFF_RUNNING as u32 == 0 {
-                    addrs = MaybeUninit::new(addr.ifa_next);
-                    continue;
-                }
-                if addr.ifa_flags & IFF_LOOPBACK as u32 != 0 {
-                    addrs = MaybeUninit::new(addr.ifa_next);
+            let mut addrs = std::ptr::null_mut();
+            if getifaddrs(&raw mut addrs) != 0 {
+                return Vec::new();
+            }
+            let mut current = addrs.cast_const();
+            while let Some(addr) = current.as_ref() {
+                current = addr.ifa_next;
+                if addr.ifa_addr.is_null()
+                    || addr.ifa_flags & IFF_RUNNING as u32 == 0
+                    || addr.ifa_flags & IFF_LOOPBACK as u32 != 0
+                {
                     continue;
                 }
-                if i32::from((*addr.ifa_addr).sa_family) == AF_INET {
-                    let ipv4 = (*(addr.ifa_addr).cast::<libc::sockaddr_in>())
-                        .sin_addr
-                        .s_addr
-                        .swap_bytes();
-                    ipv4_addrs.insert(Ipv4Addr::from(ipv4));
-                }
-                if i32::from((*addr.ifa_addr).sa_family) == AF_INET6 {
-                    let ipv6 = (*(addr.ifa_addr).cast::<libc::sockaddr_in6>())
-                        .sin6_addr
-                        .s6_addr;
-                    if !ipv6.starts_with(&[0xfe, 0x80]) {
-                        ipv6_addrs.insert(Ipv6Addr::from(ipv6));
+                let name = ArcStr::from(CStr::from_ptr(addr.ifa_name).to_string_lossy());
+                match i32::from((*addr.ifa_addr).sa_family) {
+                    AF_INET => {
+                        #[allow(clippy::cast_ptr_alignment)]
+                        let ipv4 = (*(addr.ifa_addr).cast::<libc::sockaddr_in>())
+                            .sin_addr
+                            .s_addr;
+                        ipv4_addrs.push((name.clone(), Ipv4Addr::from(u32::from_be(ipv4))));
                     }
+                    AF_INET6 => {
+                        #[allow(clippy::cast_ptr_alignment)]
+                        let ipv6 = (*(addr.ifa_addr).cast::<libc::sockaddr_in6>())
+                            .sin6_addr
+                            .s6_addr;
+                        ipv6_addrs.push((name, Ipv6Addr::from(ipv6)));
+                    }
+                    _ => {}
                 }
-                // if addr.ifa_next.is_null() {
-                //     break;
-                // }
-                addrs = MaybeUninit::new(addr.ifa_next);
             }
-        };
+            libc::freeifaddrs(addrs);
+        }
+        ip_addresses(version, ipv4_addrs, ipv6_addrs)
+    }
 
-        vec![
-            ArcStr::from(
-                ipv4_addrs
-                    .iter()
-                    .map(std::string::ToString::to_string)
-                    .collect_vec()
-                    .join(", "),
-            ),
-            /*ipv6_addrs.iter().fold(ArcStr::new(), |x, y| {
-                (if x.is_empty() { x } else { x + ", " }) + &y.to_string()
-            }),*/
-        ]
-    }
-
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    fn disks(&self) -> Vec<(ArcStr, DiskUsage)> {
         vec![]
     }
 
     fn battery(&self) -> Option<ArcStr> {
-        let manager = battery::Manager::new().unwrap();
-        Some(ArcStr::from(
-            manager
-                .batteries()
-                .unwrap()
-                .map(|battery| {
-                    // Get the bare ratio without units
-                    let ratio: f64 = battery
-                        .unwrap()
-                        .state_of_charge()
-                        .get::<battery::units::ratio::ratio>()
-                        .into();
-                    // Reformat as percent
-                    format!("{:.0}%", ratio * 100.0)
-                })
-                .join(", "),
-        ))
+        let manager = battery::Manager::new().map_err(errors::record).ok()?;
+        let res = manager
+            .batteries()
+            .map_err(errors::record)
+            .ok()?
+            // Batteries that can't be read are skipped
+            .filter_map(Result::ok)
+            .map(|battery| {
+                // Get the bare ratio without units
+                let ratio: f64 = battery
+                    .state_of_charge()
+                    .get::<battery::units::ratio::ratio>()
+                    .into();
+                // Reformat as percent
+                format!("{:.0}%", ratio * 100.0)
+            })
+            .join(", ");
+        (!res.is_empty()).then(|| ArcStr::from(res))
     }
 
     fn locale(&self) -> Option<ArcStr> {
@@ -226,17 +517,16 @@ impl OSInfo for MacInfo {
     }
 
     fn uptime(&self) -> Option<ArcStr> {
-        unsafe {
-            let time: *mut timespec = std::alloc::alloc(Layout::new::<timespec>()).cast();
-            libc::clock_gettime(libc::CLOCK_MONOTONIC_RAW, time);
-            Some(ArcStr::from(
-                (
-                    time::Duration::seconds(time.as_ref().unwrap().tv_sec)
-                    // + time::Duration::nanoseconds(time.as_ref().unwrap().tv_nsec)
-                )
-                .to_string(),
-            ))
+        let mut time = timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        let res =
+            unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC_RAW, std::ptr::addr_of_mut!(time)) };
+        if res != 0 {
+            return None;
         }
+        Some(ArcStr::from(uptime_format(time.tv_sec.try_into().ok()?)))
     }
 
     // TODO
@@ -244,6 +534,18 @@ impl OSInfo for MacInfo {
         None
     }
 
+    fn screen_reader(&self) -> Option<ArcStr> {
+        universal_access("voiceOverOnOffKey").then(|| ArcStr::from("VoiceOver"))
+    }
+
+    fn accessibility(&self) -> Option<ArcStr> {
+        Some(accessibility_summary(
+            self.screen_reader().as_deref(),
+            universal_access("increaseContrast"),
+            universal_access("reduceMotion"),
+        ))
+    }
+
     fn id(&self) -> ArcStr {
         ArcStr::from("macos")
     }
@@ -0,0 +1,104 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::UNIX_EPOCH,
+};
+
+use rustc_hash::FxHashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::version::VERSION;
+
+static CACHE: OnceLock<(PathBuf, Mutex<CacheFile>)> = OnceLock::new();
+
+/// Memoized values, thrown away when mirafetch is upgraded
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: String,
+    entries: FxHashMap<String, Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    sources: Vec<Stamp>,
+    value: Value,
+}
+
+/// Modification time and size of a source file, both `None` when it doesn't exist
+#[derive(Serialize, Deserialize, PartialEq, Eq)]
+struct Stamp {
+    path: PathBuf,
+    modified: Option<u128>,
+    len: Option<u64>,
+}
+
+impl Stamp {
+    fn new(path: &Path) -> Self {
+        let meta = fs::metadata(path).ok();
+        Self {
+            path: path.to_path_buf(),
+            modified: meta
+                .as_ref()
+                .and_then(|x| x.modified().ok())
+                .and_then(|x| x.duration_since(UNIX_EPOCH).ok())
+                .map(|x| x.as_nanos()),
+            len: meta.map(|x| x.len()),
+        }
+    }
+}
+
+/// Keep memoized values in `path`, starting with the ones saved by earlier runs
+///
+/// Until this is called [`memoized`] always does the work
+pub fn enable(path: PathBuf) {
+    let file = fs::read_to_string(&path)
+        .ok()
+        .and_then(|x| serde_json::from_str::<CacheFile>(&x).ok())
+        .filter(|x| x.version == VERSION)
+        .unwrap_or_else(|| CacheFile {
+            version: VERSION.to_string(),
+            entries: FxHashMap::default(),
+        });
+    let _ = CACHE.set((path, Mutex::new(file)));
+}
+
+/// Reuse the value stored under `key` while none of its `sources` changed, otherwise run `f`
+///
+/// Sources are compared by modification time and size, so the value is worked out again
+/// as soon as a file it was derived from is replaced, appears or goes away
+pub fn memoized<T: Serialize + DeserializeOwned>(
+    key: &str,
+    sources: &[PathBuf],
+    f: impl FnOnce() -> T,
+) -> T {
+    let Some((path, file)) = CACHE.get() else {
+        return f();
+    };
+    let stamps: Vec<Stamp> = sources.iter().map(|x| Stamp::new(x)).collect();
+    let hit = file.lock().ok().and_then(|file| {
+        let entry = file.entries.get(key).filter(|x| x.sources == stamps)?;
+        serde_json::from_value(entry.value.clone()).ok()
+    });
+    if let Some(value) = hit {
+        return value;
+    }
+
+    let value = f();
+    if let (Ok(json), Ok(mut file)) = (serde_json::to_value(&value), file.lock()) {
+        let entry = Entry {
+            sources: stamps,
+            value: json,
+        };
+        file.entries.insert(key.to_string(), entry);
+        // Failing to save only means the work is done again next time
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(data) = serde_json::to_string(&*file) {
+            let _ = fs::write(path, data);
+        }
+    }
+    value
+}
@@ -1,17 +1,58 @@
-use std::{fmt::Display, sync::Arc};
+use std::{
+    fmt::Display,
+    net::{Ipv4Addr, Ipv6Addr},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 use arcstr::ArcStr;
 use crossterm::style::{Color, Stylize};
+use itertools::Itertools;
+use rustc_hash::FxHashMap;
+use unicode_width::UnicodeWidthStr;
 
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+use crate::info::bsdinfo::BsdInfo as get_info;
 #[cfg(target_os = "ios")]
 use crate::info::iosinfo::IosInfo as get_info;
 #[cfg(target_os = "linux")]
 use crate::info::linuxinfo::LinuxInfo as get_info;
+#[cfg(target_os = "macos")]
+use crate::info::macinfo::MacInfo as get_info;
 #[cfg(target_family = "windows")]
 use crate::info::wininfo::WindowsInfo as get_info;
+use crate::{
+    config::{Config, IpVersion, Preset, TopSort},
+    info::values::{Cpu, DiskUsage, Memory, Temperature},
+    inventory::{Drive, Inventory},
+    util::bytecount_format,
+    version,
+};
+pub mod bsdinfo;
+pub mod dotfiles;
+#[cfg(feature = "drm")]
+mod drm;
+mod errors;
 pub mod iosinfo;
 pub mod linuxinfo;
+#[cfg(target_os = "macos")]
+pub mod macinfo;
+pub mod memo;
+mod sqlite;
+pub mod sysfs;
+#[cfg(feature = "io-uring")]
+mod uring;
+pub mod values;
+#[cfg(feature = "wayland")]
+mod wayland;
 pub mod wininfo;
+#[cfg(feature = "x11")]
+mod x11;
 pub trait OSInfo: Send + Sync {
     fn sys_font(&self) -> Option<ArcStr> {
         None
@@ -29,10 +70,42 @@ pub trait OSInfo: Send + Sync {
     fn gpus(&self) -> Vec<ArcStr> {
         Vec::new()
     }
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         None
     }
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    /// Used and total swap, `None` when there is none
+    fn swap(&self) -> Option<Memory> {
+        None
+    }
+    /// Share of the last 10 seconds that tasks stalled waiting for the CPU, memory or I/O
+    fn pressure(&self) -> Option<ArcStr> {
+        None
+    }
+    /// The programs using the most memory or CPU
+    fn top(&self, _by: TopSort) -> Option<ArcStr> {
+        None
+    }
+    /// The programs using the most GPU memory
+    fn top_gpu(&self) -> Option<ArcStr> {
+        None
+    }
+    /// Version, sample rate, quantum and active streams of the `PipeWire` server
+    fn pipewire(&self) -> Option<ArcStr> {
+        None
+    }
+    /// Preemption model, realtime priority limit and whether interrupts are handled in threads
+    fn realtime(&self) -> Option<ArcStr> {
+        None
+    }
+    /// The running screen reader, such as Orca
+    fn screen_reader(&self) -> Option<ArcStr> {
+        None
+    }
+    /// Screen reader, high contrast and reduced motion, see [`accessibility_summary`]
+    fn accessibility(&self) -> Option<ArcStr> {
+        None
+    }
+    fn disks(&self) -> Vec<(ArcStr, DiskUsage)> {
         Vec::new()
     }
     fn battery(&self) -> Option<ArcStr> {
@@ -52,10 +125,27 @@ pub trait OSInfo: Send + Sync {
     }
     fn id(&self) -> ArcStr;
     fn uptime(&self) -> Option<ArcStr>;
-    fn ip(&self) -> Vec<ArcStr>;
+    /// Shell frameworks, plugin managers and prompts in use
+    fn shell_framework(&self) -> Option<ArcStr> {
+        None
+    }
+    /// XDG base directories moved away from their defaults
+    fn xdg(&self) -> Option<ArcStr> {
+        None
+    }
+    /// Whether the clock is synchronized over NTP, and how far off it is
+    fn time_sync(&self) -> Option<ArcStr> {
+        None
+    }
+    /// Interface names and addresses of `version`, see [`ip_addresses`]
+    fn ip(&self, version: IpVersion) -> Vec<(ArcStr, ArcStr)>;
     fn displays(&self) -> Vec<ArcStr> {
         Vec::new()
     }
+    /// Home page, support and bug report URLs of the OS, labelled `Link (Home)` and so on
+    fn links(&self) -> Vec<(ArcStr, ArcStr)> {
+        Vec::new()
+    }
 
     fn hostname(&self) -> Option<ArcStr>;
 
@@ -78,112 +168,557 @@ pub trait OSInfo: Send + Sync {
     fn shell(&self) -> Option<ArcStr> {
         None
     }
-    fn cpu(&self) -> Option<ArcStr> {
+    fn cpu(&self) -> Option<Cpu> {
+        None
+    }
+
+    /// Base (or minimum) and boost clock speed in MHz
+    fn cpu_freq_range(&self) -> Option<(f64, f64)> {
+        None
+    }
+
+    /// CPU package temperature in degrees Celsius
+    fn cpu_temp(&self) -> Option<f64> {
         None
     }
 
     fn username(&self) -> Option<ArcStr> {
         None
     }
+
+    fn nix(&self) -> Option<ArcStr> {
+        None
+    }
+
+    fn brightness(&self) -> Option<ArcStr> {
+        None
+    }
+
+    fn power(&self) -> Option<ArcStr> {
+        None
+    }
+
+    /// Installed packages counted per package manager, e.g. `1234 (pacman), 56 (flatpak)`
+    fn packages(&self) -> Option<ArcStr> {
+        None
+    }
+
+    /// Machine architecture, endianness, and page size
+    fn arch(&self) -> Option<ArcStr> {
+        None
+    }
+
+    /// The host system when running inside a container such as toolbox
+    fn host_os(&self) -> Option<ArcStr> {
+        None
+    }
+
+    /// Icon ID of the host system when running inside a container
+    fn host_id(&self) -> Option<ArcStr> {
+        None
+    }
+
+    /// Preset matching what kind of machine this is, e.g. a laptop or a server
+    fn role(&self) -> Option<Preset> {
+        None
+    }
+
+    /// Serial number of the machine, for `mirafetch inventory`
+    fn serial_number(&self) -> Option<ArcStr> {
+        None
+    }
+
+    /// Asset tag set by the vendor or the IT department
+    fn asset_tag(&self) -> Option<ArcStr> {
+        None
+    }
+
+    /// Build or image version of the OS, more precise than the version in the OS line
+    fn os_build(&self) -> Option<ArcStr> {
+        None
+    }
+
+    /// Names and MAC addresses of the physical network interfaces
+    fn mac_addresses(&self) -> Vec<(ArcStr, ArcStr)> {
+        Vec::new()
+    }
+
+    /// Physical drives that report a serial number
+    fn disk_serials(&self) -> Vec<Drive> {
+        Vec::new()
+    }
+}
+
+/// The current platform's backend, for asking for single modules without collecting the rest
+///
+/// Use [`Info::from_config`] to collect everything at once, in parallel
+#[must_use]
+pub fn backend() -> impl OSInfo {
+    get_info::new()
+}
+
+/// Connect to the display server now, for hardened mode, which refuses to connect to any
+/// socket once its seccomp filter is applied
+#[cfg(target_os = "linux")]
+pub fn preconnect() {
+    linuxinfo::preconnect();
+    #[cfg(feature = "wayland")]
+    wayland::preconnect();
+    #[cfg(feature = "x11")]
+    if std::env::var_os("DISPLAY").is_some() {
+        x11::preconnect();
+    }
+}
+
+/// Guess the preset for the current machine from its chassis and virtualization
+#[must_use]
+pub fn role() -> Option<Preset> {
+    get_info::new().role()
 }
 
-#[derive(Debug)]
+/// The screen reader running on this machine, which switches the output to plain lines
+#[must_use]
+pub fn screen_reader() -> Option<ArcStr> {
+    get_info::new().screen_reader()
+}
+
+/// The accessibility features in use, e.g. `Orca, high contrast, reduced motion`
+#[must_use]
+pub fn accessibility_summary(
+    screen_reader: Option<&str>,
+    high_contrast: bool,
+    reduced_motion: bool,
+) -> ArcStr {
+    let res = screen_reader
+        .into_iter()
+        .chain(high_contrast.then_some("high contrast"))
+        .chain(reduced_motion.then_some("reduced motion"))
+        .join(", ");
+    if res.is_empty() {
+        arcstr::literal!("none enabled")
+    } else {
+        res.into()
+    }
+}
+
+/// Collect the details for `mirafetch inventory` from the current platform's backend
+#[must_use]
+pub fn inventory() -> Inventory {
+    Inventory::from_source(&get_info::new())
+}
+
+/// Addresses of `version` with the name of their interface, the IPv4 ones first
+///
+/// Only global IPv6 addresses are kept, as link-local and unique local ones can't be reached
+/// from outside the network. Backends leave out temporary and deprecated ones where they can
+/// tell them apart
+#[must_use]
+pub fn ip_addresses(
+    version: IpVersion,
+    v4: impl IntoIterator<Item = (ArcStr, Ipv4Addr)>,
+    v6: impl IntoIterator<Item = (ArcStr, Ipv6Addr)>,
+) -> Vec<(ArcStr, ArcStr)> {
+    let (show_v4, show_v6) = match version {
+        IpVersion::V4 => (true, false),
+        IpVersion::V6 => (false, true),
+        IpVersion::Both => (true, true),
+    };
+    let v4 = v4
+        .into_iter()
+        .filter(|_| show_v4)
+        .sorted()
+        .dedup()
+        .map(|(name, addr)| (name, arcstr::format!("{addr}")));
+    let v6 = v6
+        .into_iter()
+        .filter(|(_, addr)| show_v6 && addr.segments()[0] & 0xe000 == 0x2000)
+        .sorted()
+        .dedup()
+        .map(|(name, addr)| (name, arcstr::format!("{addr}")));
+    v4.chain(v6).collect()
+}
+
+/// Where the public IP is looked up unless the `public_ip_url` setting says otherwise
+pub const PUBLIC_IP_URL: &str = "https://api.ipify.org";
+
+/// The machine's address as seen from the internet, from `url` answering with just the address
+#[cfg(feature = "public-ip")]
+fn lookup_public_ip(url: &str) -> Option<ArcStr> {
+    let body = ureq::get(url)
+        .timeout(Duration::from_secs(2))
+        .set(
+            "User-Agent",
+            concat!("mirafetch/", env!("CARGO_PKG_VERSION")),
+        )
+        .call()
+        .map_err(|err| errors::record(format_args!("{err}")))
+        .ok()?
+        .into_string()
+        .map_err(|err| errors::record(format_args!("{err} reading the answer of {url}")))
+        .ok()?;
+    // Anything else, like an error page, is left out rather than printed to the terminal
+    let addr = body
+        .trim()
+        .parse::<std::net::IpAddr>()
+        .map_err(|_| errors::record(format_args!("{url} did not answer with an address")))
+        .ok()?;
+    Some(arcstr::format!("{addr}"))
+}
+
+/// Whether the interface `name` is shown with the `ip_interfaces` setting's `patterns`
+///
+/// Patterns starting with `!` hide the interfaces they match, and the others show only the
+/// interfaces they match, e.g. `["!docker*", "!veth*"]` or `["wl*"]`
+#[must_use]
+pub fn interface_shown(patterns: &[String], name: &str) -> bool {
+    let matches = |pattern: &str| glob::Pattern::new(pattern).is_ok_and(|x| x.matches(name));
+    let (hidden, shown): (Vec<_>, Vec<_>) = patterns.iter().partition(|x| x.starts_with('!'));
+    !hidden.iter().any(|x| matches(&x[1..]))
+        && (shown.is_empty() || shown.iter().any(|x| matches(x)))
+}
+
+/// The three programs using the most GPU memory from the bytes used by each, e.g.
+/// `firefox 1.2 GiB, steam 800.0 MiB`
+#[must_use]
+pub fn top_gpu_users(users: impl IntoIterator<Item = (String, u64)>) -> Option<ArcStr> {
+    let top = users
+        .into_iter()
+        .filter(|(_, x)| *x > 0)
+        .sorted_unstable_by(|(_, x), (_, y)| y.cmp(x))
+        .take(3)
+        .map(|(name, x)| format!("{name} {}", bytecount_format(x, 1)))
+        .join(", ");
+    (!top.is_empty()).then(|| ArcStr::from(top))
+}
+
+/// Name, resident memory in bytes and CPU time in nanoseconds of each process, by process ID
+pub type ProcessSample = FxHashMap<u32, (String, u64, u64)>;
+
+/// The three programs using the most memory or CPU, e.g. `firefox 2.1 GiB, code 1.4 GiB`
+///
+/// Processes with the same name are added up, so a browser's many processes count as one.
+/// CPU use is measured between two samples a fifth of a second apart
+#[must_use]
+pub fn top_processes(by: TopSort, sample: impl Fn() -> ProcessSample) -> Option<String> {
+    const INTERVAL: Duration = Duration::from_millis(200);
+    let own = std::process::id();
+    let first = sample();
+    let mut totals = FxHashMap::<String, u64>::default();
+    match by {
+        TopSort::Memory => {
+            for (_, (name, rss, _)) in first.into_iter().filter(|(pid, _)| *pid != own) {
+                *totals.entry(name).or_default() += rss;
+            }
+        }
+        TopSort::Cpu => {
+            std::thread::sleep(INTERVAL);
+            for (pid, (name, _, cpu)) in sample() {
+                // Skip processes that started in between, whose whole CPU time would count
+                let Some((_, _, before)) = first.get(&pid).filter(|(x, ..)| *x == name) else {
+                    continue;
+                };
+                if pid != own {
+                    *totals.entry(name).or_default() += cpu.saturating_sub(*before);
+                }
+            }
+        }
+    }
+    let mut totals = totals
+        .into_iter()
+        .filter(|(_, x)| *x > 0)
+        .collect::<Vec<_>>();
+    totals.sort_unstable_by(|(_, x), (_, y)| y.cmp(x));
+    let top = totals
+        .into_iter()
+        .take(3)
+        .map(|(name, x)| match by {
+            TopSort::Memory => format!("{name} {}", bytecount_format(x, 1)),
+            TopSort::Cpu => format!(
+                "{name} {:.0}%",
+                x as f64 / INTERVAL.as_nanos() as f64 * 100.0
+            ),
+        })
+        .collect::<Vec<_>>();
+    (!top.is_empty()).then(|| top.join(", "))
+}
+
+#[derive(Debug, Clone)]
 pub struct Info {
     pub os: Option<ArcStr>,
     pub machine: Option<ArcStr>,
     pub kernel: Option<ArcStr>,
     pub uptime: Option<ArcStr>,
+    pub time_sync: Option<ArcStr>,
+    pub packages: Option<ArcStr>,
     pub username: Option<ArcStr>,
     pub hostname: Option<ArcStr>,
     pub resolution: Vec<ArcStr>,
     pub wm: Option<ArcStr>,
     pub de: Option<ArcStr>,
     pub shell: Option<ArcStr>,
-    pub cpu: Option<ArcStr>,
+    pub shell_framework: Option<ArcStr>,
+    pub xdg: Option<ArcStr>,
+    pub cpu: Option<Cpu>,
     pub font: Option<ArcStr>,
     pub cursor: Option<ArcStr>,
     pub terminal: Option<ArcStr>,
     pub terminal_font: Option<ArcStr>,
     pub gpus: Vec<ArcStr>,
-    pub memory: Option<ArcStr>,
-    pub disks: Vec<(ArcStr, ArcStr)>,
+    pub memory: Option<Memory>,
+    pub swap: Option<Memory>,
+    pub pressure: Option<ArcStr>,
+    /// Programs using the most memory or CPU, only looked up with the `top` setting
+    pub top: Option<ArcStr>,
+    /// Programs using the most GPU memory, only looked up with the `top_gpu` setting
+    pub top_gpu: Option<ArcStr>,
+    /// The audio graph, only looked up with the `pipewire` setting
+    pub pipewire: Option<ArcStr>,
+    /// Readiness for low latency audio and control loops, only looked up with the `realtime`
+    /// setting
+    pub realtime: Option<ArcStr>,
+    /// Screen reader, high contrast and reduced motion, only looked up with the `accessibility`
+    /// setting
+    pub accessibility: Option<ArcStr>,
+    /// The running screen reader, only looked up while the `accessible` setting is left unset
+    pub screen_reader: Option<ArcStr>,
+    pub disks: Vec<(ArcStr, DiskUsage)>,
     pub battery: Option<ArcStr>,
     pub locale: Option<ArcStr>,
     pub theme: Option<ArcStr>,
     pub icons: Option<ArcStr>,
-    pub ip: Vec<ArcStr>,
+    /// Interface names and addresses
+    pub ip: Vec<(ArcStr, ArcStr)>,
+    /// Address as seen from the internet, only looked up with the `public_ip` setting
+    pub public_ip: Option<ArcStr>,
+    pub links: Vec<(ArcStr, ArcStr)>,
+    pub nix: Option<ArcStr>,
+    pub brightness: Option<ArcStr>,
+    pub power: Option<ArcStr>,
+    pub arch: Option<ArcStr>,
+    pub mirafetch: Option<ArcStr>,
+    pub host_os: Option<ArcStr>,
     pub id: ArcStr,
+    pub host_id: Option<ArcStr>,
+    /// Why modules came back empty, keyed by their label, only collected in strict mode
+    pub errors: FxHashMap<&'static str, ArcStr>,
+    /// How long each module took, keyed by their label, only collected when keeping stats
+    pub timings: FxHashMap<&'static str, Duration>,
 }
 
 impl Default for Info {
-    #[allow(clippy::default_trait_access)]
     fn default() -> Self {
-        // let mut sys = System::new_all();
-        let getter = Arc::new(get_info::new());
-        let _getter_clone = Arc::clone(&getter);
+        Self::from_config(&Config::default())
+    }
+}
+
+impl Info {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Labels of the modules that recorded why they failed, rather than just finding nothing
+    #[must_use]
+    pub fn failed_modules(&self) -> Vec<&'static str> {
+        self.errors
+            .iter()
+            .filter(|(_, err)| err.as_str() != NOT_DETECTED)
+            .map(|(label, _)| *label)
+            .sorted()
+            .collect()
+    }
+
+    /// Collect system information, honoring the collection options in the user's settings
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self::from_source(&get_info::new(), config)
+    }
+
+    /// Collect system information from `getter` rather than the current platform's backend
+    #[must_use]
+    pub fn from_source(getter: &impl OSInfo, config: &Config) -> Self {
+        Self::collect(getter, config, None)
+    }
+
+    /// Collect only the module labelled `module`, along with the fields outside of any module
+    /// such as the hostname
+    #[must_use]
+    pub fn from_config_only(config: &Config, module: &str) -> Self {
+        Self::collect(&get_info::new(), config, Some(module))
+    }
+
+    #[allow(clippy::default_trait_access, clippy::too_many_lines)]
+    fn collect(getter: &impl OSInfo, config: &Config, only: Option<&str>) -> Self {
+        let mut arch = Default::default();
         let mut battery = Default::default();
+        let mut brightness = Default::default();
         let mut cpu = Default::default();
+        let mut cpu_freq_range = None;
+        let mut cpu_temp = None;
         let mut cursor = Default::default();
         let mut de = Default::default();
         let mut disks = Default::default();
         let mut font = Default::default();
         let mut gpus = Default::default();
+        let mut host_id = Default::default();
+        let mut host_os = Default::default();
         let mut hostname = Default::default();
         let mut icons = Default::default();
         let mut id: ArcStr = Default::default();
         let mut ip = Default::default();
         let mut kernel = Default::default();
+        let mut links = Default::default();
         let mut locale = Default::default();
         let mut machine = Default::default();
         let mut memory = Default::default();
+        let mut swap = Default::default();
+        let mut nix = Default::default();
         let mut os = Default::default();
+        let mut packages = Default::default();
+        let mut power = Default::default();
+        let mut pressure = Default::default();
+        #[cfg_attr(not(feature = "public-ip"), allow(unused_mut))]
+        let mut public_ip = None;
         let mut resolution = Default::default();
         let mut shell = Default::default();
+        let mut shell_framework = Default::default();
         let mut terminal_font = Default::default();
         let mut terminal = Default::default();
         let mut theme = Default::default();
+        let mut top = Default::default();
+        let mut top_gpu = None;
+        let mut pipewire = None;
+        let mut realtime = None;
+        let mut accessibility = None;
+        let mut screen_reader = None;
+        let mut time_sync = Default::default();
         let mut uptime = Default::default();
         let mut username = Default::default();
         let mut wm = Default::default();
+        let mut xdg = Default::default();
+        let strict_errors = Mutex::default();
+        let module_timings = Mutex::default();
+        let tracking = Tracking {
+            errors: config.strict.unwrap_or_default().then_some(&strict_errors),
+            timings: config.stats.unwrap_or_default().then_some(&module_timings),
+            only,
+        };
         rayon::scope(|s| {
             // general_readout: general_readout.clone(),
-            (*s).spawn(|_| battery = getter.battery());
-            s.spawn(|_| cpu = getter.cpu());
-            s.spawn(|_| cursor = getter.cursor());
-            s.spawn(|_| de = getter.de());
-            s.spawn(|_| disks = getter.disks());
-            s.spawn(|_| font = getter.sys_font());
-            s.spawn(|_| gpus = getter.gpus());
+            (*s).spawn(|_| arch = tracked(tracking, "Architecture", || getter.arch()));
+            s.spawn(|_| battery = tracked(tracking, "Battery", || getter.battery()));
+            s.spawn(|_| brightness = tracked(tracking, "Brightness", || getter.brightness()));
+            s.spawn(|_| cpu = tracked(tracking, "CPU", || getter.cpu()));
+            if config.cpu_freq_range.unwrap_or_default() && tracking.wants("CPU") {
+                s.spawn(|_| cpu_freq_range = getter.cpu_freq_range());
+            }
+            if config.cpu_temp.unwrap_or_default() && tracking.wants("CPU") {
+                s.spawn(|_| cpu_temp = getter.cpu_temp());
+            }
+            s.spawn(|_| cursor = tracked(tracking, "Cursor", || getter.cursor()));
+            s.spawn(|_| de = tracked(tracking, "DE", || getter.de()));
+            s.spawn(|_| disks = tracked(tracking, "Disk", || getter.disks()));
+            s.spawn(|_| font = tracked(tracking, "System Font", || getter.sys_font()));
+            s.spawn(|_| gpus = tracked(tracking, "GPU", || getter.gpus()));
+            s.spawn(|_| host_id = getter.host_id());
+            s.spawn(|_| host_os = tracked(tracking, "Host OS", || getter.host_os()));
             s.spawn(|_| hostname = getter.hostname());
-            s.spawn(|_| icons = getter.icons());
+            s.spawn(|_| icons = tracked(tracking, "Icon Theme", || getter.icons()));
             s.spawn(|_| id = getter.id());
-            s.spawn(|_| ip = getter.ip());
-            s.spawn(|_| kernel = getter.kernel());
-            s.spawn(|_| locale = getter.locale());
-            s.spawn(|_| machine = getter.machine());
-            s.spawn(|_| memory = getter.memory());
-            s.spawn(|_| os = getter.os());
-            s.spawn(|_| resolution = getter.displays());
-            s.spawn(|_| shell = getter.shell());
-            s.spawn(|_| terminal = getter.terminal());
-            s.spawn(|_| terminal_font = getter.term_font());
-            s.spawn(|_| theme = getter.theme());
-            s.spawn(|_| uptime = getter.uptime());
+            s.spawn(|_| {
+                ip = tracked(tracking, "IP", || {
+                    let mut ip = getter.ip(config.ip_version.unwrap_or_default());
+                    if let Some(patterns) = &config.ip_interfaces {
+                        ip.retain(|(name, _)| interface_shown(patterns, name));
+                    }
+                    ip
+                });
+            });
+            s.spawn(|_| kernel = tracked(tracking, "Kernel", || getter.kernel()));
+            #[cfg(feature = "public-ip")]
+            if config.public_ip.unwrap_or_default() {
+                s.spawn(|_| {
+                    public_ip = tracked(tracking, "Public IP", || {
+                        lookup_public_ip(config.public_ip_url.as_deref().unwrap_or(PUBLIC_IP_URL))
+                    });
+                });
+            }
+            if config.links.unwrap_or_default() {
+                s.spawn(|_| links = tracked(tracking, "Link", || getter.links()));
+            }
+            s.spawn(|_| locale = tracked(tracking, "Locale", || getter.locale()));
+            s.spawn(|_| machine = tracked(tracking, "Host", || getter.machine()));
+            s.spawn(|_| memory = tracked(tracking, "Memory", || getter.memory()));
+            s.spawn(|_| swap = tracked(tracking, "Swap", || getter.swap()));
+            s.spawn(|_| nix = tracked(tracking, "Nix", || getter.nix()));
+            s.spawn(|_| os = tracked(tracking, "OS", || getter.os()));
+            s.spawn(|_| packages = tracked(tracking, "Packages", || getter.packages()));
+            s.spawn(|_| power = tracked(tracking, "Power", || getter.power()));
+            s.spawn(|_| pressure = tracked(tracking, "Pressure", || getter.pressure()));
+            s.spawn(|_| resolution = tracked(tracking, "Display", || getter.displays()));
+            s.spawn(|_| shell = tracked(tracking, "Shell", || getter.shell()));
+            s.spawn(|_| {
+                shell_framework = tracked(tracking, "Shell Framework", || getter.shell_framework());
+            });
+            // Reads every process, and ranking by CPU use waits a fifth of a second
+            if config.top.unwrap_or_default() {
+                s.spawn(|_| {
+                    top = tracked(tracking, "Top", || {
+                        getter.top(config.top_by.unwrap_or_default())
+                    });
+                });
+            }
+            if config.pipewire.unwrap_or_default() {
+                s.spawn(|_| pipewire = tracked(tracking, "PipeWire", || getter.pipewire()));
+            }
+            if config.realtime.unwrap_or_default() {
+                s.spawn(|_| realtime = tracked(tracking, "Realtime", || getter.realtime()));
+            }
+            if config.accessibility.unwrap_or_default() {
+                s.spawn(|_| {
+                    accessibility = tracked(tracking, "Accessibility", || getter.accessibility());
+                });
+            }
+            if config.accessible.is_none() && only.is_none() {
+                s.spawn(|_| screen_reader = getter.screen_reader());
+            }
+            if config.top_gpu.unwrap_or_default() {
+                s.spawn(|_| top_gpu = tracked(tracking, "Top GPU", || getter.top_gpu()));
+            }
+            s.spawn(|_| terminal = tracked(tracking, "Terminal", || getter.terminal()));
+            s.spawn(|_| terminal_font = tracked(tracking, "Terminal Font", || getter.term_font()));
+            s.spawn(|_| theme = tracked(tracking, "Theme", || getter.theme()));
+            s.spawn(|_| time_sync = tracked(tracking, "Time Sync", || getter.time_sync()));
+            s.spawn(|_| uptime = tracked(tracking, "Uptime", || getter.uptime()));
             s.spawn(|_| username = getter.username());
-            s.spawn(|_| wm = getter.wm());
+            s.spawn(|_| xdg = tracked(tracking, "XDG", || getter.xdg()));
+            s.spawn(|_| wm = tracked(tracking, "WM", || getter.wm()));
         });
 
+        // The base/boost range is shown instead of the clock speed
+        if let Some(cpu) = &mut cpu {
+            cpu.freq_range_mhz = cpu_freq_range;
+            cpu.temperature = cpu_temp.map(|celsius| Temperature {
+                celsius,
+                unit: config.temperature_unit.unwrap_or_default(),
+            });
+        }
+
         Self {
             os,
             machine,
             kernel,
             uptime,
+            time_sync,
+            packages,
             username,
             hostname,
             resolution,
             wm,
             de,
             shell,
+            shell_framework,
+            xdg,
             cpu,
             font,
             cursor,
@@ -191,28 +726,45 @@ impl Default for Info {
             terminal_font,
             gpus,
             memory,
+            swap,
+            pressure,
+            top,
+            top_gpu,
+            pipewire,
+            realtime,
+            accessibility,
+            screen_reader,
             disks,
             battery,
             locale,
             theme,
             icons,
             ip,
+            public_ip,
+            links,
+            nix,
+            brightness,
+            power,
+            arch,
+            mirafetch: config
+                .self_info
+                .unwrap_or_default()
+                .then(version::self_info),
+            host_os,
             id,
+            host_id,
+            errors: strict_errors.into_inner().unwrap_or_default(),
+            timings: module_timings.into_inner().unwrap_or_default(),
         }
     }
-}
 
-impl Info {
-    #[must_use]
-    pub fn new() -> Self {
-        Self::default()
-    }
     #[must_use]
+    #[allow(clippy::too_many_lines)]
     pub fn as_vec(self) -> Vec<(ArcStr, ArcStr)> {
         let username = self.username.unwrap_or_default();
         let hostname = self.hostname.unwrap_or_default();
         let y = arcstr::format!("{username}@{hostname}");
-        let repeats = y.len();
+        let repeats = y.width();
         let (dark, light) = palette();
         let mut res: Vec<(ArcStr, ArcStr)> = vec![
             (y, Some(ArcStr::default())),
@@ -223,29 +775,52 @@ impl Info {
             // if none, empty string
             // if not none
             (arcstr::literal!("OS"), self.os),
+            (arcstr::literal!("Host OS"), self.host_os),
             (arcstr::literal!("Host"), self.machine),
             (arcstr::literal!("Kernel"), self.kernel),
+            (arcstr::literal!("Architecture"), self.arch),
             (arcstr::literal!("Uptime"), self.uptime),
+            (arcstr::literal!("Time Sync"), self.time_sync),
+            (arcstr::literal!("Packages"), self.packages),
             (arcstr::literal!("Shell"), self.shell),
+            (arcstr::literal!("Shell Framework"), self.shell_framework),
+            (arcstr::literal!("XDG"), self.xdg),
             (arcstr::literal!("WM"), self.wm),
             (arcstr::literal!("DE"), self.de),
-            (arcstr::literal!("CPU"), self.cpu),
+            (
+                arcstr::literal!("CPU"),
+                self.cpu.map(|x| arcstr::format!("{x}")),
+            ),
             (arcstr::literal!("Theme"), self.theme),
             (arcstr::literal!("System Font"), self.font),
             (arcstr::literal!("Cursor"), self.cursor),
             (arcstr::literal!("Terminal"), self.terminal),
             (arcstr::literal!("Terminal Font"), self.terminal_font),
-            (arcstr::literal!("Memory"), self.memory),
+            (
+                arcstr::literal!("Memory"),
+                self.memory.map(|x| arcstr::format!("{x}")),
+            ),
+            (
+                arcstr::literal!("Swap"),
+                self.swap.map(|x| arcstr::format!("{x}")),
+            ),
+            (arcstr::literal!("Pressure"), self.pressure),
+            (arcstr::literal!("Top"), self.top),
             (arcstr::literal!("Battery"), self.battery),
+            (arcstr::literal!("Power"), self.power),
+            (arcstr::literal!("Brightness"), self.brightness),
             (arcstr::literal!("Locale"), self.locale),
             (arcstr::literal!("Icon Theme"), self.icons),
+            (arcstr::literal!("Nix"), self.nix),
+            (arcstr::literal!("Mirafetch"), self.mirafetch),
         ]
         .into_iter()
-        .map(|(x, y)| {
-            y.map(|z| (x.clone(), z))
-                .unwrap_or((x.clone(), arcstr::format!("> DUMMY {}", x)))
-        }) // todo debugging
-        // .filter_map(|(x, y)| y.map(|z| (x, z)))
+        // Modules that found nothing are left out, unless they failed
+        .filter_map(|(x, y)| match (y, self.errors.get(x.as_str())) {
+            (Some(z), _) => Some((x, z)),
+            (None, Some(err)) => Some((x, arcstr::format!("<error: {err}>"))),
+            (None, None) => None,
+        })
         .chain(
             self.resolution
                 .into_iter()
@@ -258,8 +833,42 @@ impl Info {
                 .enumerate()
                 .map(|(idx, res)| (arcstr::format!("GPU {}", idx + 1), res)),
         )
-        .chain(self.disks)
-        .chain(self.ip.into_iter().map(|x| (arcstr::literal!("IP"), x)))
+        .chain(
+            self.disks
+                .into_iter()
+                .map(|(name, usage)| (name, arcstr::format!("{usage}"))),
+        )
+        .chain(ip_by_interface(&self.ip))
+        .chain(self.public_ip.map(|x| (arcstr::literal!("Public IP"), x)))
+        .chain(self.top_gpu.map(|x| (arcstr::literal!("Top GPU"), x)))
+        .chain(self.pipewire.map(|x| (arcstr::literal!("PipeWire"), x)))
+        .chain(self.realtime.map(|x| (arcstr::literal!("Realtime"), x)))
+        .chain(
+            self.accessibility
+                .map(|x| (arcstr::literal!("Accessibility"), x)),
+        )
+        .chain(self.links)
+        // Lists with nothing in them get a single line for their error, as do the modules that
+        // are only looked up when asked for
+        .chain(
+            [
+                "Display",
+                "GPU",
+                "Disk",
+                "IP",
+                "Public IP",
+                "Top GPU",
+                "PipeWire",
+                "Realtime",
+                "Accessibility",
+                "Link",
+            ]
+            .into_iter()
+            .filter_map(|x| {
+                let err = self.errors.get(x)?;
+                Some((ArcStr::from(x), arcstr::format!("<error: {err}>")))
+            }),
+        )
         .collect();
         res.push((ArcStr::new(), dark));
         res.push((ArcStr::new(), light));
@@ -289,6 +898,99 @@ impl Info {
     }
 }
 
+/// An `IP (eth0)` line for each interface with its addresses, and an `IP` line for each address
+/// whose interface isn't known, as in older snapshots where they are whole lines
+fn ip_by_interface(ip: &[(ArcStr, ArcStr)]) -> Vec<(ArcStr, ArcStr)> {
+    let mut lines: Vec<(&ArcStr, Vec<&ArcStr>)> = Vec::new();
+    for (name, addr) in ip {
+        match lines
+            .iter_mut()
+            .find(|(x, _)| *x == name && !name.is_empty())
+        {
+            Some((_, addrs)) => addrs.push(addr),
+            None => lines.push((name, vec![addr])),
+        }
+    }
+    lines
+        .into_iter()
+        .map(|(name, addrs)| {
+            let label = if name.is_empty() {
+                arcstr::literal!("IP")
+            } else {
+                arcstr::format!("IP ({name})")
+            };
+            (label, ArcStr::from(addrs.into_iter().join(", ")))
+        })
+        .collect()
+}
+
+trait Found {
+    fn found(&self) -> bool;
+}
+
+impl<T> Found for Option<T> {
+    fn found(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl<T> Found for Vec<T> {
+    fn found(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+/// Reason given for a module that found nothing without recording why
+const NOT_DETECTED: &str = "not detected";
+
+/// What to remember about each module while collecting
+#[derive(Clone, Copy)]
+struct Tracking<'a> {
+    errors: Option<&'a Mutex<FxHashMap<&'static str, ArcStr>>>,
+    timings: Option<&'a Mutex<FxHashMap<&'static str, Duration>>>,
+    /// The one module to collect, if not all of them
+    only: Option<&'a str>,
+}
+
+impl Tracking<'_> {
+    fn wants(self, label: &str) -> bool {
+        self.only.is_none_or(|x| x == label)
+    }
+}
+
+/// Run a module, and in strict mode remember why it came back empty
+///
+/// The reason is the last error recorded on the thread, which is best effort as
+/// rayon may run other modules on the same thread while this one waits
+fn tracked<T: Found + Default>(
+    tracking: Tracking,
+    label: &'static str,
+    f: impl FnOnce() -> T,
+) -> T {
+    if !tracking.wants(label) {
+        return T::default();
+    }
+    let start = Instant::now();
+    let res = match tracking.errors {
+        Some(errors) => {
+            errors::take();
+            let res = f();
+            if !res.found() {
+                let err = errors::take().unwrap_or_else(|| String::from(NOT_DETECTED));
+                if let Ok(mut errors) = errors.lock() {
+                    errors.insert(label, ArcStr::from(err));
+                }
+            }
+            res
+        }
+        None => f(),
+    };
+    if let Some(Ok(mut timings)) = tracking.timings.map(Mutex::lock) {
+        timings.insert(label, start.elapsed());
+    }
+    res
+}
+
 fn palette() -> (ArcStr, ArcStr) {
     (
         (0..8u8)
@@ -0,0 +1,189 @@
+#![cfg(any(target_os = "linux", target_os = "freebsd", target_os = "dragonfly"))]
+use std::{fs::File, os::unix::fs::FileExt, path::Path};
+
+/// B-tree page types of tables
+const INTERIOR: u8 = 0x05;
+const LEAF: u8 = 0x0d;
+/// Deepest table b-tree that is walked, far more than any real database needs
+const MAX_DEPTH: usize = 32;
+
+/// Number of rows in `table` of the sqlite database at `path`, such as the rpm database
+///
+/// Only the pages of the table are read, without a sqlite library. Changes still in a
+/// write-ahead log aren't counted until they are checkpointed
+pub fn count_rows(path: &Path, table: &str) -> Option<u64> {
+    let db = Database::open(path)?;
+    let root = db.table_root(table)?;
+    db.count(root, 0)
+}
+
+struct Database {
+    file: File,
+    page_size: usize,
+}
+
+impl Database {
+    fn open(path: &Path) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        let mut header = [0u8; 100];
+        file.read_exact_at(&mut header, 0).ok()?;
+        if !header.starts_with(b"SQLite format 3\0") {
+            return None;
+        }
+        let page_size = match u16::from_be_bytes([header[16], header[17]]) {
+            1 => 65536,
+            x => usize::from(x),
+        };
+        Some(Self { file, page_size })
+    }
+
+    fn page(&self, number: u32) -> Option<Vec<u8>> {
+        let offset = u64::from(number.checked_sub(1)?) * self.page_size as u64;
+        let mut page = vec![0; self.page_size];
+        self.file.read_exact_at(&mut page, offset).ok()?;
+        Some(page)
+    }
+
+    /// Root page of `table`, looked up in the schema table on the first page
+    fn table_root(&self, table: &str) -> Option<u32> {
+        let page = self.page(1)?;
+        // The schema of a database with many tables could span several pages, rpm's doesn't
+        let node = Node::parse(&page, 100)?;
+        if node.kind != LEAF {
+            return None;
+        }
+        let root = node.cells(&page).find_map(|cell| {
+            // Payload size and rowid, then the record
+            let (_, size_len) = varint(page.get(cell..)?)?;
+            let (_, rowid_len) = varint(page.get(cell + size_len..)?)?;
+            let columns = record(page.get(cell + size_len + rowid_len..)?)?;
+            // type, name, tbl_name, rootpage, sql
+            if columns.first()? != &Column::Text(b"table")
+                || columns.get(1)? != &Column::Text(table.as_bytes())
+            {
+                return None;
+            }
+            match columns.get(3)? {
+                Column::Int(x) => u32::try_from(*x).ok(),
+                Column::Text(_) | Column::Other => None,
+            }
+        });
+        root
+    }
+
+    /// Rows in the table b-tree rooted at `number`
+    fn count(&self, number: u32, depth: usize) -> Option<u64> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let page = self.page(number)?;
+        let node = Node::parse(&page, 0)?;
+        match node.kind {
+            LEAF => Some(u64::from(node.cells)),
+            INTERIOR => node
+                .cells(&page)
+                .map(|cell| {
+                    Some(u32::from_be_bytes(
+                        page.get(cell..cell + 4)?.try_into().ok()?,
+                    ))
+                })
+                .chain([node.right])
+                .map(|child| self.count(child?, depth + 1))
+                .sum(),
+            _ => None,
+        }
+    }
+}
+
+/// Header of a b-tree page, which starts at `start` on the first page and 0 on others
+struct Node {
+    kind: u8,
+    cells: u16,
+    right: Option<u32>,
+    pointers: usize,
+}
+
+impl Node {
+    fn parse(page: &[u8], start: usize) -> Option<Self> {
+        let header = page.get(start..start + 12)?;
+        let kind = header[0];
+        let interior = kind == INTERIOR;
+        Some(Self {
+            kind,
+            cells: u16::from_be_bytes([header[3], header[4]]),
+            right: interior
+                .then(|| u32::from_be_bytes([header[8], header[9], header[10], header[11]])),
+            pointers: start + if interior { 12 } else { 8 },
+        })
+    }
+
+    /// Offsets of each cell in the page
+    fn cells<'a>(&self, page: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let pointers = self.pointers;
+        (0..usize::from(self.cells)).filter_map(move |idx| {
+            let at = pointers + idx * 2;
+            Some(usize::from(u16::from_be_bytes(
+                page.get(at..at + 2)?.try_into().ok()?,
+            )))
+        })
+    }
+}
+
+#[derive(PartialEq, Eq)]
+enum Column<'a> {
+    Int(i64),
+    Text(&'a [u8]),
+    Other,
+}
+
+/// Columns of a record, as far as they fit in `data`
+fn record(data: &[u8]) -> Option<Vec<Column<'_>>> {
+    let (header_len, mut at) = varint(data)?;
+    let header_len = usize::try_from(header_len).ok()?;
+    let mut body = header_len;
+    let mut columns = Vec::new();
+    while at < header_len {
+        let (kind, len) = varint(data.get(at..)?)?;
+        at += len;
+        let size = match kind {
+            0 | 8 | 9 => 0,
+            1..=4 => usize::try_from(kind).ok()?,
+            5 => 6,
+            6 | 7 => 8,
+            x if x >= 12 => usize::try_from((x - 12) / 2).ok()?,
+            _ => return None,
+        };
+        let Some(value) = data.get(body..body + size) else {
+            break;
+        };
+        body += size;
+        columns.push(match kind {
+            1..=6 => {
+                // Sign extend the big endian integer
+                let mut bytes = [if value[0] & 0x80 == 0 { 0 } else { 0xff }; 8];
+                bytes[8 - size..].copy_from_slice(value);
+                Column::Int(i64::from_be_bytes(bytes))
+            }
+            8 => Column::Int(0),
+            9 => Column::Int(1),
+            x if x >= 13 && x % 2 == 1 => Column::Text(value),
+            _ => Column::Other,
+        });
+    }
+    Some(columns)
+}
+
+/// A sqlite variable length integer and the number of bytes it took
+fn varint(data: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (idx, byte) in data.iter().take(9).enumerate() {
+        if idx == 8 {
+            return Some(((value << 8) | u64::from(*byte), 9));
+        }
+        value = (value << 7) | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            return Some((value, idx + 1));
+        }
+    }
+    None
+}
@@ -0,0 +1,85 @@
+#![cfg(target_os = "linux")]
+use std::{
+    cell::RefCell,
+    fs::{self, File},
+    io::ErrorKind,
+    os::unix::fs::FileExt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::info::errors;
+
+thread_local! {
+    static BUFFER: RefCell<Vec<u8>> = RefCell::new(vec![0; 4096]);
+}
+
+/// Call `f` with the raw contents of `path`
+///
+/// Files are read with `pread` into a buffer that is reused by every read on the current
+/// thread, which keeps scanning hwmon, `power_supply` or drm directories allocation free
+pub fn read_bytes_with<T>(path: impl AsRef<Path>, f: impl FnOnce(&[u8]) -> T) -> Option<T> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .map_err(|err| errors::record(format_args!("{err} reading {}", path.display())))
+        .ok()?;
+    // Taken rather than borrowed so `f` can read other files
+    let mut buf = BUFFER.take();
+    if buf.is_empty() {
+        buf.resize(4096, 0);
+    }
+    let mut len = 0;
+    let read = loop {
+        match file.read_at(&mut buf[len..], len as u64) {
+            Ok(0) => break true,
+            Ok(n) => {
+                len += n;
+                if len == buf.len() {
+                    buf.resize(len * 2, 0);
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => {
+                errors::record(format_args!("{err} reading {}", path.display()));
+                break false;
+            }
+        }
+    };
+    let res = read.then(|| f(&buf[..len]));
+    BUFFER.set(buf);
+    res
+}
+
+/// Read a whole file such as `/proc/meminfo`, remembering the error if it can't be read
+pub fn read_to_string(path: impl AsRef<Path>) -> Option<String> {
+    let path = path.as_ref();
+    fs::read_to_string(path)
+        .map_err(|err| errors::record(format_args!("{err} reading {}", path.display())))
+        .ok()
+}
+
+/// Call `f` with the trimmed text contents of `path`
+pub fn read_with<T>(path: impl AsRef<Path>, f: impl FnOnce(&str) -> T) -> Option<T> {
+    read_bytes_with(path, |x| std::str::from_utf8(x).ok().map(|x| f(x.trim()))).flatten()
+}
+
+/// Parse the contents of a single value file such as `/sys/class/backlight/*/brightness`
+pub fn read_parse<T: FromStr>(path: impl AsRef<Path>) -> Option<T> {
+    read_with(path, |x| x.parse().ok()).flatten()
+}
+
+/// Parse many single value files, leaving out the ones that can't be read or parsed
+///
+/// With the `io-uring` feature the files are read in batches through `io_uring`, and one
+/// by one when it isn't available
+pub fn read_parse_all<T: FromStr>(paths: &[PathBuf]) -> Vec<T> {
+    #[cfg(feature = "io-uring")]
+    if let Some(contents) = crate::info::uring::read_all(paths) {
+        return contents
+            .iter()
+            .flatten()
+            .filter_map(|x| std::str::from_utf8(x).ok()?.trim().parse().ok())
+            .collect();
+    }
+    paths.iter().filter_map(read_parse).collect()
+}
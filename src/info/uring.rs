@@ -0,0 +1,102 @@
+#![cfg(target_os = "linux")]
+use std::{ffi::CString, io, os::unix::ffi::OsStrExt, path::PathBuf};
+
+use io_uring::{opcode, squeue, types, IoUring};
+
+use crate::info::errors;
+
+/// Largest file read, which covers any sysfs attribute
+const MAX_LEN: u32 = 4096;
+/// Most files handled per round of submissions
+const BATCH: usize = 256;
+
+/// Read small files such as sysfs attributes with `io_uring`
+///
+/// Each batch of files is opened, read and closed with one submission per step instead of
+/// a few syscalls per file. Returns `None` when `io_uring` can't be used, e.g. on kernels
+/// before 5.6 or where a container runtime blocks it, so the files can be read normally
+pub fn read_all(paths: &[PathBuf]) -> Option<Vec<Option<Vec<u8>>>> {
+    let size = u32::try_from(paths.len().clamp(1, BATCH).next_power_of_two()).ok()?;
+    let mut ring = IoUring::new(size).ok()?;
+    let mut res = Vec::with_capacity(paths.len());
+    for chunk in paths.chunks(BATCH) {
+        let names = chunk
+            .iter()
+            .map(|x| CString::new(x.as_os_str().as_bytes()).ok())
+            .collect::<Option<Vec<_>>>()?;
+        let opens = names.iter().map(|name| {
+            Some(
+                opcode::OpenAt::new(types::Fd(libc::AT_FDCWD), name.as_ptr())
+                    .flags(libc::O_RDONLY | libc::O_CLOEXEC)
+                    .build(),
+            )
+        });
+        // SAFETY: the names outlive the submission
+        let fds = unsafe { run(&mut ring, opens.collect())? };
+        if fds.iter().all(|x| *x == -libc::EINVAL) {
+            return None;
+        }
+
+        let mut bufs = vec![[0u8; MAX_LEN as usize]; chunk.len()];
+        let reads = fds.iter().zip(&mut bufs).map(|(fd, buf)| {
+            (*fd >= 0).then(|| {
+                opcode::Read::new(types::Fd(*fd), buf.as_mut_ptr(), MAX_LEN)
+                    .offset(0)
+                    .build()
+            })
+        });
+        // SAFETY: the buffers outlive the submission
+        let lens = unsafe { run(&mut ring, reads.collect()) };
+        let closes = fds
+            .iter()
+            .map(|fd| (*fd >= 0).then(|| opcode::Close::new(types::Fd(*fd)).build()));
+        // SAFETY: closing doesn't point to any memory
+        unsafe { run(&mut ring, closes.collect()) };
+        let lens = lens?;
+
+        for (idx, path) in chunk.iter().enumerate() {
+            let err = if fds[idx] < 0 { fds[idx] } else { lens[idx] };
+            if err < 0 {
+                let err = io::Error::from_raw_os_error(-err);
+                errors::record(format_args!("{err} reading {}", path.display()));
+                res.push(None);
+            } else {
+                let len = usize::try_from(lens[idx]).unwrap_or_default();
+                res.push(Some(bufs[idx][..len].to_vec()));
+            }
+        }
+    }
+    Some(res)
+}
+
+/// Submit `entries` and wait for all of them, returning each one's result in order
+///
+/// Entries that are `None` are skipped and get `-ENOENT` as their result
+///
+/// # Safety
+///
+/// Any memory the entries point to has to stay valid until this returns
+unsafe fn run(ring: &mut IoUring, entries: Vec<Option<squeue::Entry>>) -> Option<Vec<i32>> {
+    let mut res = vec![-libc::ENOENT; entries.len()];
+    let mut pending = 0;
+    for (idx, entry) in entries.into_iter().enumerate() {
+        let Some(entry) = entry else {
+            continue;
+        };
+        ring.submission().push(&entry.user_data(idx as u64)).ok()?;
+        pending += 1;
+    }
+    if pending == 0 {
+        return Some(res);
+    }
+    ring.submit_and_wait(pending).ok()?;
+    for cqe in ring.completion() {
+        if let Some(x) = usize::try_from(cqe.user_data())
+            .ok()
+            .and_then(|idx| res.get_mut(idx))
+        {
+            *x = cqe.result();
+        }
+    }
+    Some(res)
+}
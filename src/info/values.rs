@@ -0,0 +1,134 @@
+//! Values of the modules that are more than text, which backends fill in and which are only
+//! turned into text when shown, through their `Display` impls
+use std::fmt;
+
+use arcstr::ArcStr;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::TemperatureUnit, util::bytecount_format};
+
+/// Used and total bytes of memory or swap, shown as `7.43 GiB / 31.05 GiB`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Memory {
+    pub used: u64,
+    pub total: u64,
+    /// Whether these are the limits of the container's cgroup rather than the machine's
+    #[serde(default)]
+    pub cgroup: bool,
+}
+
+impl Memory {
+    #[must_use]
+    pub const fn free(&self) -> u64 {
+        self.total.saturating_sub(self.used)
+    }
+}
+
+impl fmt::Display for Memory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} / {}",
+            bytecount_format(self.used, 2),
+            bytecount_format(self.total, 2)
+        )?;
+        if self.cgroup {
+            f.write_str(" (cgroup)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Used and total bytes of a filesystem, shown as `112 GiB/ 476 GiB`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    pub used: u64,
+    pub total: u64,
+}
+
+impl fmt::Display for DiskUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/ {}",
+            bytecount_format(self.used, 0),
+            bytecount_format(self.total, 0)
+        )
+    }
+}
+
+/// CPU model with its core count and clock speed, shown as `Ryzen 7 5800X (16) @ 4.85 GHz`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cpu {
+    pub model: ArcStr,
+    /// Logical cores, or the CPUs of a container's quota, which may be a fraction
+    pub cores: Option<f64>,
+    /// Fastest clock speed in MHz
+    pub freq_mhz: Option<f64>,
+    /// Base (or minimum) and boost clock speed in MHz, with the `cpu_freq_range` setting
+    #[serde(default)]
+    pub freq_range_mhz: Option<(f64, f64)>,
+    /// Package temperature, with the `cpu_temp` setting
+    #[serde(default)]
+    pub temperature: Option<Temperature>,
+    /// Whether the core count is the container's cgroup quota rather than the machine's
+    #[serde(default)]
+    pub cgroup: bool,
+}
+
+impl Cpu {
+    /// A CPU known only by its model
+    #[must_use]
+    pub fn new(model: impl Into<ArcStr>) -> Self {
+        Self {
+            model: model.into(),
+            cores: None,
+            freq_mhz: None,
+            freq_range_mhz: None,
+            temperature: None,
+            cgroup: false,
+        }
+    }
+}
+
+impl fmt::Display for Cpu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.model)?;
+        if let Some(cores) = self.cores {
+            write!(f, " ({cores})")?;
+        }
+        match (self.freq_range_mhz, self.freq_mhz) {
+            (Some((min, max)), _) => {
+                write!(f, " @ {:.2} - {:.2} GHz", min / 1000.0, max / 1000.0)?;
+            }
+            (None, Some(mhz)) => write!(f, " @ {:.2} GHz", mhz / 1000.0)?,
+            (None, None) => {}
+        }
+        if self.cgroup {
+            f.write_str(" (cgroup)")?;
+        }
+        if let Some(temperature) = &self.temperature {
+            write!(f, ", {temperature}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A temperature and the unit it is shown in, e.g. `52°C`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Temperature {
+    pub celsius: f64,
+    #[serde(default)]
+    pub unit: TemperatureUnit,
+}
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.unit {
+            TemperatureUnit::Celsius => write!(f, "{:.0}°C", self.celsius),
+            TemperatureUnit::Fahrenheit => {
+                write!(f, "{:.0}°F", self.celsius.mul_add(1.8, 32.0))
+            }
+        }
+    }
+}
@@ -0,0 +1,214 @@
+#![cfg(target_os = "linux")]
+use std::sync::OnceLock;
+
+use arcstr::ArcStr;
+use rustc_hash::FxHashMap;
+use wayland_client::{
+    backend::ObjectId, event_created_child, protocol::wl_registry, Connection, Dispatch, Proxy,
+    QueueHandle, WEnum,
+};
+use wayland_protocols_wlr::output_management::v1::client::{
+    zwlr_output_head_v1::{self, AdaptiveSyncState, ZwlrOutputHeadV1},
+    zwlr_output_manager_v1::{self, ZwlrOutputManagerV1},
+    zwlr_output_mode_v1::{self, ZwlrOutputModeV1},
+};
+
+#[derive(Default)]
+struct Head {
+    name: String,
+    enabled: bool,
+    current_mode: Option<ObjectId>,
+    scale: f64,
+    adaptive_sync: bool,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Mode {
+    width: i32,
+    height: i32,
+    /// Refresh rate in mHz
+    refresh: i32,
+}
+
+#[derive(Default)]
+struct State {
+    manager: Option<ZwlrOutputManagerV1>,
+    heads: Vec<(ObjectId, Head)>,
+    modes: FxHashMap<ObjectId, Mode>,
+    done: bool,
+}
+
+impl State {
+    fn head(&mut self, id: &ObjectId) -> Option<&mut Head> {
+        self.heads
+            .iter_mut()
+            .find(|(head, _)| head == id)
+            .map(|(_, head)| head)
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for State {
+    fn event(
+        state: &mut Self,
+        registry: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _data: &(),
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            if interface == ZwlrOutputManagerV1::interface().name {
+                state.manager = Some(registry.bind(name, version.min(4), qh, ()));
+            }
+        }
+    }
+}
+
+impl Dispatch<ZwlrOutputManagerV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _manager: &ZwlrOutputManagerV1,
+        event: zwlr_output_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_output_manager_v1::Event::Head { head } => {
+                state.heads.push((
+                    head.id(),
+                    Head {
+                        scale: 1.0,
+                        ..Head::default()
+                    },
+                ));
+            }
+            zwlr_output_manager_v1::Event::Done { .. } => state.done = true,
+            _ => {}
+        }
+    }
+
+    event_created_child!(State, ZwlrOutputManagerV1, [
+        zwlr_output_manager_v1::EVT_HEAD_OPCODE => (ZwlrOutputHeadV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrOutputHeadV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        head: &ZwlrOutputHeadV1,
+        event: zwlr_output_head_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let zwlr_output_head_v1::Event::Mode { mode } = event {
+            state.modes.insert(mode.id(), Mode::default());
+            return;
+        }
+        let Some(info) = state.head(&head.id()) else {
+            return;
+        };
+        match event {
+            zwlr_output_head_v1::Event::Name { name } => info.name = name,
+            zwlr_output_head_v1::Event::Enabled { enabled } => info.enabled = enabled != 0,
+            zwlr_output_head_v1::Event::CurrentMode { mode } => info.current_mode = Some(mode.id()),
+            zwlr_output_head_v1::Event::Scale { scale } => info.scale = scale,
+            zwlr_output_head_v1::Event::AdaptiveSync { state } => {
+                info.adaptive_sync = matches!(state, WEnum::Value(AdaptiveSyncState::Enabled));
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(State, ZwlrOutputHeadV1, [
+        zwlr_output_head_v1::EVT_MODE_OPCODE => (ZwlrOutputModeV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrOutputModeV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        mode: &ZwlrOutputModeV1,
+        event: zwlr_output_mode_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let Some(info) = state.modes.get_mut(&mode.id()) else {
+            return;
+        };
+        match event {
+            zwlr_output_mode_v1::Event::Size { width, height } => {
+                info.width = width;
+                info.height = height;
+            }
+            zwlr_output_mode_v1::Event::Refresh { refresh } => info.refresh = refresh,
+            _ => {}
+        }
+    }
+}
+
+/// The connection opened by [`preconnect`]
+static CONNECTION: OnceLock<Connection> = OnceLock::new();
+
+/// Connect to the compositor at `WAYLAND_DISPLAY` now rather than when it's first asked
+pub fn preconnect() {
+    if let Ok(conn) = Connection::connect_to_env() {
+        let _ = CONNECTION.set(conn);
+    }
+}
+
+/// Query enabled outputs from a wlroots-compatible compositor
+///
+/// Returns `None` when not running under Wayland or when the compositor does not
+/// implement `wlr-output-management`
+pub fn outputs() -> Option<Vec<ArcStr>> {
+    let conn = CONNECTION
+        .get()
+        .cloned()
+        .or_else(|| Connection::connect_to_env().ok())?;
+    let mut queue = conn.new_event_queue();
+    let qh = queue.handle();
+    conn.display().get_registry(&qh, ());
+
+    let mut state = State::default();
+    queue.roundtrip(&mut state).ok()?;
+    state.manager.as_ref()?;
+    // The manager sends every head and mode before its first done event
+    for _ in 0..4 {
+        if state.done {
+            break;
+        }
+        queue.roundtrip(&mut state).ok()?;
+    }
+
+    Some(
+        state
+            .heads
+            .iter()
+            .filter(|(_, head)| head.enabled)
+            .filter_map(|(_, head)| {
+                let mode = state.modes.get(head.current_mode.as_ref()?)?;
+                let mut details = vec![format!(
+                    "{}x{} @ {}Hz",
+                    mode.width,
+                    mode.height,
+                    (mode.refresh + 500) / 1000
+                )];
+                if (head.scale - 1.0).abs() > f64::EPSILON {
+                    details.push(format!("scale {}", head.scale));
+                }
+                if head.adaptive_sync {
+                    details.push(String::from("adaptive sync"));
+                }
+                Some(arcstr::format!("{}: {}", head.name, details.join(", ")))
+            })
+            .collect(),
+    )
+}
@@ -6,8 +6,10 @@ use crossterm::style::{Color, Stylize};
 use itertools::Itertools;
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use rustc_hash::FxHashMap;
+use std::fs;
 use std::mem::size_of;
 use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
 use windows::Win32::System::SystemInformation::{
     RelationProcessorCore, SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX,
 };
@@ -17,7 +19,10 @@ use windows::Win32::{
         GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER,
         GAA_FLAG_SKIP_MULTICAST, IP_ADAPTER_ADDRESSES_LH,
     },
-    Networking::WinSock::{AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR_IN, SOCKADDR_IN6},
+    Networking::WinSock::{
+        IpDadStatePreferred, IpSuffixOriginRandom, AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR_IN,
+        SOCKADDR_IN6,
+    },
     System::SystemInformation::{GetLogicalProcessorInformationEx, RelationAll},
 };
 use winsafe::co::{SPI, SPIF};
@@ -25,7 +30,7 @@ use winsafe::{
     GetComputerName, GetDiskFreeSpaceEx, GetLogicalDriveStrings, GetTickCount64,
     GlobalMemoryStatusEx, SystemParametersInfo, MEMORYSTATUSEX, NONCLIENTMETRICS,
 };
-use wmi::{COMLibrary, WMIConnection};
+use wmi::{COMLibrary, Variant, WMIConnection};
 
 use regex::Regex;
 use std::sync::{Arc, OnceLock};
@@ -35,8 +40,15 @@ use winreg::{
     RegKey,
 };
 
-use crate::info::OSInfo;
-use crate::util::bytecount_format;
+use crate::{
+    config::IpVersion,
+    info::{
+        errors, ip_addresses,
+        values::{Cpu, DiskUsage, Memory},
+        OSInfo,
+    },
+    util::uptime_format,
+};
 
 #[derive(Default)]
 pub struct WindowsInfo {
@@ -44,7 +56,7 @@ pub struct WindowsInfo {
 }
 impl WindowsInfo {
     #[must_use]
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self {
             hklm: OnceLock::new(),
         }
@@ -54,6 +66,39 @@ impl WindowsInfo {
         self.hklm.get_or_init(|| RegKey::predef(HKEY_LOCAL_MACHINE))
     }
 }
+
+/// Rows returned by a WMI query, with the value of each column
+fn wmi_query(query: &str) -> Option<Vec<FxHashMap<String, Variant>>> {
+    let wmi_con = WMIConnection::new(COMLibrary::new().ok()?).ok()?;
+    wmi_con.raw_query(query).ok()
+}
+
+/// An integer column, which WMI may return as any integer type whatever its declared type
+fn wmi_number(value: Option<&Variant>) -> Option<u64> {
+    match value? {
+        Variant::UI1(x) => Some(u64::from(*x)),
+        Variant::UI2(x) => Some(u64::from(*x)),
+        Variant::UI4(x) => Some(u64::from(*x)),
+        Variant::UI8(x) => Some(*x),
+        Variant::I1(x) => u64::try_from(*x).ok(),
+        Variant::I2(x) => u64::try_from(*x).ok(),
+        Variant::I4(x) => u64::try_from(*x).ok(),
+        Variant::I8(x) => u64::try_from(*x).ok(),
+        _ => None,
+    }
+}
+
+/// Subdirectories of `dir`, e.g. one per installed package
+fn count_dirs(dir: PathBuf) -> Option<usize> {
+    Some(
+        fs::read_dir(dir)
+            .ok()?
+            .filter_map(Result::ok)
+            .filter(|x| x.file_type().is_ok_and(|x| x.is_dir()))
+            .count(),
+    )
+}
+
 impl OSInfo for WindowsInfo {
     fn displays(&self) -> Vec<ArcStr> {
         use std::sync::Mutex;
@@ -66,7 +111,11 @@ impl OSInfo for WindowsInfo {
         let handle = HDC::NULL;
         handle
             .EnumDisplayMonitors(None, |_monitor, _hdc, rect| -> bool {
-                displays.lock().unwrap().push(arcstr::format!(
+                // Only poisoned if an earlier call panicked, which stops the enumeration
+                let Ok(mut displays) = displays.lock() else {
+                    return false;
+                };
+                displays.push(arcstr::format!(
                     "{}x{}",
                     rect.right - rect.left,
                     rect.bottom - rect.top
@@ -74,7 +123,11 @@ impl OSInfo for WindowsInfo {
                 true
             })
             .ok();
-        return displays.lock().unwrap().to_vec();
+        let displays = displays.lock().map(|x| x.to_vec());
+        displays.unwrap_or_else(|err| {
+            errors::record(err);
+            Vec::new()
+        })
     }
 
     fn machine(&self) -> Option<ArcStr> {
@@ -196,7 +249,7 @@ impl OSInfo for WindowsInfo {
                             .open_subkey(x.ok()?)
                             .map(|uuid| {
                                 uuid.enum_keys().find_map(|y| -> Option<ArcStr> {
-                                    uuid.open_subkey(y.unwrap())
+                                    uuid.open_subkey(y.ok()?)
                                         .ok()?
                                         .get_value::<String, &str>("DriverDesc")
                                         .ok()
@@ -230,22 +283,15 @@ impl OSInfo for WindowsInfo {
                     .join(" "),
             ))
         }()
-        .unwrap()
+        // WMI may be unavailable, e.g. in Windows containers
+        .unwrap_or(arcstr::literal!("Windows"))
     }
 
     fn uptime(&self) -> Option<ArcStr> {
-        let uptime = time::Duration::milliseconds(GetTickCount64().try_into().ok()?).to_string();
-        Some(
-            uptime
-                .split_inclusive('m')
-                .next()?
-                .replace('h', " hours, ")
-                .replace('m', " mins")
-                .into(),
-        )
+        Some(ArcStr::from(uptime_format(GetTickCount64() / 1000)))
     }
 
-    fn ip(&self) -> Vec<ArcStr> {
+    fn ip(&self, version: IpVersion) -> Vec<(ArcStr, ArcStr)> {
         unsafe {
             let size = Box::into_raw(Box::new(0x3FFF));
             let mut buf = Vec::<u8>::with_capacity(*size as usize);
@@ -260,24 +306,28 @@ impl OSInfo for WindowsInfo {
             {
                 buf.reserve_exact(*size as usize);
             }
-            let mut ipv4_addrs: Vec<Ipv4Addr> = Vec::new();
-            let mut ipv6_addrs: Vec<Ipv6Addr> = Vec::new();
+            let mut ipv4_addrs: Vec<(ArcStr, Ipv4Addr)> = Vec::new();
+            let mut ipv6_addrs: Vec<(ArcStr, Ipv6Addr)> = Vec::new();
             let mut adapter_current = addrs.as_ref();
-            let mut res = Vec::new();
             while let Some(adapter) = adapter_current {
+                let name = ArcStr::from(adapter.FriendlyName.to_string().unwrap_or_default());
                 let mut addr_current = (adapter).FirstUnicastAddress.as_ref();
                 while let Some(addr) = addr_current {
                     match (*addr.Address.lpSockaddr).sa_family {
                         AF_INET6 => {
+                            #[allow(clippy::cast_ptr_alignment)]
                             let ip_addr = Ipv6Addr::from(
                                 (*addr.Address.lpSockaddr.cast::<SOCKADDR_IN6>())
                                     .sin6_addr
                                     .u
                                     .Byte,
                             );
-                            if !ip_addr.is_loopback() && (ip_addr.segments()[0] & 0xfe00 == 0xfc00)
+                            // Privacy addresses have a random suffix, and only the preferred
+                            // ones are still used for new connections
+                            if addr.DadState == IpDadStatePreferred
+                                && addr.SuffixOrigin != IpSuffixOriginRandom
                             {
-                                ipv6_addrs.push(ip_addr);
+                                ipv6_addrs.push((name.clone(), ip_addr));
                             }
                         }
                         AF_INET => {
@@ -292,37 +342,19 @@ impl OSInfo for WindowsInfo {
                             if !ip_addr.is_loopback()
                                 && (ip_addr.is_private()/*|| ip_addr.is_link_local()*/)
                             {
-                                ipv4_addrs.push(ip_addr);
+                                ipv4_addrs.push((name.clone(), ip_addr));
                             }
                         }
-                        _ => panic!(),
-                    };
-                    res.push(());
+                        family => errors::record(format_args!(
+                            "skipped an address of unknown family {}",
+                            family.0
+                        )),
+                    }
                     addr_current = addr.Next.as_ref();
                 }
                 adapter_current = adapter.Next.as_ref();
             }
-            let mut res = Vec::with_capacity(2);
-            if !ipv4_addrs.is_empty() {
-                res.push(ArcStr::from(
-                    ipv4_addrs
-                        .par_iter()
-                        .map(std::string::ToString::to_string)
-                        .collect::<Vec<String>>()
-                        .join(", "),
-                ));
-            }
-            if !ipv6_addrs.is_empty() {
-                res.push(ArcStr::from(
-                    ipv6_addrs
-                        .par_iter()
-                        .map(std::string::ToString::to_string)
-                        .collect::<Vec<String>>()
-                        .join(", "),
-                ));
-            }
-
-            res
+            ip_addresses(version, ipv4_addrs, ipv6_addrs)
         }
     }
 
@@ -343,7 +375,7 @@ impl OSInfo for WindowsInfo {
                 &mut metrics,
                 SPIF::NoValue,
             )
-            .unwrap();
+            .ok()?;
         };
         Some(metrics.lfMenuFont.lfFaceName().into())
     }
@@ -353,24 +385,34 @@ impl OSInfo for WindowsInfo {
     }
 
     fn terminal(&self) -> Option<ArcStr> {
-        None
+        // Terminals that tell the programs they run about themselves
+        if std::env::var_os("WT_SESSION").is_some() {
+            return Some(arcstr::literal!("Windows Terminal"));
+        }
+        if std::env::var_os("ConEmuPID").is_some() {
+            return Some(arcstr::literal!("ConEmu"));
+        }
+        std::env::var("TERM_PROGRAM")
+            .ok()
+            .filter(|x| !x.is_empty())
+            .map(ArcStr::from)
     }
 
     fn term_font(&self) -> Option<ArcStr> {
         None
     }
 
-    fn memory(&self) -> Option<ArcStr> {
+    fn memory(&self) -> Option<Memory> {
         let mut state = MEMORYSTATUSEX::default();
         GlobalMemoryStatusEx(&mut state).ok()?;
-        Some(arcstr::format!(
-            "{} / {}",
-            bytecount_format(state.ullTotalPhys - state.ullAvailPhys, 2),
-            bytecount_format(state.ullTotalPhys, 2),
-        ))
+        Some(Memory {
+            used: state.ullTotalPhys - state.ullAvailPhys,
+            total: state.ullTotalPhys,
+            cgroup: false,
+        })
     }
 
-    fn disks(&self) -> Vec<(ArcStr, ArcStr)> {
+    fn disks(&self) -> Vec<(ArcStr, DiskUsage)> {
         let q = GetLogicalDriveStrings();
         q.map_or(Vec::new(), |c| {
             c.par_iter()
@@ -381,11 +423,10 @@ impl OSInfo for WindowsInfo {
                         let var_name = 0xDEAD;
                         let mut free: Option<u64> = Some(var_name);
                         GetDiskFreeSpaceEx(Some(x), None, total.as_mut(), free.as_mut()).ok()?;
-                        arcstr::format!(
-                            "{} / {}",
-                            bytecount_format(total? - free?, 0),
-                            bytecount_format(total?, 0)
-                        )
+                        DiskUsage {
+                            used: total? - free?,
+                            total: total?,
+                        }
                     }))
                 })
                 .collect()
@@ -393,7 +434,25 @@ impl OSInfo for WindowsInfo {
     }
 
     fn battery(&self) -> Option<ArcStr> {
-        None
+        let batteries =
+            wmi_query("SELECT EstimatedChargeRemaining, BatteryStatus FROM Win32_Battery")?;
+        let res = batteries
+            .iter()
+            .filter_map(|battery| {
+                let capacity = wmi_number(battery.get("EstimatedChargeRemaining"))?;
+                let status = match wmi_number(battery.get("BatteryStatus")) {
+                    Some(1) => Some("Discharging"),
+                    Some(3) => Some("Full"),
+                    Some(6..=9) => Some("Charging"),
+                    _ => None,
+                };
+                Some(status.map_or_else(
+                    || format!("{capacity}%"),
+                    |status| format!("{capacity}% {status}"),
+                ))
+            })
+            .join(", ");
+        (!res.is_empty()).then(|| ArcStr::from(res))
     }
 
     fn locale(&self) -> Option<ArcStr> {
@@ -419,26 +478,76 @@ impl OSInfo for WindowsInfo {
     }
 
     fn shell(&self) -> Option<ArcStr> {
-        None
-        // let pid = get_current_pid().ok()?;
-        // let parent_pid = s.process(pid)?.parent()?;
-        // let parent = s.process(parent_pid)?.name();
-        // Some(parent.replace(".exe", ""))
+        let process = |query: String| wmi_query(&query)?.into_iter().next();
+        let current = process(format!(
+            "SELECT ParentProcessId FROM Win32_Process WHERE ProcessId = {}",
+            std::process::id()
+        ))?;
+        let parent = wmi_number(current.get("ParentProcessId"))?;
+        let parent = process(format!(
+            "SELECT Name FROM Win32_Process WHERE ProcessId = {parent}"
+        ))?;
+        let Some(Variant::String(name)) = parent.get("Name") else {
+            return None;
+        };
+        Some(ArcStr::from(name.trim_end_matches(".exe")))
+    }
+
+    fn arch(&self) -> Option<ArcStr> {
+        // A 32-bit process on a 64-bit system finds the system's architecture in the W6432 variable
+        let native = std::env::var("PROCESSOR_ARCHITEW6432")
+            .or_else(|_| std::env::var("PROCESSOR_ARCHITECTURE"))
+            .ok()?;
+        let machine = match native.as_str() {
+            "AMD64" => "x86_64",
+            "ARM64" => "aarch64",
+            "x86" => "i686",
+            x => x,
+        };
+        Some(arcstr::format!("{machine}, little-endian"))
+    }
+
+    fn packages(&self) -> Option<ArcStr> {
+        let scoop = std::env::var_os("SCOOP")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("USERPROFILE").map(|x| PathBuf::from(x).join("scoop")));
+        let choco = std::env::var_os("ChocolateyInstall").map_or_else(
+            || PathBuf::from(r"C:\ProgramData\chocolatey"),
+            PathBuf::from,
+        );
+        let counts = [
+            // Scoop is installed as one of its own apps
+            (
+                "scoop",
+                scoop
+                    .and_then(|x| count_dirs(x.join("apps")))
+                    .map(|x| x.saturating_sub(1)),
+            ),
+            ("choco", count_dirs(choco.join("lib"))),
+        ];
+        let res = counts
+            .into_iter()
+            .filter_map(|(name, count)| Some((name, count.filter(|x| *x > 0)?)))
+            .map(|(name, count)| format!("{count} ({name})"))
+            .join(", ");
+        (!res.is_empty()).then(|| ArcStr::from(res))
     }
 
-    fn cpu(&self) -> Option<ArcStr> {
+    fn cpu(&self) -> Option<Cpu> {
         let mut length = 0;
         let mut buf: Vec<u8>;
         let mut core_count = 0;
         unsafe {
-            GetLogicalProcessorInformationEx(RelationAll, None, &mut length);
+            // Fails with the size of the buffer it needs
+            let _ = GetLogicalProcessorInformationEx(RelationAll, None, &raw mut length);
             buf = Vec::<u8>::with_capacity(length as usize);
 
             GetLogicalProcessorInformationEx(
                 RelationAll,
                 Some(buf.as_mut_ptr().cast()),
-                &mut length,
-            );
+                &raw mut length,
+            )
+            .ok()?;
             let mut current: *mut SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX = buf.as_mut_ptr().cast();
             while current.cast() < buf.as_mut_ptr().add(length as usize) {
                 if (*current).Relationship == RelationProcessorCore {
@@ -452,15 +561,15 @@ impl OSInfo for WindowsInfo {
             .get_hklm()
             .open_subkey(r"HARDWARE\DESCRIPTION\System\CentralProcessor\0")
             .ok()?;
-        let name: ArcStr = core0
+        let name = core0
             .get_value::<String, &str>("ProcessorNameString")
-            .ok()?
-            .into();
+            .ok()?;
         let freq: u32 = core0.get_value("~MHz").ok()?;
-        Some(arcstr::format!(
-            "{name} ({core_count}) @ {:.2}GHz",
-            freq as f32 / 1000.0
-        ))
+        Some(Cpu {
+            cores: Some(f64::from(core_count)),
+            freq_mhz: Some(f64::from(freq)),
+            ..Cpu::new(name)
+        })
     }
 
     fn username(&self) -> std::option::Option<ArcStr> {
@@ -0,0 +1,144 @@
+#![cfg(target_os = "linux")]
+use std::sync::OnceLock;
+
+use arcstr::ArcStr;
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        randr::{self, ConnectionExt},
+        xproto::{AtomEnum, ConnectionExt as _},
+    },
+    rust_connection::RustConnection,
+};
+
+/// The connection opened by [`preconnect`], with the number of the default screen
+static CONNECTION: OnceLock<(RustConnection, usize)> = OnceLock::new();
+
+/// Connect to the X server at `DISPLAY` now rather than when it's first asked
+pub fn preconnect() {
+    if let Ok(conn) = x11rb::connect(None) {
+        let _ = CONNECTION.set(conn);
+    }
+}
+
+/// Call `f` with the connection opened by [`preconnect`], or with a new one
+fn with_connection<T>(f: impl FnOnce(&RustConnection, usize) -> Option<T>) -> Option<T> {
+    if let Some((conn, screen_num)) = CONNECTION.get() {
+        return f(conn, *screen_num);
+    }
+    let (conn, screen_num) = x11rb::connect(None).ok()?;
+    f(&conn, screen_num)
+}
+
+/// Query connected outputs through the X11 `RandR` extension
+///
+/// Returns `None` when no X server can be reached
+pub fn outputs() -> Option<Vec<ArcStr>> {
+    with_connection(outputs_of)
+}
+
+fn outputs_of(conn: &RustConnection, screen_num: usize) -> Option<Vec<ArcStr>> {
+    let root = conn.setup().roots.get(screen_num)?.root;
+    let resources = conn
+        .randr_get_screen_resources_current(root)
+        .ok()?
+        .reply()
+        .ok()?;
+    let primary = conn
+        .randr_get_output_primary(root)
+        .ok()
+        .and_then(|x| x.reply().ok())
+        .map(|x| x.output);
+
+    let mut res = Vec::new();
+    for output in &resources.outputs {
+        let Some(info) = conn
+            .randr_get_output_info(*output, resources.config_timestamp)
+            .ok()
+            .and_then(|x| x.reply().ok())
+        else {
+            continue;
+        };
+        if info.connection != randr::Connection::CONNECTED || info.crtc == 0 {
+            continue;
+        }
+        let Some(crtc) = conn
+            .randr_get_crtc_info(info.crtc, resources.config_timestamp)
+            .ok()
+            .and_then(|x| x.reply().ok())
+        else {
+            continue;
+        };
+        let Some(mode) = resources.modes.iter().find(|x| x.id == crtc.mode) else {
+            continue;
+        };
+
+        let frame = u32::from(mode.htotal) * u32::from(mode.vtotal);
+        let mut details = vec![if frame == 0 {
+            format!("{}x{}", crtc.width, crtc.height)
+        } else {
+            let mut refresh = f64::from(mode.dot_clock) / f64::from(frame);
+            if mode.mode_flags.contains(randr::ModeFlag::DOUBLE_SCAN) {
+                refresh /= 2.0;
+            }
+            if mode.mode_flags.contains(randr::ModeFlag::INTERLACE) {
+                refresh *= 2.0;
+            }
+            format!("{}x{} @ {refresh:.0}Hz", crtc.width, crtc.height)
+        }];
+        if info.mm_width != 0 {
+            let dpi = f64::from(crtc.width) * 25.4 / f64::from(info.mm_width);
+            details.push(format!("{dpi:.0} dpi"));
+        }
+        if primary == Some(*output) {
+            details.push(String::from("primary"));
+        }
+        res.push(arcstr::format!(
+            "{}: {}",
+            String::from_utf8_lossy(&info.name),
+            details.join(", ")
+        ));
+    }
+    Some(res)
+}
+
+/// Name of the window manager, from the window it points to with `_NET_SUPPORTING_WM_CHECK`
+///
+/// Returns `None` when no X server can be reached or the window manager isn't EWMH compliant
+pub fn wm_name() -> Option<ArcStr> {
+    with_connection(wm_name_of)
+}
+
+fn wm_name_of(conn: &RustConnection, screen_num: usize) -> Option<ArcStr> {
+    let root = conn.setup().roots.get(screen_num)?.root;
+    let atom = |name: &[u8]| Some(conn.intern_atom(false, name).ok()?.reply().ok()?.atom);
+    let window = conn
+        .get_property(
+            false,
+            root,
+            atom(b"_NET_SUPPORTING_WM_CHECK")?,
+            AtomEnum::WINDOW,
+            0,
+            1,
+        )
+        .ok()?
+        .reply()
+        .ok()?
+        .value32()?
+        .next()?;
+    let name = conn
+        .get_property(
+            false,
+            window,
+            atom(b"_NET_WM_NAME")?,
+            atom(b"UTF8_STRING")?,
+            0,
+            u32::from(u8::MAX),
+        )
+        .ok()?
+        .reply()
+        .ok()?;
+    let name = String::from_utf8_lossy(&name.value);
+    let name = name.trim_matches(|x: char| x.is_whitespace() || x == '\0');
+    (!name.is_empty()).then(|| ArcStr::from(name))
+}
@@ -0,0 +1,95 @@
+//! Serial numbers and hardware addresses for asset inventories, printed by `mirafetch inventory`
+//!
+//! Reading the serial number usually needs root, as firmware tables keep it private
+use arcstr::ArcStr;
+use serde::Serialize;
+
+use crate::info::OSInfo;
+
+/// Fields of the inventory, in the order of the CSV columns
+pub const FIELDS: &[&str] = &[
+    "hostname",
+    "host",
+    "serial_number",
+    "asset_tag",
+    "os",
+    "os_build",
+    "mac_addresses",
+    "disks",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Inventory {
+    pub hostname: Option<ArcStr>,
+    pub host: Option<ArcStr>,
+    pub serial_number: Option<ArcStr>,
+    pub asset_tag: Option<ArcStr>,
+    pub os: Option<ArcStr>,
+    pub os_build: Option<ArcStr>,
+    pub mac_addresses: Vec<Nic>,
+    pub disks: Vec<Drive>,
+}
+
+/// A physical network interface
+#[derive(Debug, Clone, Serialize)]
+pub struct Nic {
+    pub interface: ArcStr,
+    pub mac: ArcStr,
+}
+
+/// A physical drive with its serial number
+#[derive(Debug, Clone, Serialize)]
+pub struct Drive {
+    pub name: ArcStr,
+    pub model: Option<ArcStr>,
+    pub serial: ArcStr,
+}
+
+impl Inventory {
+    /// Collect the inventory from `getter`
+    #[must_use]
+    pub fn from_source(getter: &impl OSInfo) -> Self {
+        Self {
+            hostname: getter.hostname(),
+            host: getter.machine(),
+            serial_number: getter.serial_number(),
+            asset_tag: getter.asset_tag(),
+            os: getter.os(),
+            os_build: getter.os_build(),
+            mac_addresses: getter
+                .mac_addresses()
+                .into_iter()
+                .map(|(interface, mac)| Nic { interface, mac })
+                .collect(),
+            disks: getter.disk_serials(),
+        }
+    }
+
+    /// `Label: value` lines, with a line for each network interface and drive
+    #[must_use]
+    pub fn lines(&self) -> Vec<(ArcStr, ArcStr)> {
+        [
+            ("Hostname", &self.hostname),
+            ("Host", &self.host),
+            ("Serial Number", &self.serial_number),
+            ("Asset Tag", &self.asset_tag),
+            ("OS", &self.os),
+            ("OS Build", &self.os_build),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| Some((ArcStr::from(label), value.clone()?)))
+        .chain(
+            self.mac_addresses
+                .iter()
+                .map(|x| (arcstr::format!("MAC ({})", x.interface), x.mac.clone())),
+        )
+        .chain(self.disks.iter().map(|x| {
+            let value = x.model.as_ref().map_or_else(
+                || x.serial.clone(),
+                |model| arcstr::format!("{model} ({})", x.serial),
+            );
+            (arcstr::format!("Disk ({})", x.name), value)
+        }))
+        .collect()
+    }
+}
@@ -1,11 +1,47 @@
+//! System information as shown by mirafetch, for use from other programs
+//!
+//! [`info::Info`] collects every module in parallel, honoring the collection options of a
+//! [`config::Config`], and [`info::backend`] asks the current platform's backend for single
+//! modules through the [`info::OSInfo`] trait. The rest of the crate renders it, and the
+//! `mirafetch` binary only parses its arguments and settings on top
+//!
+//! ```no_run
+//! use mirafetch::{config::Config, info::{self, Info, OSInfo}};
+//!
+//! let info = Info::from_config(&Config::default());
+//! println!("{:?} on {:?}", info.os, info.cpu);
+//! println!("{:?}", info::backend().memory());
+//! ```
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
 #![allow(clippy::missing_panics_doc)]
 #![warn(clippy::style)]
 #![allow(clippy::cast_precision_loss)]
 
+pub mod audit;
+pub mod badge;
 pub mod colorizer;
 pub mod config;
+#[cfg(feature = "images")]
+pub mod image;
 pub mod info;
+pub mod inventory;
+#[cfg(feature = "mdns")]
+pub mod mdns;
+pub mod output;
+pub mod palette;
+#[cfg(feature = "publish")]
+pub mod publish;
+pub mod quirks;
+pub mod render;
+#[cfg(feature = "seccomp")]
+pub mod sandbox;
+#[cfg(feature = "signatures")]
+pub mod signature;
+pub mod stats;
 mod tests;
+pub mod transform;
+#[cfg(feature = "update")]
+pub mod update;
 pub mod util;
+pub mod version;
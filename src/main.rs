@@ -5,38 +5,1378 @@
 
 use anyhow::{anyhow, Result};
 use arcstr::ArcStr;
+use clap::{builder::PossibleValuesParser, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use crossterm::{
-    cursor::{position, MoveTo, MoveToColumn, MoveToNextLine},
-    style::{Color, PrintStyledContent, Stylize},
-    terminal::{Clear, ClearType::All},
-    ExecutableCommand,
+    style::{Color, Stylize},
+    terminal,
 };
 use directories::ProjectDirs;
+use itertools::Itertools;
+#[cfg(feature = "signatures")]
+use mirafetch::signature::TrustedKeys;
 use mirafetch::{
-    colorizer::{Colorizer, Default, Flag},
-    config::Config,
+    audit::{Kind, Sources},
+    badge,
+    colorizer::{gradient, Colorizer, Default, Flag},
+    config::{
+        Config, Direction, FlagStyle, IpVersion, LogoSize, LogoSource, Orientation, PaletteName,
+    },
     info::Info,
-    util::{get_colorscheme, get_icon, AsciiArt},
+    output,
+    palette::Palette,
+    quirks::Quirks,
+    render::{icon_height, render, render_lines, visible_width},
+    stats::Stats,
+    util::{colorscheme_names, find_icon, get_colorscheme, load_logo_file, AsciiArt},
 };
-use std::{cmp::max, fmt::Display, fs, io::stdout, process::ExitCode, sync::Arc};
-mod util;
+#[cfg(feature = "update")]
+use mirafetch::{config::UpdateInterval, update};
+use std::{
+    fmt::Display,
+    fs,
+    io::{stdout, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+mod configure;
+
+#[derive(Parser)]
+#[command(version, about)]
+#[allow(clippy::struct_excessive_bools)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// How to print the system information, `plain` when stdout isn't a terminal and `pretty` otherwise
+    #[arg(long, visible_alias = "format", global = true, value_enum)]
+    output: Option<OutputFormat>,
+    /// Short for `--output json`
+    #[arg(long, global = true, conflicts_with = "output")]
+    json: bool,
+    /// Keep the colors when stdout isn't a terminal
+    #[arg(long)]
+    force_color: bool,
+    /// Show the logo when stdout isn't a terminal, instead of switching to plain output
+    #[arg(long)]
+    force_logo: bool,
+    /// Print line by line below the cursor instead of clearing the screen, e.g. from a shell greeting
+    #[arg(long)]
+    inline: bool,
+    /// Only show these comma separated modules, see `mirafetch list modules`
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "MODULES",
+        value_parser = PossibleValuesParser::new(output::MODULES.iter().map(|(name, ..)| *name)),
+    )]
+    show: Vec<String>,
+    /// Print the JSON Schema of `--output json` and exit
+    #[arg(long)]
+    schema: bool,
+    /// Show why modules came back empty, e.g. `GPU: <error: permission denied reading /sys/...>`
+    #[arg(long)]
+    strict: bool,
+    /// Print which modules came back empty and why to stderr, and show the rest as usual
+    #[arg(long)]
+    debug: bool,
+    /// Show a snapshot saved with `--output json` instead of this system's information
+    #[arg(long, value_name = "SNAPSHOT")]
+    from_json: Option<PathBuf>,
+    /// Show the ASCII art in this file instead of the icon, see the `logo_file` setting
+    #[arg(long, value_name = "PATH")]
+    logo_file: Option<PathBuf>,
+    /// Show the large or the small variant of the logo, or none, see the `logo_size` setting
+    #[arg(long, value_enum, value_name = "SIZE")]
+    logo_size: Option<LogoSize>,
+    /// Color the logo with this flag, see `mirafetch list themes` and the `scheme_name` setting
+    #[arg(long, value_name = "NAME", value_parser = flag_name)]
+    flag: Option<String>,
+    /// Show this image instead of the icon, in terminals that can draw images
+    #[cfg(feature = "images")]
+    #[arg(long, value_name = "PATH")]
+    image: Option<PathBuf>,
+    /// Collect the system information without printing it
+    #[arg(short, long)]
+    quiet: bool,
+    /// Which addresses the `ip` module shows, see the `ip_version` setting
+    #[arg(long, value_enum, value_name = "VERSION")]
+    ip_version: Option<IpVersion>,
+    /// Look up the address the machine has on the internet, see the `public_ip` setting
+    #[cfg(feature = "public-ip")]
+    #[arg(long)]
+    public_ip: bool,
+    /// Only allow reading files and system details from here on, see the `hardened` setting
+    #[arg(long)]
+    hardened: bool,
+    /// List the files, sockets, hosts and commands the other options and the settings would use,
+    /// without using them, and exit
+    #[arg(long)]
+    audit_sources: bool,
+    /// Columns of `--output csv`, as named in the JSON output, e.g. `hostname,os,cpu,disks.0.usage`
+    #[arg(long, global = true, value_delimiter = ',', value_name = "FIELDS")]
+    fields: Vec<String>,
+    /// Exit with status 2 if any of these comma separated fields (as named in the JSON output) could not be collected
+    #[arg(long, value_delimiter = ',', value_name = "FIELDS")]
+    fail_on_missing: Vec<String>,
+    /// Keep answering `mirafetch discover` on the LAN with this machine's hostname, OS and uptime
+    #[cfg(all(feature = "mdns", unix))]
+    #[arg(long)]
+    advertise: bool,
+    /// Keep sending the JSON output to the `publish_to` webhook or MQTT topic, see `publish_interval`
+    #[cfg(feature = "publish")]
+    #[arg(long)]
+    publish: bool,
+    /// Check whether a newer release of mirafetch is available and exit
+    #[cfg(feature = "update")]
+    #[arg(long)]
+    check_update: bool,
+}
+
+impl Args {
+    /// Let options that have a setting of the same name take precedence over it
+    fn override_settings(&self, settings: &mut Config) {
+        if self.strict {
+            settings.strict = Some(true);
+        }
+        if let Some(flag) = &self.flag {
+            settings.scheme_name = Some(flag.as_str().into());
+            settings.orientation.get_or_insert(Orientation::Horizontal);
+        }
+        if let Some(path) = &self.logo_file {
+            settings.logo_file = Some(path.clone());
+        }
+        if self.logo_size.is_some() {
+            settings.logo_size = self.logo_size;
+        }
+        if self.ip_version.is_some() {
+            settings.ip_version = self.ip_version;
+        }
+        #[cfg(feature = "public-ip")]
+        if self.public_ip {
+            settings.public_ip = Some(true);
+        }
+        #[cfg(feature = "images")]
+        if let Some(image) = &self.image {
+            settings.image = Some(image.clone());
+        }
+    }
+
+    /// Output format and target, where output to a pipe or file is plain unless the logo is asked for
+    fn format(&self) -> (OutputFormat, Target) {
+        let terminal = stdout().is_terminal();
+        let target = if terminal && !self.inline {
+            Target::Screen
+        } else {
+            Target::Lines {
+                color: (terminal && self.inline) || self.force_color,
+            }
+        };
+        let output = self
+            .output
+            .or_else(|| self.json.then_some(OutputFormat::Json));
+        let format = output.unwrap_or(if terminal || self.force_logo {
+            OutputFormat::Pretty
+        } else {
+            OutputFormat::Plain
+        });
+        (format, target)
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print a single field, e.g. `cpu` or `gpus.0`, named as in the JSON output
+    Get { field: String },
+    /// List names accepted by mirafetch's options, one per line
+    List {
+        #[arg(value_enum)]
+        kind: ListKind,
+    },
+    /// Print a completion script for a shell
+    Completions { shell: Shell },
+    /// Flag patterns that can be overlaid on the icon
+    Themes {
+        #[command(subcommand)]
+        command: ThemesCommand,
+    },
+    /// Show how long each module took in recent runs, recorded with the `stats` setting
+    Stats,
+    /// Pick the modules, their order and the theme with a live preview, and save them
+    Configure,
+    /// Print an SVG card with the OS, CPU, GPU, editor and uptime, e.g. for a profile README
+    Badge {
+        #[arg(long, value_enum, default_value = "dark")]
+        theme: badge::Theme,
+    },
+    /// Print the serial number, asset tag, MAC addresses, drive serials and OS build, e.g. as
+    /// CSV or JSON with --output for an asset inventory
+    Inventory,
+    /// List the machines on the LAN running `mirafetch --advertise`
+    #[cfg(all(feature = "mdns", unix))]
+    Discover,
+}
+
+#[derive(Subcommand)]
+enum ThemesCommand {
+    /// Show every flag pattern on the small logo next to a sample of the system information
+    Preview,
+}
 
+#[derive(Clone, Copy, ValueEnum)]
+enum ListKind {
+    /// Modules for --show
+    Modules,
+    /// Flag patterns for the `scheme_name` setting
+    Themes,
+    /// Color palettes for the `palette` setting
+    Palettes,
+    /// Fields of the JSON output, for `get` and --fail-on-missing
+    Fields,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    /// The logo next to colored system information
+    Pretty,
+    /// One `Label: value` line per field, without the logo or colors
+    Plain,
+    /// A JSON document, see --schema
+    Json,
+    /// The JSON document's fields as YAML
+    Yaml,
+    /// The JSON document's fields as TOML, leaving out the ones that weren't collected
+    Toml,
+    /// Home Assistant MQTT discovery configs and state for memory, disk, battery and uptime
+    Hass,
+    /// A header row of the JSON document's fields, or those picked with --fields, and a row of
+    /// their values
+    Csv,
+    /// Some of the facts Ansible gathers, with the same names, e.g. `ansible_distribution`
+    Ansible,
+}
+
+/// Where the pretty output is written
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Target {
+    /// Drawn with cursor movements from the top of a cleared screen
+    Screen,
+    /// Written line by line from wherever the cursor is, with or without colors
+    Lines { color: bool },
+}
+
+/// A module failed, or a field given to `--fail-on-missing` could not be collected
+const EXIT_MISSING: u8 = 2;
+/// The config file could not be read or is invalid
+const EXIT_CONFIG: u8 = 3;
+
+#[allow(clippy::too_many_lines)]
 fn main() -> anyhow::Result<std::process::ExitCode> {
-    let settings = load_settings_file()?;
-    let scheme = get_colorscheme_from_settings(&settings);
+    let args = Args::parse();
+    if args.schema {
+        print!("{}", output::SCHEMA);
+        return Ok(ExitCode::SUCCESS);
+    }
+    #[cfg(feature = "update")]
+    if args.check_update {
+        return check_update();
+    }
+    match &args.command {
+        Some(Command::List { kind }) => {
+            list(*kind);
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Completions { shell }) => {
+            clap_complete::generate(*shell, &mut Args::command(), "mirafetch", &mut stdout());
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Stats) => return print_stats(),
+        Some(Command::Inventory) => return print_inventory(args.format().0, &args.fields),
+        _ => {}
+    }
+
+    let mut settings = match load_settings_file() {
+        Ok(settings) => settings,
+        Err(err) => {
+            eprintln!("{err}");
+            return Ok(ExitCode::from(EXIT_CONFIG));
+        }
+    };
+    apply_resource_limits(&settings)?;
+    args.override_settings(&mut settings);
+    let hardened = args.hardened || settings.hardened.unwrap_or_default();
+    if hardened {
+        if let Some(what) = refused_when_hardened(&args) {
+            return Err(anyhow!(
+                "{what} can't be used in hardened mode, which refuses network access and writing files"
+            ));
+        }
+        harden(&mut settings)?;
+    }
+
+    if args.audit_sources {
+        return audit_sources(&args, &settings);
+    }
+    if matches!(args.command, Some(Command::Configure)) {
+        return configure::run(settings, Reloader::new(&args));
+    }
+    if matches!(
+        args.command,
+        Some(Command::Themes {
+            command: ThemesCommand::Preview
+        })
+    ) {
+        preview_themes(&settings)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    #[cfg(feature = "publish")]
+    if args.publish {
+        return daemon(&args, &settings);
+    }
+    #[cfg(all(feature = "mdns", unix))]
+    if args.advertise {
+        return advertise(&settings);
+    }
+    #[cfg(all(feature = "mdns", unix))]
+    if matches!(args.command, Some(Command::Discover)) {
+        return discover(&settings, args.format().0);
+    }
+    // Errors are always collected so failed modules set the exit status, and only shown inline
+    // in strict mode
+    let strict = settings.strict.unwrap_or_default();
+    settings.strict = Some(true);
+    let (format, target) = args.format();
+    if format_is_fixed(format, &args) {
+        settings.accessible.get_or_insert(false);
+    }
+    // `get` only needs the module behind its field
+    let only = match &args.command {
+        Some(Command::Get { field }) => Some(output::module(field)),
+        _ => None,
+    };
+    let mut info = collect(&settings, args.from_json.as_deref(), only)?;
+    if args.debug {
+        print_debug(&info);
+    }
+    let failed = info.failed_modules();
+    if !strict {
+        info.errors.clear();
+    }
+    match &args.command {
+        Some(Command::Get { field }) => return print_field(&info, field, format),
+        Some(Command::Badge { theme }) => {
+            print!("{}", badge(&settings, &info, *theme)?);
+            return Ok(ExitCode::SUCCESS);
+        }
+        _ => {}
+    }
+    let format = accessible_format(format, &settings, &info);
+    let modules = shown_modules(&settings, &args.show, args.from_json.is_some());
+    let missing = output::missing_fields(&info, &args.fail_on_missing)?;
+    if !args.quiet {
+        match format {
+            OutputFormat::Json => println!("{}", output::to_json(&info)?),
+            OutputFormat::Yaml => print!("{}", output::to_yaml(&info)?),
+            OutputFormat::Toml => print!("{}", output::to_toml(&info)?),
+            OutputFormat::Hass => println!("{}", output::to_hass(&info)?),
+            OutputFormat::Csv => print!("{}", output::to_csv(&info, &args.fields)?),
+            OutputFormat::Ansible => println!("{}", output::to_ansible(&info)?),
+            OutputFormat::Plain => plain(&settings, info, &modules, args.force_color),
+            OutputFormat::Pretty => show(&settings, info, &modules, target)?,
+        }
+    }
+
+    if !missing.is_empty() {
+        eprintln!("Could not collect: {}", missing.join(", "));
+        return Ok(ExitCode::from(EXIT_MISSING));
+    }
+    if !failed.is_empty() {
+        eprintln!("Failed to collect: {}", failed.join(", "));
+        return Ok(ExitCode::from(EXIT_MISSING));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_stats() -> Result<ExitCode> {
+    let stats = Stats::load(&stats_path()?)?;
+    if stats.runs == 0 {
+        println!("No runs recorded yet, set `stats = true` in the configuration to record them");
+    } else {
+        print!("{}", stats.report());
+    }
+    Ok(ExitCode::SUCCESS)
+}
 
-    let info = Info::default();
-    let id = info.id.clone();
-    let info_vec = info.as_vec();
-    let logo: AsciiArt = get_icon(get_os_id(&settings, id.as_str()))?;
-    let colored_logo = colorize_logo(&settings, &scheme, &logo)?;
+/// Print the details for an asset inventory in `format`, as `Label: value` lines unless it's
+/// one of the structured formats
+fn print_inventory(format: OutputFormat, fields: &[String]) -> Result<ExitCode> {
+    let inventory = mirafetch::info::inventory();
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&inventory)?),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&inventory)?),
+        OutputFormat::Toml => print!("{}", toml::to_string(&inventory)?),
+        OutputFormat::Csv if fields.is_empty() => {
+            print!("{}", output::csv(&inventory, mirafetch::inventory::FIELDS)?);
+        }
+        OutputFormat::Csv => print!("{}", output::csv(&inventory, fields)?),
+        OutputFormat::Hass | OutputFormat::Ansible => {
+            return Err(anyhow!(
+                "The inventory can only be printed as plain text, JSON, YAML, TOML or CSV"
+            ))
+        }
+        OutputFormat::Plain | OutputFormat::Pretty => print_plain(&inventory.lines(), None),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Answer `mirafetch discover` until interrupted, collecting the information again each minute
+#[cfg(all(feature = "mdns", unix))]
+fn advertise(settings: &Config) -> Result<ExitCode> {
+    // `Duration::from_mins` needs Rust 1.91
+    #[allow(clippy::duration_suboptimal_units)]
+    const REFRESH: Duration = Duration::from_secs(60);
+    let summary = || mirafetch::mdns::summary(Info::from_config(settings));
+    let mut txt = summary();
+    let mut collected = Instant::now();
+    let host = txt
+        .iter()
+        .find(|(key, _)| key == "host")
+        .map_or_else(|| String::from("mirafetch"), |(_, value)| value.clone());
+    eprintln!("Advertising {host} on the LAN, press Ctrl+C to stop");
+    mirafetch::mdns::advertise(&host, || {
+        if collected.elapsed() > REFRESH {
+            txt = summary();
+            collected = Instant::now();
+        }
+        txt.clone()
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print what collecting and showing the system information would read, connect to and run
+/// with `settings` and the options in `args`, without doing any of it
+fn audit_sources(args: &Args, settings: &Config) -> Result<ExitCode> {
+    let path = |x: &Path| x.display().to_string();
+    let mut sources = args.from_json.as_deref().map_or_else(
+        || Sources::for_collection(settings),
+        |snapshot| {
+            let mut res = Sources::default();
+            res.add(Kind::File, path(snapshot), "--from-json");
+            res
+        },
+    );
+    for config in config_paths()? {
+        sources.add(Kind::File, path(&config), "settings");
+    }
+    let dirs = project_dirs()?;
+    if settings.cache.unwrap_or_default() && args.from_json.is_none() {
+        let memo = dirs.cache_dir().join("memo.json");
+        sources.add(Kind::File, path(&memo), "cache (written)");
+    }
+    if settings.stats.unwrap_or_default() {
+        sources.add(Kind::File, path(&stats_path()?), "stats (written)");
+    }
+    for dir in logo_dirs(settings) {
+        sources.add(Kind::File, path(&dir), "logo_dirs");
+    }
+    if let Some(logo) = &settings.logo_file {
+        sources.add(Kind::File, path(logo), "logo_file");
+    }
+    if let Some(image) = &settings.image {
+        sources.add(Kind::File, path(image), "image");
+    }
+    if settings.verify_logos.unwrap_or_default() {
+        let keys = dirs.config_dir().join("trusted_keys");
+        sources.add(Kind::File, path(&keys), "verify_logos");
+    }
+    if std::env::var_os("TMUX").is_some() {
+        sources.add(
+            Kind::Command,
+            "tmux show-options -Apv allow-passthrough",
+            "terminal_quirks",
+        );
+    }
+    #[cfg(feature = "update")]
+    if settings
+        .update_check
+        .and_then(UpdateInterval::duration)
+        .is_some()
+    {
+        sources.add(Kind::Network, update::LATEST_RELEASE_URL, "update_check");
+        let cached = dirs.cache_dir().join("latest_version");
+        sources.add(Kind::File, path(&cached), "update_check (written)");
+    }
+    #[cfg(feature = "publish")]
+    if args.publish {
+        if let Some(target) = &settings.publish_to {
+            sources.add(
+                Kind::Network,
+                mirafetch::publish::redact(target),
+                "publish_to",
+            );
+        }
+    }
+    #[cfg(all(feature = "mdns", unix))]
+    {
+        let group = format!(
+            "{}:{} (mDNS)",
+            mirafetch::mdns::GROUP,
+            mirafetch::mdns::PORT
+        );
+        if args.advertise {
+            sources.add(Kind::Network, group.as_str(), "--advertise");
+        }
+        if matches!(args.command, Some(Command::Discover)) {
+            sources.add(Kind::Network, group.as_str(), "discover");
+            for peer in settings.discover_peers.iter().flatten() {
+                sources.add(Kind::Network, peer.as_str(), "discover_peers");
+            }
+        }
+    }
+    match args.format().0 {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&sources.iter().collect::<Vec<_>>())?
+        ),
+        OutputFormat::Yaml => print!(
+            "{}",
+            serde_yaml::to_string(&sources.iter().collect::<Vec<_>>())?
+        ),
+        _ => {
+            for line in sources.lines() {
+                println!("{line}");
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print the names of a kind, one per line
+fn list(kind: ListKind) {
+    let names = match kind {
+        ListKind::Modules => output::MODULES
+            .iter()
+            .map(|(name, ..)| (*name).to_string())
+            .collect(),
+        ListKind::Themes => colorscheme_names(),
+        ListKind::Palettes => PaletteName::value_variants()
+            .iter()
+            .filter_map(|x| Some(x.to_possible_value()?.get_name().to_string()))
+            .collect(),
+        ListKind::Fields => output::fields(),
+    };
+    for name in names {
+        println!("{name}");
+    }
+}
+
+/// Publish until interrupted, advertising on the LAN at the same time if asked to
+#[cfg(feature = "publish")]
+fn daemon(args: &Args, settings: &Config) -> Result<ExitCode> {
+    std::thread::scope(|s| {
+        #[cfg(all(feature = "mdns", unix))]
+        if args.advertise {
+            s.spawn(|| {
+                if let Err(err) = advertise(settings) {
+                    eprintln!("Stopped advertising: {err}");
+                }
+            });
+        }
+        publish(
+            settings.clone(),
+            Reloader::new(args),
+            matches!(args.format().0, OutputFormat::Hass),
+        )
+    })
+}
+
+/// Send the JSON output, or Home Assistant discovery messages with `hass`, to `publish_to` every
+/// `publish_interval` seconds until interrupted
+///
+/// Failures are reported and retried at the next interval, as the target may only be down for a
+/// while. Changes to the config file are picked up before each one
+#[cfg(feature = "publish")]
+fn publish(mut settings: Config, mut reloader: Reloader, hass: bool) -> Result<ExitCode> {
+    let mut announced = None;
+    loop {
+        let started = Instant::now();
+        if let Some(changed) = reloader.reload() {
+            match changed {
+                Ok(changed) => settings = changed,
+                Err(err) => eprintln!("Keeping the previous settings, {err}"),
+            }
+        }
+        let target = settings
+            .publish_to
+            .clone()
+            .ok_or_else(|| anyhow!("Set publish_to in the configuration file to use --publish"))?;
+        let interval = Duration::from_secs(settings.publish_interval.unwrap_or(60).max(1));
+        if announced.as_ref() != Some(&(target.clone(), interval)) {
+            eprintln!(
+                "Publishing to {} every {}s, press Ctrl+C to stop",
+                mirafetch::publish::redact(&target),
+                interval.as_secs()
+            );
+            announced = Some((target.clone(), interval));
+        }
+        let info = Info::from_config(&settings);
+        let sent = if hass {
+            mirafetch::publish::send_hass(&target, &info)
+        } else {
+            mirafetch::publish::send(&target, &output::to_json(&info)?)
+        };
+        if let Err(err) = sent {
+            eprintln!(
+                "Could not publish to {}: {err}",
+                mirafetch::publish::redact(&target)
+            );
+        }
+        std::thread::sleep(interval.saturating_sub(started.elapsed()));
+    }
+}
+
+/// Print a table of the machines that answered, or their details as JSON
+///
+/// Machines in the `discover_peers` setting are asked directly as well, for those that
+/// multicast doesn't reach
+#[cfg(all(feature = "mdns", unix))]
+fn discover(settings: &Config, format: OutputFormat) -> Result<ExitCode> {
+    use unicode_width::UnicodeWidthStr;
+
+    const COLUMNS: [(&str, &str); 5] = [
+        ("host", "HOST"),
+        ("os", "OS"),
+        ("uptime", "UPTIME"),
+        ("load", "LOAD"),
+        ("disk_free", "DISK FREE"),
+    ];
+    let peers = settings.discover_peers.as_deref().unwrap_or_default();
+    let machines = mirafetch::mdns::discover(Duration::from_secs(2), peers)?;
+    if matches!(format, OutputFormat::Json) {
+        let machines = machines
+            .into_iter()
+            .map(|pairs| {
+                pairs
+                    .into_iter()
+                    .map(|(key, value)| (key, serde_json::Value::String(value)))
+                    .collect::<serde_json::Map<_, _>>()
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&machines)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+    if machines.is_empty() {
+        eprintln!("No machines found, is `mirafetch --advertise` running on any?");
+        return Ok(ExitCode::SUCCESS);
+    }
+    // Machines running older versions leave out some of the columns
+    let rows = machines
+        .iter()
+        .map(|pairs| {
+            COLUMNS.map(|(key, _)| {
+                pairs
+                    .iter()
+                    .find(|(x, _)| x == key)
+                    .map_or("-", |(_, value)| value.as_str())
+            })
+        })
+        .collect::<Vec<_>>();
+    let headers = COLUMNS.map(|(_, header)| header);
+    let widths = (0..COLUMNS.len())
+        .map(|idx| {
+            rows.iter()
+                .chain([&headers])
+                .map(|row| UnicodeWidthStr::width(row[idx]))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    for row in std::iter::once(&headers).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(text, width)| format!("{text}{:1$}", "", width - UnicodeWidthStr::width(*text)))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Show every flag pattern on the small logo next to a sample of the system information, in a
+/// grid as wide as the terminal, to help pick a `scheme_name`
+fn preview_themes(settings: &Config) -> Result<()> {
+    const GAP: usize = 4;
+    const VALUE_WIDTH: usize = 24;
+    let quirks = Quirks::detect(settings.terminal_quirks);
+    let info = Info::from_config(settings);
+    let (id, source) = logo_names(settings, &info);
+    let palette = palette(settings, &info).adapt(&quirks);
+    let small = Config {
+        logo_size: Some(LogoSize::Small),
+        ..settings.clone()
+    };
+    let logo = pick_logo(&small, &id, source.as_deref())?;
+    // user@host and the first three modules, leaving out the line under it and the palette
+    let mut sample = info.as_vec();
+    let title = sample.remove(0);
+    sample.retain(|(label, value)| !label.starts_with('-') && !value.is_empty());
+    sample.truncate(3);
+    sample.insert(0, title);
+    // Long values are cut short to fit more themes side by side
+    for (_, value) in &mut sample {
+        if value.chars().count() > VALUE_WIDTH {
+            *value = arcstr::format!(
+                "{}…",
+                value.chars().take(VALUE_WIDTH - 1).collect::<String>()
+            );
+        }
+    }
+
+    let orientation = settings.orientation.unwrap_or(Orientation::Horizontal);
+    let length = match orientation {
+        Orientation::Vertical => usize::from(logo.width),
+        Orientation::Horizontal => icon_height(&logo),
+    };
+    let cells = colorscheme_names()
+        .into_iter()
+        .map(|name| {
+            let flag = Flag {
+                color_scheme: flag_colors(
+                    settings,
+                    &get_colorscheme(name.as_str())?,
+                    length,
+                    quirks,
+                ),
+                orientation,
+            };
+            let mut frame = Vec::new();
+            render_lines(
+                &mut frame,
+                flag.colorize(&logo),
+                &sample,
+                logo.width,
+                text_direction(settings),
+                true,
+                palette,
+            )?;
+            Ok(std::iter::once(name.bold().to_string())
+                .chain(String::from_utf8(frame)?.lines().map(String::from))
+                .collect_vec())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let cell_width = cells
+        .iter()
+        .flatten()
+        .map(|x| visible_width(x))
+        .max()
+        .unwrap_or_default()
+        + GAP;
+    let columns = (usize::from(terminal::size().map_or(80, |(x, _)| x)) / cell_width).max(1);
+
+    let mut out = String::new();
+    for row in cells.chunks(columns) {
+        let height = row.iter().map(Vec::len).max().unwrap_or_default();
+        for idx in 0..height {
+            let mut line = String::new();
+            for cell in row {
+                let text = cell.get(idx).map_or("", String::as_str);
+                line.push_str(text);
+                line.push_str(&" ".repeat(cell_width - visible_width(text)));
+            }
+            out.push_str(line.trim_end_matches(' '));
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    stdout().lock().write_all(out.as_bytes())?;
+    Ok(())
+}
+
+/// Spell the lines in ASCII when the `transliterate` setting is on
+#[cfg(feature = "transliterate")]
+fn transliterate(settings: &Config, mut lines: Vec<(ArcStr, ArcStr)>) -> Vec<(ArcStr, ArcStr)> {
+    if !settings.transliterate.unwrap_or_default() {
+        return lines;
+    }
+    for (label, value) in &mut lines {
+        *label = ArcStr::from(deunicode::deunicode(label));
+        *value = ArcStr::from(deunicode::deunicode(value));
+    }
+    // The line under user@host has to match its new length
+    if let [(title, _), (separator, _), ..] = lines.as_mut_slice() {
+        *separator = ArcStr::from("-".repeat(title.len()));
+    }
+    lines
+}
+
+#[cfg(not(feature = "transliterate"))]
+const fn transliterate(_: &Config, lines: Vec<(ArcStr, ArcStr)>) -> Vec<(ArcStr, ArcStr)> {
+    lines
+}
+
+/// Modules from `--show`, the `modules` setting, or the preset
+fn shown_modules(settings: &Config, show: &[String], from_json: bool) -> Vec<String> {
+    if !show.is_empty() {
+        return show.to_vec();
+    }
+    // Snapshots come from another machine, so the preset for this one does not apply
+    settings.modules.clone().unwrap_or_else(|| {
+        if from_json {
+            Vec::new()
+        } else {
+            preset_modules(settings)
+        }
+    })
+}
+
+/// Modules of the configured preset, or the one detected for this machine
+fn preset_modules(settings: &Config) -> Vec<String> {
+    let preset = settings
+        .preset
+        .or_else(mirafetch::info::role)
+        .unwrap_or_default();
+    settings
+        .presets
+        .as_ref()
+        .and_then(|x| x.get(&preset))
+        .cloned()
+        .unwrap_or_else(|| preset.modules().iter().map(ToString::to_string).collect())
+}
+
+/// Keep only the lines of `modules` in their order, along with the title and color palette
+fn filter_lines(lines: Vec<(ArcStr, ArcStr)>, modules: &[String]) -> Vec<(ArcStr, ArcStr)> {
+    if modules.is_empty() {
+        return lines;
+    }
+    // The first two lines are the user@host title and its underline
+    let mut lines = lines.into_iter();
+    let title = lines.by_ref().take(2).collect::<Vec<_>>();
+    let (palette, mut shown): (Vec<_>, Vec<_>) = lines.partition(|(label, _)| label.is_empty());
+    let position = |label: &str| {
+        modules
+            .iter()
+            .position(|x| output::shown(label, std::slice::from_ref(x)))
+    };
+    shown.retain(|(label, _)| position(label).is_some());
+    shown.sort_by_cached_key(|(label, _)| position(label));
+    title.into_iter().chain(shown).chain(palette).collect()
+}
+
+/// Replace the labels of modules that have one in the `labels` setting
+///
+/// Lists keep what follows their label, so with `gpus = "Graphics"` the line `GPU 1` becomes
+/// `Graphics 1`
+fn relabel(settings: &Config, mut lines: Vec<(ArcStr, ArcStr)>) -> Vec<(ArcStr, ArcStr)> {
+    let Some(labels) = settings.labels.as_ref().filter(|x| !x.is_empty()) else {
+        return lines;
+    };
+    // Skip the user@host title and its underline
+    for (label, _) in lines.iter_mut().skip(2) {
+        let custom = output::MODULES.iter().find_map(|(name, prefix, _)| {
+            let custom = labels.get(*name)?;
+            output::shown(label, &[(*name).to_string()])
+                .then(|| format!("{custom}{}", &label[prefix.trim_end().len()..]))
+        });
+        if let Some(custom) = custom {
+            *label = ArcStr::from(custom);
+        }
+    }
+    lines
+}
+
+/// Run the values of modules with a pipeline in the `transforms` setting through it
+///
+/// This goes by the modules' own labels, so it has to come before [`relabel`]
+fn transform(settings: &Config, mut lines: Vec<(ArcStr, ArcStr)>) -> Vec<(ArcStr, ArcStr)> {
+    let Some(transforms) = settings.transforms.as_ref().filter(|x| !x.is_empty()) else {
+        return lines;
+    };
+    for (label, value) in lines.iter_mut().skip(2) {
+        let pipeline = output::MODULES.iter().find_map(|(name, ..)| {
+            let pipeline = transforms.get(*name)?;
+            output::shown(label, &[(*name).to_string()]).then_some(pipeline)
+        });
+        if let Some(pipeline) = pipeline {
+            *value = ArcStr::from(pipeline.apply(value));
+        }
+    }
+    lines
+}
+
+/// Make the values that are web addresses, such as those of the `links` module, clickable in
+/// terminals that support OSC 8 hyperlinks, which other terminals ignore
+fn hyperlink(lines: &mut [(ArcStr, ArcStr)]) {
+    for (_, value) in lines.iter_mut() {
+        if value.starts_with("https://") || value.starts_with("http://") {
+            *value = arcstr::format!("\x1b]8;;{value}\x1b\\{value}\x1b]8;;\x1b\\");
+        }
+    }
+}
+
+/// The colors of the flag `colors` for `length` rows or columns, blended into each other with
+/// the `gradient` flag style
+fn flag_colors(settings: &Config, colors: &[Color], length: usize, quirks: Quirks) -> Arc<[Color]> {
+    let colors = match settings.flag_style.unwrap_or_default() {
+        FlagStyle::Stripes => colors.to_vec(),
+        FlagStyle::Gradient => gradient(colors, length),
+    };
+    colors.into_iter().map(|x| quirks.color(x)).collect()
+}
+
+/// Color the labels with the stripes of the flag `colors`, top to bottom, for `flag_text`
+fn flag_labels(
+    settings: &Config,
+    lines: &mut [(ArcStr, ArcStr)],
+    colors: &[Color],
+    quirks: Quirks,
+) {
+    // Lines without a label are the color palette
+    let labels = lines
+        .iter_mut()
+        .filter(|(x, _)| !x.is_empty())
+        .collect_vec();
+    let rows = labels.len();
+    let flag = Flag {
+        color_scheme: flag_colors(settings, colors, rows, quirks),
+        orientation: Orientation::Horizontal,
+    };
+    for ((label, _), color) in labels.into_iter().zip(flag.length_to_colors(rows)) {
+        *label = arcstr::format!("{}", label.as_str().with(color));
+    }
+}
+
+/// Print the modules as plain text, in the palette's colors if `color` is set
+fn plain(settings: &Config, info: Info, modules: &[String], color: bool) {
+    let palette =
+        color.then(|| palette(settings, &info).adapt(&Quirks::detect(settings.terminal_quirks)));
+    print_plain(
+        &transliterate(
+            settings,
+            relabel(
+                settings,
+                transform(settings, filter_lines(info.as_vec(), modules)),
+            ),
+        ),
+        palette,
+    );
+}
+
+/// Print one `Label: value` line per field, with bold labels in the colors of `palette` if
+/// there is one
+fn print_plain(lines: &[(ArcStr, ArcStr)], palette: Option<Palette>) {
+    // Lines without a label are the color palette
+    for (label, value) in lines.iter().filter(|(x, _)| !x.is_empty()) {
+        match (value.is_empty(), palette) {
+            (true, None) => println!("{label}"),
+            (true, Some(palette)) => println!("{}", label.as_str().bold().with(palette.label)),
+            (false, None) => println!("{label}: {value}"),
+            (false, Some(palette)) => println!(
+                "{}{}{}",
+                label.as_str().bold().with(palette.label),
+                ": ".bold().with(palette.separator),
+                palette
+                    .value
+                    .map_or_else(|| value.to_string(), |x| value.as_str().with(x).to_string()),
+            ),
+        }
+    }
+}
+
+/// SVG card of the main details, under the stripes of the `scheme_name` flag if there is one
+fn badge(settings: &Config, info: &Info, theme: badge::Theme) -> Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .and_then(|x| {
+            let program = Path::new(x.split_whitespace().next()?).file_name()?;
+            Some(program.to_string_lossy().into_owned())
+        });
+    let rows = [
+        ("OS", info.os.as_ref().map(ToString::to_string)),
+        ("CPU", info.cpu.as_ref().map(ToString::to_string)),
+        ("GPU", info.gpus.first().map(ToString::to_string)),
+        ("Editor", editor),
+        ("Uptime", info.uptime.as_ref().map(ToString::to_string)),
+    ]
+    .into_iter()
+    .filter_map(|(label, value)| Some((label, value?)))
+    .collect::<Vec<_>>();
+    let title = match (&info.username, &info.hostname) {
+        (Some(user), Some(host)) => format!("{user}@{host}"),
+        (user, host) => host
+            .as_ref()
+            .or(user.as_ref())
+            .map_or_else(|| String::from("mirafetch"), ToString::to_string),
+    };
+    let stripes = get_colorscheme_from_settings(settings)?
+        .map(|colors| {
+            colors
+                .iter()
+                .filter_map(|color| match color {
+                    Color::Rgb { r, g, b } => Some((*r, *g, *b)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    Ok(badge::render(&title, &rows, &stripes, theme))
+}
+
+/// Print the raw value of one field, exiting with status 2 if it could not be collected
+fn print_field(info: &Info, field: &str, format: OutputFormat) -> Result<ExitCode> {
+    let value = output::field(info, field)?;
+    if value.is_null() {
+        return Ok(ExitCode::from(EXIT_MISSING));
+    }
+    match (format, value) {
+        (OutputFormat::Json, value) => println!("{value}"),
+        (_, serde_json::Value::String(x)) => println!("{x}"),
+        // One item per line, e.g. for `mirafetch get gpus`
+        (_, serde_json::Value::Array(x)) => {
+            for item in x {
+                match item {
+                    serde_json::Value::String(x) => println!("{x}"),
+                    x => println!("{x}"),
+                }
+            }
+        }
+        (_, value) => println!("{value}"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Show the logo and system information, followed by an update notice if one is due
+fn show(settings: &Config, info: Info, modules: &[String], target: Target) -> Result<()> {
+    let quirks = Quirks::detect(settings.terminal_quirks);
+    let flag = get_colorscheme_from_settings(settings)?;
+    let (id, source) = logo_names(settings, &info);
+    let palette = palette(settings, &info).adapt(&quirks);
+    let mut info_vec = transliterate(
+        settings,
+        relabel(
+            settings,
+            transform(settings, filter_lines(info.as_vec(), modules)),
+        ),
+    );
+    if target != (Target::Lines { color: false }) {
+        hyperlink(&mut info_vec);
+        if let Some(flag) = flag
+            .as_deref()
+            .filter(|_| settings.flag_text.unwrap_or_default())
+        {
+            flag_labels(settings, &mut info_vec, flag, quirks);
+        }
+    }
+
+    #[cfg(feature = "images")]
+    let image = settings
+        .image
+        .as_deref()
+        .filter(|_| target == Target::Screen && settings.logo_size != Some(LogoSize::None))
+        .and_then(|path| {
+            let protocol = quirks
+                .images()
+                .then(mirafetch::image::Protocol::detect)
+                .flatten()
+                .unwrap_or(mirafetch::image::Protocol::Blocks(
+                    settings.image_symbols.unwrap_or_default(),
+                ));
+            // By default the image is as tall as the info column
+            let rows = settings
+                .image_rows
+                .unwrap_or_else(|| u16::try_from(info_vec.len()).unwrap_or(u16::MAX));
+            mirafetch::image::load(path, rows, protocol, quirks)
+                .map(|x| (x.escape, x.columns, x.rows))
+                .map_err(|err| eprintln!("Could not show {}: {err}", path.display()))
+                .ok()
+        });
+    #[cfg(not(feature = "images"))]
+    let image: Option<(String, u16, u16)> = None;
+
+    // Without a logo the info is shown as if next to an empty image
+    let image = image
+        .or_else(|| (settings.logo_size == Some(LogoSize::None)).then(|| (String::new(), 0, 0)));
 
     // Show system info
-    display(colored_logo, info_vec, &logo)?;
+    if let Some((escape, columns, rows)) = image {
+        display(
+            &mut Vec::new(),
+            [escape.stylize()],
+            &info_vec,
+            columns,
+            rows.into(),
+            text_direction(settings),
+            palette,
+            target,
+        )?;
+    } else {
+        let mut logo = pick_logo(settings, &id, source.as_deref())?;
+        if let (None, Some(tint)) = (&flag, palette.logo) {
+            logo.colors.fill(tint);
+        }
+        let scheme = flag.as_deref().map(|colors| {
+            let length = match settings.orientation {
+                Some(Orientation::Vertical) => usize::from(logo.width),
+                _ => icon_height(&logo),
+            };
+            flag_colors(settings, colors, length, quirks)
+        });
+        let colored_logo = colorize_logo(settings, &scheme, &logo)?;
+        display(
+            &mut Vec::new(),
+            colored_logo,
+            &info_vec,
+            logo.width,
+            icon_height(&logo),
+            text_direction(settings),
+            palette,
+            target,
+        )?;
+    }
 
+    #[cfg(feature = "update")]
+    if let Some(interval) = settings.update_check.and_then(UpdateInterval::duration) {
+        let cache_dir = project_dirs()?.cache_dir().to_path_buf();
+        if let Some(latest) = update::check_periodically(&cache_dir, interval) {
+            let notice = update::notice(&latest);
+            if target == (Target::Lines { color: false }) {
+                println!("{notice}");
+            } else {
+                println!("{}", notice.dark_grey());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Icon ID of the system, and the DE, WM or terminal whose logo is preferred if configured
+fn logo_names(settings: &Config, info: &Info) -> (ArcStr, Option<ArcStr>) {
+    // Inside toolbox/distrobox the container's icon is used unless the host's is preferred
+    let id = match &info.host_id {
+        Some(host_id) if settings.prefer_host_icon.unwrap_or_default() => host_id.clone(),
+        _ => info.id.clone(),
+    };
+    let source = match settings.logo_source.unwrap_or_default() {
+        LogoSource::Distro => None,
+        // The DE is shown with its version, e.g. `KDE Plasma 6.1`, which no logo is named after
+        LogoSource::De => info.de.as_deref().map(|de| {
+            ArcStr::from(
+                de.rsplit_once(' ')
+                    .filter(|(_, version)| version.starts_with(|x: char| x.is_ascii_digit()))
+                    .map_or(de, |(name, _)| name),
+            )
+        }),
+        LogoSource::Wm => info.wm.clone(),
+        LogoSource::Terminal => info.terminal.clone(),
+    };
+    (id, source)
+}
+
+/// Find the logo for `source`, falling back to the one for the icon ID `id`
+fn pick_logo(settings: &Config, id: &str, source: Option<&str>) -> Result<AsciiArt> {
+    let dirs = logo_dirs(settings);
+    let verify = logo_verifier(settings)?;
+    let find = |name: &str| {
+        if settings.logo_size == Some(LogoSize::Small) {
+            // Logos without a small variant are shown as they are
+            if let Ok(logo) = find_icon(format!("{name}_small").as_str(), &dirs, &verify) {
+                return Ok(logo);
+            }
+        }
+        find_icon(name, &dirs, &verify)
+    };
+    // The distro logo is used when there is no logo for the detected DE, WM or terminal
+    let distro = || find(get_os_id(settings, id).into());
+    if let Some(path) = &settings.logo_file {
+        let colors = distro().map(|x| x.colors).unwrap_or_default();
+        return load_logo_file(path, &colors, &verify);
+    }
+    source
+        .filter(|_| settings.icon_name.is_none())
+        .map_or_else(distro, |name| find(name).or_else(|_| distro()))
+}
+
+/// Logo packs to search, `logos` in the config directory and then any from the settings
+fn logo_dirs(settings: &Config) -> Vec<PathBuf> {
+    project_dirs()
+        .map(|x| x.config_dir().join("logos"))
+        .into_iter()
+        .chain(settings.logo_dirs.iter().flatten().cloned())
+        .collect()
+}
+
+type Verifier = Box<dyn Fn(&Path, &[u8]) -> Result<()>>;
+
+/// Check logos from packs against `trusted_keys` in the config directory if configured
+#[cfg(feature = "signatures")]
+fn logo_verifier(settings: &Config) -> Result<Verifier> {
+    if !settings.verify_logos.unwrap_or_default() {
+        return Ok(Box::new(|_, _| Ok(())));
+    }
+    let keys = TrustedKeys::load(&project_dirs()?.config_dir().join("trusted_keys"))?;
+    Ok(Box::new(move |path, data| keys.verify(path, data)))
+}
+
+#[cfg(not(feature = "signatures"))]
+fn logo_verifier(settings: &Config) -> Result<Verifier> {
+    if settings.verify_logos.unwrap_or_default() {
+        return Err(anyhow!(
+            "Verifying logos needs a build of mirafetch with the signatures feature"
+        ));
+    }
+    Ok(Box::new(|_, _| Ok(())))
+}
+
+#[cfg(feature = "update")]
+fn check_update() -> Result<ExitCode> {
+    match update::check_now(project_dirs()?.cache_dir())? {
+        Some(latest) => println!("{}", update::notice(&latest)),
+        None => println!("mirafetch is up to date"),
+    }
     Ok(ExitCode::SUCCESS)
 }
 
+/// Collect system information, or just the module `only`, or read it from a snapshot
+fn collect(settings: &Config, from_json: Option<&Path>, only: Option<&str>) -> Result<Info> {
+    if let Some(path) = from_json {
+        let mut info = output::from_json(&fs::read_to_string(path)?)?;
+        // The screen reader is this machine's, not the snapshot's
+        if settings.accessible.is_none() {
+            info.screen_reader = mirafetch::info::screen_reader();
+        }
+        return Ok(info);
+    }
+    if settings.cache.unwrap_or_default() {
+        mirafetch::info::memo::enable(project_dirs()?.cache_dir().join("memo.json"));
+    }
+    let start = Instant::now();
+    let info = only.map_or_else(
+        || Info::from_config(settings),
+        |module| Info::from_config_only(settings, module),
+    );
+    if settings.stats.unwrap_or_default() && only.is_none() {
+        record_stats(start.elapsed(), &info)
+            .unwrap_or_else(|err| eprintln!("Could not record stats: {err}"));
+    }
+    Ok(info)
+}
+
+/// Whether the output or the logo was asked for on the command line, which a screen reader
+/// doesn't change, so there's no need to look for one
+const fn format_is_fixed(format: OutputFormat, args: &Args) -> bool {
+    !matches!(format, OutputFormat::Pretty) || args.output.is_some() || args.force_logo
+}
+
+/// Plain lines for screen readers in place of the logo, while one is running unless the
+/// `accessible` setting says otherwise, which it does for a [fixed](format_is_fixed) format
+fn accessible_format(format: OutputFormat, settings: &Config, info: &Info) -> OutputFormat {
+    if !matches!(format, OutputFormat::Pretty) {
+        return format;
+    }
+    let accessible = settings
+        .accessible
+        .unwrap_or_else(|| info.screen_reader.is_some());
+    if accessible {
+        OutputFormat::Plain
+    } else {
+        format
+    }
+}
+
+/// Print why each module came back empty, for `--debug`
+fn print_debug(info: &Info) {
+    for (label, err) in info.errors.iter().sorted() {
+        eprintln!("{label}: {err}");
+    }
+}
+
+fn stats_path() -> Result<PathBuf> {
+    Ok(project_dirs()?.data_local_dir().join("stats.json"))
+}
+
+fn record_stats(total: Duration, info: &Info) -> Result<()> {
+    let path = stats_path()?;
+    let mut stats = Stats::load(&path)?;
+    stats.record(total, &info.timings);
+    stats.save(&path)
+}
+
+fn project_dirs() -> Result<ProjectDirs> {
+    ProjectDirs::from("", "", "Mirafetch").ok_or_else(|| {
+        anyhow!("Could not find a project directory for Mirafetch. Please report this as a bug.")
+    })
+}
+
+/// Turn off what writes files or uses the network, then apply the seccomp filter
+#[cfg(all(feature = "seccomp", target_os = "linux"))]
+fn harden(settings: &mut Config) -> Result<()> {
+    disable_writes(settings);
+    mirafetch::sandbox::apply()
+}
+
+#[cfg(all(feature = "seccomp", target_os = "linux"))]
+const fn disable_writes(settings: &mut Config) {
+    settings.stats = None;
+    settings.cache = None;
+    settings.update_check = None;
+    // Not a write, but the sandbox refuses network access as well
+    settings.public_ip = None;
+}
+
+/// The option or command given that hardened mode would break part way through
+const fn refused_when_hardened(args: &Args) -> Option<&'static str> {
+    #[cfg(feature = "publish")]
+    if args.publish {
+        return Some("`--publish`");
+    }
+    #[cfg(all(feature = "mdns", unix))]
+    if args.advertise {
+        return Some("`--advertise`");
+    }
+    match args.command {
+        Some(Command::Configure) => Some("`mirafetch configure`"),
+        #[cfg(all(feature = "mdns", unix))]
+        Some(Command::Discover) => Some("`mirafetch discover`"),
+        _ => None,
+    }
+}
+
+#[cfg(not(all(feature = "seccomp", target_os = "linux")))]
+fn harden(_: &mut Config) -> Result<()> {
+    Err(anyhow!(
+        "Hardened mode needs a Linux build of mirafetch with the seccomp feature"
+    ))
+}
+
+/// Limit the collection thread pool and lower the process priority if configured
+fn apply_resource_limits(settings: &Config) -> Result<()> {
+    if let Some(threads) = settings.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()?;
+    }
+    if settings.low_priority.unwrap_or_default() {
+        lower_priority();
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn lower_priority() {
+    // Failing to lower the priority is harmless, so errors are ignored
+    unsafe {
+        libc::setpriority(libc::PRIO_PROCESS, 0, 10);
+        // IOPRIO_WHO_PROCESS with the idle I/O scheduling class
+        #[cfg(target_os = "linux")]
+        libc::syscall(libc::SYS_ioprio_set, 1, 0, 3 << 13);
+    }
+}
+
+#[cfg(not(unix))]
+const fn lower_priority() {}
+
 fn get_os_id<'a>(settings: &'a Config, default: impl Into<&'a str>) -> impl Into<&str> {
     settings
         .icon_name
@@ -44,36 +1384,127 @@ fn get_os_id<'a>(settings: &'a Config, default: impl Into<&'a str>) -> impl Into
         .map_or_else(|| default.into(), |name| name.as_ref())
 }
 
-fn get_colorscheme_from_settings(settings: &Config) -> Option<Arc<[Color]>> {
-    let scheme: Option<Arc<[Color]>> = settings
+/// A flag pattern of `data/flags.toml`, for `--flag`
+fn flag_name(name: &str) -> Result<String, String> {
+    let names = colorscheme_names();
+    if names.iter().any(|x| x == name) {
+        Ok(name.to_string())
+    } else {
+        Err(format!(
+            "see `mirafetch list themes` for the {} flags",
+            names.len()
+        ))
+    }
+}
+
+fn get_colorscheme_from_settings(settings: &Config) -> Result<Option<Arc<[Color]>>> {
+    settings
         .scheme_name
-        .as_ref()
-        .map_or_else(|| None, |name| Some(get_colorscheme(name.as_ref())));
-    scheme
+        .as_deref()
+        .map(get_colorscheme)
+        .transpose()
+}
+
+/// Loads the config files again whenever one is saved, for modes that keep running
+struct Reloader<'a> {
+    args: &'a Args,
+    modified: Vec<Option<SystemTime>>,
+}
+
+impl<'a> Reloader<'a> {
+    fn new(args: &'a Args) -> Self {
+        Self {
+            args,
+            modified: Self::modified(),
+        }
+    }
+
+    fn modified() -> Vec<Option<SystemTime>> {
+        config_paths()
+            .unwrap_or_default()
+            .iter()
+            .map(|path| fs::metadata(path).and_then(|x| x.modified()).ok())
+            .collect()
+    }
+
+    /// The settings from the config file if it changed since the last call, with the same
+    /// command line overrides as at the start
+    fn reload(&mut self) -> Option<Result<Config>> {
+        let modified = Self::modified();
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        // Checked again with the overrides, so a bad config keeps the previous settings rather
+        // than failing once it's used
+        Some(load_settings_file().and_then(|mut settings| {
+            self.args.override_settings(&mut settings);
+            check_settings(&settings)?;
+            Ok(settings)
+        }))
+    }
+}
+
+/// Defaults a distribution packaging mirafetch can set, below the user's config file
+#[cfg(unix)]
+const VENDOR_CONFIG: &str = "/etc/mirafetch/config.toml";
+
+fn config_path() -> Result<PathBuf> {
+    Ok(project_dirs()?.config_dir().join("config.toml"))
+}
+
+/// Config files from the lowest precedence to the highest: the one a distribution ships, then
+/// the user's own
+fn config_paths() -> Result<Vec<PathBuf>> {
+    Ok(vec![
+        #[cfg(unix)]
+        PathBuf::from(VENDOR_CONFIG),
+        config_path()?,
+    ])
 }
 
 fn load_settings_file() -> Result<Config, anyhow::Error> {
-    let proj_dir = ProjectDirs::from("", "", "Mirafetch");
-    let settings = proj_dir
-        .ok_or_else(|| {
-            anyhow!(
-                "Could not find a project directory for Mirafetch. Please report this as a bug.",
-            )
-        })
-        .and_then(|dir| {
-            let config_path = dir.config_dir().join("config.toml");
-            if !config_path.exists() {
-                return anyhow::Ok(Config::default());
-            };
-            let config_file = fs::read_to_string(config_path)?;
-            toml::from_str::<Config>(&config_file).map_err(|err| {
-                eprintln!("Invalid config: {err}");
-                anyhow!(exitcode::CONFIG)
-            })
-        })?;
+    let mut settings = toml::Table::new();
+    for path in config_paths()? {
+        if !path.exists() {
+            continue;
+        }
+        let config_file = fs::read_to_string(&path)?;
+        // Checked on its own first, so errors point at the line in the file they're in
+        toml::from_str::<Config>(&config_file)
+            .map_err(|err| anyhow!("Invalid config in {}: {err}", path.display()))?;
+        merge_settings(&mut settings, toml::from_str(&config_file)?);
+    }
+    let settings: Config = toml::Value::Table(settings)
+        .try_into()
+        .map_err(|err| anyhow!("Invalid config: {err}"))?;
+    check_settings(&settings)?;
     Ok(settings)
 }
 
+/// Reject settings that parse but would fail once used, like an unknown `scheme_name`
+fn check_settings(settings: &Config) -> Result<()> {
+    if let Some(name) = &settings.scheme_name {
+        flag_name(name)
+            .map_err(|err| anyhow!("Invalid config: unknown scheme_name `{name}`, {err}"))?;
+    }
+    Ok(())
+}
+
+/// Lay the settings of `layer` over `base`, merging tables such as `labels` key by key
+fn merge_settings(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(layer)) => {
+                merge_settings(base, layer);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
 fn colorize_logo(
     settings: &Config,
     scheme: &Option<Arc<[Color]>>,
@@ -100,34 +1531,76 @@ fn colorize_logo(
 
 /// Display the formatted logo and system information
 ///
+/// The whole frame is queued into `frame` and written to stdout at once
+///
 /// # Errors
 ///
-/// This function will return an error if the terminal settings (eg color, cursor position) cannot be modified
+/// This function will return an error if the frame cannot be written to stdout
+#[allow(clippy::too_many_arguments)]
 fn display(
+    frame: &mut Vec<u8>,
     icon: impl IntoIterator<Item = crossterm::style::StyledContent<impl Display>>,
-    info: impl IntoIterator<Item = (ArcStr, ArcStr)>,
-    logo: &AsciiArt,
+    info: &[(ArcStr, ArcStr)],
+    icon_width: u16,
+    icon_height: usize,
+    direction: Direction,
+    palette: Palette,
+    target: Target,
 ) -> Result<(), anyhow::Error> {
-    stdout().execute(Clear(All))?.execute(MoveTo(0, 0))?;
-    for line in icon {
-        stdout() /* .execute(ResetColor)?*/
-            .execute(PrintStyledContent(line))?;
-    }
-    let pos = position()?;
-    stdout().execute(MoveTo(0, 0))?;
-    for line in info {
-        let (x, y) = line;
-        stdout()
-            .execute(MoveToColumn(logo.width + 3))?
-            .execute(PrintStyledContent(x.clone().bold().red()))?;
-        if !x.is_empty() && !y.is_empty() {
-            stdout().execute(PrintStyledContent(": ".bold().red()))?;
-        }
-        stdout()
-            .execute(PrintStyledContent(y.reset()))?
-            .execute(MoveToNextLine(1))?;
-    }
-    let (_x, y) = position()?;
-    stdout().execute(MoveTo(0, max(y, pos.1) + 1))?;
+    match target {
+        Target::Screen => render(
+            frame,
+            icon,
+            info,
+            icon_width,
+            icon_height,
+            direction,
+            palette,
+        )?,
+        Target::Lines { color } => {
+            render_lines(frame, icon, info, icon_width, direction, color, palette)?;
+        }
+    }
+    let mut out = stdout().lock();
+    out.write_all(frame)?;
+    out.flush()?;
     Ok(())
 }
+
+/// Colors of the output from the `colors`, `palette` and `accent` settings, in that order
+fn palette(settings: &Config, info: &Info) -> Palette {
+    let palette = settings.palette.map_or_else(
+        || {
+            Palette::accent(
+                settings
+                    .accent
+                    .unwrap_or_default()
+                    .color(info.hostname.as_deref()),
+            )
+        },
+        Palette::named,
+    );
+    settings
+        .colors
+        .as_ref()
+        .map_or(palette, |colors| palette.with_colors(colors))
+}
+
+/// Direction from the settings, or right-to-left when the locale's language is written that way
+fn text_direction(settings: &Config) -> Direction {
+    const RTL_LANGUAGES: &[&str] = &[
+        "ar", "ckb", "dv", "fa", "he", "ks", "ps", "sd", "ug", "ur", "yi",
+    ];
+    settings.direction.unwrap_or_else(|| {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|x| std::env::var(x).ok().filter(|x| !x.is_empty()))
+            .unwrap_or_default();
+        let language = locale.split(['_', '.', '@']).next().unwrap_or_default();
+        if RTL_LANGUAGES.contains(&language) {
+            Direction::Rtl
+        } else {
+            Direction::Ltr
+        }
+    })
+}
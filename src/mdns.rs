@@ -0,0 +1,367 @@
+#![cfg(unix)]
+//! Just enough multicast DNS to announce a machine's summary and find the others on the LAN
+//!
+//! Each machine answers queries for [`SERVICE`] with a PTR record to its instance and a TXT
+//! record of `key=value` pairs, and nothing else, so the instances show up in other mDNS
+//! browsers but can't be resolved to an address through them
+use std::{
+    io::{self, ErrorKind},
+    mem,
+    net::{Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    os::fd::{AsRawFd, FromRawFd},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+
+use crate::{info::Info, util::bytecount_format};
+
+/// Service type of mirafetch instances
+pub const SERVICE: &str = "_mirafetch._udp.local";
+pub const GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+pub const PORT: u16 = 5353;
+const PTR: u16 = 12;
+const TXT: u16 = 16;
+const ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+/// Tells caches to replace what they have for a name, for records only one machine owns
+const CACHE_FLUSH: u16 = 0x8000;
+const TTL: u32 = 120;
+
+/// Answer queries for [`SERVICE`] until an error, with the TXT pairs from `txt`
+///
+/// The instance is announced once at the start. `txt` is called for every answer, so it should
+/// cache anything slow to collect
+///
+/// # Errors
+///
+/// This function will return an error if the mDNS port can't be bound or a packet can't be
+/// sent or received
+pub fn advertise(instance: &str, mut txt: impl FnMut() -> Vec<(String, String)>) -> Result<()> {
+    let socket = multicast_socket()?;
+    let instance = format!("{}.{SERVICE}", instance.replace('.', "-"));
+    socket.send_to(&response(0, &instance, &txt()), (GROUP, PORT))?;
+    let mut buf = [0; 9000];
+    loop {
+        let (len, from) = socket.recv_from(&mut buf)?;
+        let Some(query) = Message::parse(&buf[..len]) else {
+            continue;
+        };
+        let asked = query.questions.iter().any(|(name, kind)| {
+            matches!(*kind, PTR | TXT | ANY)
+                && (name.eq_ignore_ascii_case(SERVICE) || name.eq_ignore_ascii_case(&instance))
+        });
+        if query.response || !asked {
+            continue;
+        }
+        // Queries from other ports come from simple resolvers that expect a unicast reply
+        let (id, to) = if from.port() == PORT {
+            (0, SocketAddr::from((GROUP, PORT)))
+        } else {
+            (query.id, from)
+        };
+        socket.send_to(&response(id, &instance, &txt()), to)?;
+    }
+}
+
+/// The TXT pairs a machine is announced with: its host name, OS, uptime, load and the space
+/// left on its root filesystem
+#[must_use]
+pub fn summary(info: Info) -> Vec<(String, String)> {
+    [
+        ("host", info.hostname.map(|x| x.to_string())),
+        ("os", info.os.map(|x| x.to_string())),
+        ("uptime", info.uptime.map(|x| x.to_string())),
+        ("load", load_average()),
+        ("disk_free", root_headroom()),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| Some((key.to_string(), value?)))
+    .collect()
+}
+
+/// Load average over the last minute
+fn load_average() -> Option<String> {
+    let mut load = [0.0; 3];
+    (unsafe { libc::getloadavg(load.as_mut_ptr(), 1) } == 1).then(|| format!("{:.2}", load[0]))
+}
+
+/// Space left on the root filesystem, e.g. `64.0 GiB (25%)`
+// The block counts are narrower on some platforms
+#[allow(clippy::useless_conversion)]
+fn root_headroom() -> Option<String> {
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    if unsafe { libc::statvfs(c"/".as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let stat = unsafe { stat.assume_init() };
+    let block = u64::from(stat.f_frsize);
+    let total = u64::from(stat.f_blocks) * block;
+    let free = u64::from(stat.f_bavail) * block;
+    (total > 0).then(|| format!("{} ({}%)", bytecount_format(free, 1), free * 100 / total))
+}
+
+/// Ask for [`SERVICE`] instances and collect the TXT pairs of each that answers in `timeout`
+///
+/// The query goes to the mDNS group, and straight to each of `peers`, given as a host name or
+/// address with an optional port, for machines on networks that multicast doesn't reach
+///
+/// # Errors
+///
+/// This function will return an error if the query can't be sent or a reply can't be received
+pub fn discover(timeout: Duration, peers: &[String]) -> Result<Vec<Vec<(String, String)>>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_multicast_ttl_v4(255)?;
+    socket.send_to(&query(SERVICE), (GROUP, PORT))?;
+    for peer in peers {
+        let addr = if peer.contains(':') {
+            peer.to_socket_addrs()
+        } else {
+            (peer.as_str(), PORT).to_socket_addrs()
+        };
+        match addr.map(|mut x| x.find(SocketAddr::is_ipv4)) {
+            Ok(Some(addr)) => {
+                socket.send_to(&query(SERVICE), addr)?;
+            }
+            _ => eprintln!("Could not resolve {peer}"),
+        }
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut found: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    let mut buf = [0; 9000];
+    while let Some(left) = deadline
+        .checked_duration_since(Instant::now())
+        .filter(|x| !x.is_zero())
+    {
+        socket.set_read_timeout(Some(left))?;
+        let len = match socket.recv(&mut buf) {
+            Ok(len) => len,
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break,
+            Err(err) => return Err(err.into()),
+        };
+        for record in Message::parse(&buf[..len])
+            .map(|x| x.records)
+            .unwrap_or_default()
+        {
+            if let Data::Txt(pairs) = record.data {
+                let ours = record.name.to_ascii_lowercase().ends_with(SERVICE);
+                if ours && !found.iter().any(|(name, _)| *name == record.name) {
+                    found.push((record.name, pairs));
+                }
+            }
+        }
+    }
+    Ok(found.into_iter().map(|(_, pairs)| pairs).collect())
+}
+
+/// Socket on the mDNS port in the mDNS group, shared with any other responder on the machine
+fn multicast_socket() -> Result<UdpSocket> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    // Closes the descriptor if anything below fails
+    let socket = unsafe { UdpSocket::from_raw_fd(fd) };
+    let one: libc::c_int = 1;
+    for option in [libc::SO_REUSEADDR, libc::SO_REUSEPORT] {
+        let res = unsafe {
+            libc::setsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                option,
+                std::ptr::addr_of!(one).cast(),
+                libc::socklen_t::try_from(mem::size_of::<libc::c_int>())?,
+            )
+        };
+        if res != 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+    }
+    let mut addr = unsafe { mem::zeroed::<libc::sockaddr_in>() };
+    addr.sin_family = libc::sa_family_t::try_from(libc::AF_INET)?;
+    addr.sin_port = PORT.to_be();
+    let res = unsafe {
+        libc::bind(
+            socket.as_raw_fd(),
+            std::ptr::addr_of!(addr).cast(),
+            libc::socklen_t::try_from(mem::size_of::<libc::sockaddr_in>())?,
+        )
+    };
+    if res != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    socket.join_multicast_v4(&GROUP, &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_multicast_ttl_v4(255)?;
+    Ok(socket)
+}
+
+fn write_name(buf: &mut Vec<u8>, name: &str) {
+    for label in name.split('.').filter(|x| !x.is_empty()) {
+        let label = &label.as_bytes()[..label.len().min(63)];
+        buf.push(u8::try_from(label.len()).unwrap_or(63));
+        buf.extend_from_slice(label);
+    }
+    buf.push(0);
+}
+
+fn write_record(buf: &mut Vec<u8>, name: &str, kind: u16, class: u16, data: &[u8]) {
+    write_name(buf, name);
+    buf.extend_from_slice(&kind.to_be_bytes());
+    buf.extend_from_slice(&class.to_be_bytes());
+    buf.extend_from_slice(&TTL.to_be_bytes());
+    buf.extend_from_slice(&u16::try_from(data.len()).unwrap_or_default().to_be_bytes());
+    buf.extend_from_slice(data);
+}
+
+fn header(id: u16, flags: u16, questions: u16, answers: u16) -> Vec<u8> {
+    [id, flags, questions, answers, 0, 0]
+        .iter()
+        .flat_map(|x| x.to_be_bytes())
+        .collect()
+}
+
+fn query(service: &str) -> Vec<u8> {
+    let mut buf = header(0, 0, 1, 0);
+    write_name(&mut buf, service);
+    buf.extend_from_slice(&PTR.to_be_bytes());
+    buf.extend_from_slice(&CLASS_IN.to_be_bytes());
+    buf
+}
+
+/// PTR and TXT records for `instance`, each TXT string cut to the 255 bytes allowed
+fn response(id: u16, instance: &str, txt: &[(String, String)]) -> Vec<u8> {
+    // An authoritative answer
+    let mut buf = header(id, 0x8400, 0, 2);
+    let mut ptr = Vec::new();
+    write_name(&mut ptr, instance);
+    write_record(&mut buf, SERVICE, PTR, CLASS_IN, &ptr);
+    let mut data = Vec::new();
+    for (key, value) in txt {
+        let pair = format!("{key}={value}");
+        let mut end = pair.len().min(255);
+        while !pair.is_char_boundary(end) {
+            end -= 1;
+        }
+        data.push(u8::try_from(end).unwrap_or(255));
+        data.extend_from_slice(&pair.as_bytes()[..end]);
+    }
+    write_record(&mut buf, instance, TXT, CLASS_IN | CACHE_FLUSH, &data);
+    buf
+}
+
+enum Data {
+    Txt(Vec<(String, String)>),
+    Other,
+}
+
+struct Record {
+    name: String,
+    data: Data,
+}
+
+struct Message {
+    id: u16,
+    response: bool,
+    questions: Vec<(String, u16)>,
+    records: Vec<Record>,
+}
+
+impl Message {
+    /// Parse a DNS message, or `None` if it's cut short or malformed
+    fn parse(packet: &[u8]) -> Option<Self> {
+        let mut reader = Reader { packet, pos: 0 };
+        let id = reader.u16()?;
+        let flags = reader.u16()?;
+        let questions = reader.u16()?;
+        let counts = [reader.u16()?, reader.u16()?, reader.u16()?];
+        let questions = (0..questions)
+            .map(|_| {
+                let name = reader.name()?;
+                let kind = reader.u16()?;
+                reader.u16()?;
+                Some((name, kind))
+            })
+            .collect::<Option<_>>()?;
+        let records = (0..counts.iter().map(|x| u32::from(*x)).sum::<u32>())
+            .map(|_| {
+                let name = reader.name()?;
+                let kind = reader.u16()?;
+                reader.u16()?;
+                reader.u16()?;
+                reader.u16()?;
+                let len = usize::from(reader.u16()?);
+                let data = packet.get(reader.pos..reader.pos + len)?;
+                reader.pos += len;
+                let data = if kind == TXT {
+                    txt_pairs(data)
+                } else {
+                    Data::Other
+                };
+                Some(Record { name, data })
+            })
+            .collect::<Option<_>>()?;
+        Some(Self {
+            id,
+            response: flags & 0x8000 != 0,
+            questions,
+            records,
+        })
+    }
+}
+
+fn txt_pairs(mut data: &[u8]) -> Data {
+    let mut pairs = Vec::new();
+    while let Some((&len, rest)) = data.split_first() {
+        let len = usize::from(len).min(rest.len());
+        let text = String::from_utf8_lossy(&rest[..len]);
+        if let Some((key, value)) = text.split_once('=') {
+            pairs.push((key.to_string(), value.to_string()));
+        }
+        data = &rest[len..];
+    }
+    Data::Txt(pairs)
+}
+
+struct Reader<'a> {
+    packet: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn u16(&mut self) -> Option<u16> {
+        let bytes = self.packet.get(self.pos..self.pos + 2)?;
+        self.pos += 2;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// A name, following compression pointers back into the packet
+    fn name(&mut self) -> Option<String> {
+        let mut labels = Vec::new();
+        let mut pos = self.pos;
+        let mut jumped = false;
+        // Pointers can only point backwards, but a bad packet could still loop
+        for _ in 0..128 {
+            let len = *self.packet.get(pos)?;
+            if len & 0xc0 == 0xc0 {
+                let target =
+                    usize::from(u16::from_be_bytes([len & 0x3f, *self.packet.get(pos + 1)?]));
+                if !jumped {
+                    self.pos = pos + 2;
+                    jumped = true;
+                }
+                pos = target;
+            } else if len == 0 {
+                if !jumped {
+                    self.pos = pos + 1;
+                }
+                return Some(labels.join("."));
+            } else {
+                let label = self.packet.get(pos + 1..pos + 1 + usize::from(len))?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                pos += 1 + usize::from(len);
+            }
+        }
+        None
+    }
+}
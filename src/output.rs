@@ -0,0 +1,875 @@
+use arcstr::ArcStr;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::info::{
+    values::{Cpu, DiskUsage, Memory},
+    Info,
+};
+
+/// Version of the JSON output
+///
+/// Adding a field keeps the version, consumers are expected to ignore fields they don't know.
+/// Removing or renaming a field, or changing its type, bumps it
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema describing the output of `--output json`
+pub const SCHEMA: &str = include_str!("../data/schema.json");
+
+/// Modules that can be picked with `--show`, with the label of their lines in the text output
+///
+/// Modules that can have several lines, like `gpus`, match every label starting with theirs, and
+/// their label without the trailing space, which is used for their error and when there are no
+/// details to add
+pub const MODULES: &[(&str, &str, bool)] = &[
+    ("os", "OS", false),
+    ("host_os", "Host OS", false),
+    ("host", "Host", false),
+    ("kernel", "Kernel", false),
+    ("architecture", "Architecture", false),
+    ("uptime", "Uptime", false),
+    ("time_sync", "Time Sync", false),
+    ("packages", "Packages", false),
+    ("shell", "Shell", false),
+    ("shell_framework", "Shell Framework", false),
+    ("xdg", "XDG", false),
+    ("wm", "WM", false),
+    ("de", "DE", false),
+    ("cpu", "CPU", false),
+    ("theme", "Theme", false),
+    ("system_font", "System Font", false),
+    ("cursor", "Cursor", false),
+    ("terminal", "Terminal", false),
+    ("terminal_font", "Terminal Font", false),
+    ("memory", "Memory", false),
+    ("swap", "Swap", false),
+    ("pressure", "Pressure", false),
+    ("top", "Top", false),
+    ("top_gpu", "Top GPU", false),
+    ("pipewire", "PipeWire", false),
+    ("realtime", "Realtime", false),
+    ("accessibility", "Accessibility", false),
+    ("battery", "Battery", false),
+    ("power", "Power", false),
+    ("brightness", "Brightness", false),
+    ("locale", "Locale", false),
+    ("icon_theme", "Icon Theme", false),
+    ("nix", "Nix", false),
+    ("mirafetch", "Mirafetch", false),
+    ("displays", "Display ", true),
+    ("gpus", "GPU ", true),
+    ("disks", "Disk ", true),
+    ("ip", "IP ", true),
+    ("public_ip", "Public IP", false),
+    ("links", "Link ", true),
+];
+
+/// Whether a line of the text output with `label` belongs to one of `modules`
+#[must_use]
+pub fn shown(label: &str, modules: &[String]) -> bool {
+    MODULES
+        .iter()
+        .filter(|(name, ..)| modules.iter().any(|x| x == name))
+        .any(|(_, prefix, list)| {
+            label == *prefix || (*list && (label.starts_with(prefix) || label == prefix.trim_end()))
+        })
+}
+
+/// Names of the top level fields of the JSON output, sorted alphabetically
+#[must_use]
+pub fn fields() -> Vec<String> {
+    serde_json::from_str::<Value>(SCHEMA)
+        .ok()
+        .and_then(|x| Some(x.get("properties")?.as_object()?.keys().cloned().collect()))
+        .unwrap_or_default()
+}
+
+/// System information as written by `--output json`
+///
+/// Fields are serialized in declaration order, which follows the order of the text output
+#[derive(Serialize)]
+pub struct Document<'a> {
+    pub schema: u32,
+    pub username: Option<&'a str>,
+    pub hostname: Option<&'a str>,
+    pub os: Option<&'a str>,
+    pub host_os: Option<&'a str>,
+    pub host: Option<&'a str>,
+    pub kernel: Option<&'a str>,
+    pub architecture: Option<&'a str>,
+    pub uptime: Option<&'a str>,
+    pub time_sync: Option<&'a str>,
+    pub packages: Option<&'a str>,
+    pub shell: Option<&'a str>,
+    pub shell_framework: Option<&'a str>,
+    pub xdg: Option<&'a str>,
+    pub wm: Option<&'a str>,
+    pub de: Option<&'a str>,
+    pub cpu: Option<String>,
+    pub cpu_details: Option<&'a Cpu>,
+    pub theme: Option<&'a str>,
+    pub system_font: Option<&'a str>,
+    pub cursor: Option<&'a str>,
+    pub terminal: Option<&'a str>,
+    pub terminal_font: Option<&'a str>,
+    pub memory: Option<String>,
+    pub memory_usage: Option<&'a Memory>,
+    pub swap: Option<String>,
+    pub swap_usage: Option<&'a Memory>,
+    pub pressure: Option<&'a str>,
+    pub top: Option<&'a str>,
+    pub top_gpu: Option<&'a str>,
+    pub pipewire: Option<&'a str>,
+    pub realtime: Option<&'a str>,
+    pub accessibility: Option<&'a str>,
+    pub battery: Option<&'a str>,
+    pub power: Option<&'a str>,
+    pub brightness: Option<&'a str>,
+    pub locale: Option<&'a str>,
+    pub icon_theme: Option<&'a str>,
+    pub nix: Option<&'a str>,
+    pub mirafetch: Option<&'a str>,
+    pub displays: Vec<&'a str>,
+    pub gpus: Vec<&'a str>,
+    pub disks: Vec<Disk<'a>>,
+    pub disk_usage: Vec<DiskBytes<'a>>,
+    pub ip: Vec<&'a str>,
+    pub addresses: Vec<Address<'a>>,
+    pub public_ip: Option<&'a str>,
+    pub links: Vec<Link<'a>>,
+    pub id: &'a str,
+    pub host_id: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+pub struct Disk<'a> {
+    pub name: &'a str,
+    pub usage: String,
+}
+
+#[derive(Serialize)]
+pub struct DiskBytes<'a> {
+    pub name: &'a str,
+    #[serde(flatten)]
+    pub usage: &'a DiskUsage,
+}
+
+#[derive(Serialize)]
+pub struct Address<'a> {
+    pub interface: &'a str,
+    pub address: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct Link<'a> {
+    pub name: &'a str,
+    pub url: &'a str,
+}
+
+impl<'a> From<&'a Info> for Document<'a> {
+    fn from(info: &'a Info) -> Self {
+        let list = |x: &'a [ArcStr]| x.iter().map(ArcStr::as_str).collect();
+        Self {
+            schema: SCHEMA_VERSION,
+            username: info.username.as_deref(),
+            hostname: info.hostname.as_deref(),
+            os: info.os.as_deref(),
+            host_os: info.host_os.as_deref(),
+            host: info.machine.as_deref(),
+            kernel: info.kernel.as_deref(),
+            architecture: info.arch.as_deref(),
+            uptime: info.uptime.as_deref(),
+            time_sync: info.time_sync.as_deref(),
+            packages: info.packages.as_deref(),
+            shell: info.shell.as_deref(),
+            shell_framework: info.shell_framework.as_deref(),
+            xdg: info.xdg.as_deref(),
+            wm: info.wm.as_deref(),
+            de: info.de.as_deref(),
+            cpu: info.cpu.as_ref().map(ToString::to_string),
+            cpu_details: info.cpu.as_ref(),
+            theme: info.theme.as_deref(),
+            system_font: info.font.as_deref(),
+            cursor: info.cursor.as_deref(),
+            terminal: info.terminal.as_deref(),
+            terminal_font: info.terminal_font.as_deref(),
+            memory: info.memory.as_ref().map(ToString::to_string),
+            memory_usage: info.memory.as_ref(),
+            swap: info.swap.as_ref().map(ToString::to_string),
+            swap_usage: info.swap.as_ref(),
+            pressure: info.pressure.as_deref(),
+            top: info.top.as_deref(),
+            top_gpu: info.top_gpu.as_deref(),
+            pipewire: info.pipewire.as_deref(),
+            realtime: info.realtime.as_deref(),
+            accessibility: info.accessibility.as_deref(),
+            battery: info.battery.as_deref(),
+            power: info.power.as_deref(),
+            brightness: info.brightness.as_deref(),
+            locale: info.locale.as_deref(),
+            icon_theme: info.icons.as_deref(),
+            nix: info.nix.as_deref(),
+            mirafetch: info.mirafetch.as_deref(),
+            displays: list(&info.resolution),
+            gpus: list(&info.gpus),
+            disks: info
+                .disks
+                .iter()
+                .map(|(name, usage)| Disk {
+                    name,
+                    usage: usage.to_string(),
+                })
+                .collect(),
+            disk_usage: info
+                .disks
+                .iter()
+                .map(|(name, usage)| DiskBytes { name, usage })
+                .collect(),
+            ip: info
+                .ip
+                .iter()
+                .map(|(_, address)| address.as_str())
+                .collect(),
+            addresses: info
+                .ip
+                .iter()
+                .map(|(interface, address)| Address { interface, address })
+                .collect(),
+            public_ip: info.public_ip.as_deref(),
+            links: info
+                .links
+                .iter()
+                .map(|(name, url)| Link { name, url })
+                .collect(),
+            id: &info.id,
+            host_id: info.host_id.as_deref(),
+        }
+    }
+}
+
+/// Which of `fields` could not be collected, a list counts as missing when it is empty
+///
+/// # Errors
+///
+/// This function will return an error if a field is not part of the JSON output
+pub fn missing_fields<'a>(info: &Info, fields: &'a [String]) -> anyhow::Result<Vec<&'a str>> {
+    let document = serde_json::to_value(Document::from(info))?;
+    let mut missing = Vec::new();
+    for field in fields {
+        match lookup(&document, field)? {
+            Value::Null => missing.push(field.as_str()),
+            Value::Array(x) if x.is_empty() => missing.push(field.as_str()),
+            _ => {}
+        }
+    }
+    Ok(missing)
+}
+
+/// Look up a single field of the JSON output by a dotted path, e.g. `cpu` or `disks.0.usage`
+///
+/// An index past the end of a list gives `null`
+///
+/// # Errors
+///
+/// This function will return an error if the path does not name a field of the JSON output
+pub fn field(info: &Info, path: &str) -> anyhow::Result<Value> {
+    lookup(&serde_json::to_value(Document::from(info))?, path)
+}
+
+/// The field of `value` at the dotted `path`
+fn lookup(value: &Value, path: &str) -> anyhow::Result<Value> {
+    let mut value = value;
+    for key in path.split('.') {
+        value = match value {
+            Value::Object(x) => x.get(key),
+            // An index past the end gives `null`
+            Value::Array(x) => key
+                .parse::<usize>()
+                .ok()
+                .map(|idx| x.get(idx).unwrap_or(&Value::Null)),
+            // Nothing below a module that found nothing, e.g. `swap_usage.used` without swap
+            Value::Null => Some(&Value::Null),
+            _ => None,
+        }
+        .ok_or_else(|| anyhow::anyhow!("Unknown field: {path}"))?;
+    }
+    Ok(value.clone())
+}
+
+/// Label of the module that collects the top level of `path` in the JSON output, or nothing for
+/// the fields that are always collected
+#[must_use]
+pub fn module(path: &str) -> &'static str {
+    match path.split('.').next().unwrap_or_default() {
+        "os" => "OS",
+        "host_os" => "Host OS",
+        "host" => "Host",
+        "kernel" => "Kernel",
+        "architecture" => "Architecture",
+        "uptime" => "Uptime",
+        "time_sync" => "Time Sync",
+        "packages" => "Packages",
+        "shell" => "Shell",
+        "shell_framework" => "Shell Framework",
+        "xdg" => "XDG",
+        "wm" => "WM",
+        "de" => "DE",
+        "cpu" | "cpu_details" => "CPU",
+        "theme" => "Theme",
+        "system_font" => "System Font",
+        "cursor" => "Cursor",
+        "terminal" => "Terminal",
+        "terminal_font" => "Terminal Font",
+        "memory" | "memory_usage" => "Memory",
+        "swap" | "swap_usage" => "Swap",
+        "pressure" => "Pressure",
+        "top" => "Top",
+        "top_gpu" => "Top GPU",
+        "pipewire" => "PipeWire",
+        "realtime" => "Realtime",
+        "accessibility" => "Accessibility",
+        "battery" => "Battery",
+        "power" => "Power",
+        "brightness" => "Brightness",
+        "locale" => "Locale",
+        "icon_theme" => "Icon Theme",
+        "nix" => "Nix",
+        "displays" => "Display",
+        "gpus" => "GPU",
+        "disks" | "disk_usage" => "Disk",
+        "ip" | "addresses" => "IP",
+        "public_ip" => "Public IP",
+        "links" => "Link",
+        _ => "",
+    }
+}
+
+/// Read back a snapshot saved with `--output json`
+///
+/// Fields missing from the snapshot are left empty and unknown fields are ignored
+///
+/// # Errors
+///
+/// This function will return an error if `json` is not a JSON object of the current schema version
+#[allow(clippy::too_many_lines)]
+pub fn from_json(json: &str) -> anyhow::Result<Info> {
+    let document: Value = serde_json::from_str(json)?;
+    match document.get("schema").and_then(Value::as_u64) {
+        Some(x) if x == u64::from(SCHEMA_VERSION) => {}
+        x => anyhow::bail!(
+            "Snapshot has schema {}, expected {SCHEMA_VERSION}",
+            x.map_or_else(|| String::from("none"), |x| x.to_string())
+        ),
+    }
+    let text = |key: &str| document.get(key).and_then(Value::as_str).map(ArcStr::from);
+    let list = |key: &str| -> Vec<&Value> {
+        document
+            .get(key)
+            .and_then(Value::as_array)
+            .map(|x| x.iter().collect())
+            .unwrap_or_default()
+    };
+    let strings = |key: &str| -> Vec<ArcStr> {
+        list(key)
+            .into_iter()
+            .filter_map(Value::as_str)
+            .map(ArcStr::from)
+            .collect()
+    };
+    Ok(Info {
+        os: text("os"),
+        machine: text("host"),
+        kernel: text("kernel"),
+        uptime: text("uptime"),
+        time_sync: text("time_sync"),
+        packages: text("packages"),
+        username: text("username"),
+        hostname: text("hostname"),
+        resolution: strings("displays"),
+        wm: text("wm"),
+        de: text("de"),
+        shell: text("shell"),
+        shell_framework: text("shell_framework"),
+        xdg: text("xdg"),
+        cpu: document
+            .get("cpu_details")
+            .and_then(|x| Cpu::deserialize(x).ok())
+            .or_else(|| Some(cpu_from_text(document.get("cpu")?.as_str()?))),
+        font: text("system_font"),
+        cursor: text("cursor"),
+        terminal: text("terminal"),
+        terminal_font: text("terminal_font"),
+        gpus: strings("gpus"),
+        memory: usage(&document, "memory_usage", "memory"),
+        swap: usage(&document, "swap_usage", "swap"),
+        pressure: text("pressure"),
+        top: text("top"),
+        top_gpu: text("top_gpu"),
+        pipewire: text("pipewire"),
+        realtime: text("realtime"),
+        accessibility: text("accessibility"),
+        screen_reader: None,
+        disks: list("disks")
+            .into_iter()
+            .filter_map(|x| {
+                let name = x.get("name")?.as_str()?;
+                Some((ArcStr::from(name), disk_usage(&document, name, x)?))
+            })
+            .collect(),
+        battery: text("battery"),
+        locale: text("locale"),
+        theme: text("theme"),
+        icons: text("icon_theme"),
+        public_ip: text("public_ip"),
+        // Snapshots from before addresses had their interface only have `ip`
+        ip: if document.get("addresses").is_some() {
+            list("addresses")
+                .into_iter()
+                .filter_map(|x| {
+                    Some((
+                        ArcStr::from(x.get("interface")?.as_str()?),
+                        ArcStr::from(x.get("address")?.as_str()?),
+                    ))
+                })
+                .collect()
+        } else {
+            strings("ip")
+                .into_iter()
+                .map(|x| (ArcStr::new(), x))
+                .collect()
+        },
+        links: list("links")
+            .into_iter()
+            .filter_map(|x| {
+                Some((
+                    ArcStr::from(x.get("name")?.as_str()?),
+                    ArcStr::from(x.get("url")?.as_str()?),
+                ))
+            })
+            .collect(),
+        nix: text("nix"),
+        brightness: text("brightness"),
+        power: text("power"),
+        arch: text("architecture"),
+        mirafetch: text("mirafetch"),
+        host_os: text("host_os"),
+        id: text("id").unwrap_or_default(),
+        host_id: text("host_id"),
+        errors: FxHashMap::default(),
+        timings: FxHashMap::default(),
+    })
+}
+
+/// Serialize `info` as a pretty printed JSON document
+///
+/// # Errors
+///
+/// This function will return an error if serialization fails
+pub fn to_json(info: &Info) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&Document::from(info))
+}
+
+/// Serialize `info` as YAML, with the same fields as the JSON document
+///
+/// # Errors
+///
+/// This function will return an error if serialization fails
+pub fn to_yaml(info: &Info) -> serde_yaml::Result<String> {
+    serde_yaml::to_string(&Document::from(info))
+}
+
+/// Serialize `info` as TOML, with the same fields as the JSON document
+///
+/// TOML has no null, so fields that weren't collected are left out
+///
+/// # Errors
+///
+/// This function will return an error if serialization fails
+pub fn to_toml(info: &Info) -> Result<String, toml::ser::Error> {
+    toml::to_string(&Document::from(info))
+}
+
+/// Serialize `info` as CSV, with a header row of `columns` and a row of their values
+///
+/// Columns are named as in the JSON output and can be dotted paths like `disks.0.usage`. Without
+/// any, every field is a column in the order of the JSON document, so every machine gets the same
+/// columns whatever could be collected
+///
+/// # Errors
+///
+/// This function will return an error if a column is not part of the JSON output
+pub fn to_csv(info: &Info, columns: &[String]) -> anyhow::Result<String> {
+    if columns.is_empty() {
+        csv(&Document::from(info), &document_fields())
+    } else {
+        csv(&Document::from(info), columns)
+    }
+}
+
+/// Names of the top level fields of the JSON output, in the order of the document
+#[must_use]
+pub fn document_fields() -> Vec<String> {
+    serde_json::from_str::<Value>(SCHEMA)
+        .ok()
+        .and_then(|x| {
+            let required = x.get("required")?.as_array()?;
+            Some(
+                required
+                    .iter()
+                    .filter_map(|x| Some(x.as_str()?.to_string()))
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// A header row of `columns` and a row of their values in `value`, so the output of several
+/// machines can be put together by leaving out all but the first header
+///
+/// Lists are joined with `; `, and the fields of objects in them with spaces
+///
+/// # Errors
+///
+/// This function will return an error if `value` can't be serialized or a column is not one of
+/// its fields
+pub fn csv(value: &impl Serialize, columns: &[impl AsRef<str>]) -> anyhow::Result<String> {
+    fn text(value: &Value) -> String {
+        match value {
+            Value::Null => String::new(),
+            Value::String(x) => x.clone(),
+            Value::Array(x) => x.iter().map(text).collect::<Vec<_>>().join("; "),
+            Value::Object(x) => x
+                .values()
+                .map(text)
+                .filter(|x| !x.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+            x => x.to_string(),
+        }
+    }
+    fn quote(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    }
+    let value = serde_json::to_value(value)?;
+    let header = columns
+        .iter()
+        .map(|x| quote(x.as_ref()))
+        .collect::<Vec<_>>();
+    let row = columns
+        .iter()
+        .map(|x| Ok(quote(&text(&lookup(&value, x.as_ref())?))))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(format!("{}\n{}\n", header.join(","), row.join(",")))
+}
+
+/// Home Assistant sensors: the state key, name, unit and icon
+const HASS_SENSORS: [(&str, &str, Option<&str>, &str); 4] = [
+    ("memory", "Memory", Some("%"), "mdi:memory"),
+    ("disk", "Disk", Some("%"), "mdi:harddisk"),
+    ("battery", "Battery", Some("%"), "mdi:battery"),
+    ("uptime", "Uptime", None, "mdi:timer-outline"),
+];
+
+/// Bytes in a size like `5.86 GiB`
+fn parse_size(size: &str) -> Option<f64> {
+    let (number, unit) = size.trim().split_once(' ')?;
+    let power = ["bytes", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]
+        .iter()
+        .position(|x| unit.starts_with(x))?;
+    Some(number.parse::<f64>().ok()? * 1024_f64.powi(i32::try_from(power).ok()?))
+}
+
+/// The CPU of an older snapshot from its text, split up where that reads back the same
+fn cpu_from_text(text: &str) -> Cpu {
+    let (rest, freq_mhz) = match text.rsplit_once(" @ ") {
+        Some((rest, speed)) => match speed.strip_suffix(" GHz").map(str::parse::<f64>) {
+            Some(Ok(ghz)) => (rest, Some(ghz * 1000.0)),
+            _ => return Cpu::new(text),
+        },
+        None => (text, None),
+    };
+    let (model, cores) = rest
+        .strip_suffix(')')
+        .and_then(|x| x.rsplit_once(" ("))
+        .and_then(|(model, cores)| Some((model, cores.parse::<f64>().ok()?)))
+        .map_or((rest, None), |(model, cores)| (model, Some(cores)));
+    let cpu = Cpu {
+        cores,
+        freq_mhz,
+        ..Cpu::new(model)
+    };
+    // Anything else, like a temperature, is kept as it was
+    if cpu.to_string() == text {
+        cpu
+    } else {
+        Cpu::new(text)
+    }
+}
+
+/// Memory or swap of a snapshot, from the text of older snapshots that only have that
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn usage(document: &Value, key: &str, text_key: &str) -> Option<Memory> {
+    document
+        .get(key)
+        .and_then(|x| Memory::deserialize(x).ok())
+        .or_else(|| {
+            let text = document.get(text_key)?.as_str()?;
+            let (used, total) = parse_usage(text)?;
+            Some(Memory {
+                used: used as u64,
+                total: total as u64,
+                cgroup: text.ends_with("(cgroup)"),
+            })
+        })
+}
+
+/// Bytes used on the disk `name` of a snapshot, from the text of older snapshots that only have
+/// that
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn disk_usage(document: &Value, name: &str, disk: &Value) -> Option<DiskUsage> {
+    document
+        .get("disk_usage")
+        .and_then(Value::as_array)
+        .and_then(|x| {
+            x.iter()
+                .find(|x| x.get("name").and_then(Value::as_str) == Some(name))
+        })
+        .and_then(|x| DiskUsage::deserialize(x).ok())
+        .or_else(|| {
+            let (used, total) = parse_usage(disk.get("usage")?.as_str()?)?;
+            Some(DiskUsage {
+                used: used as u64,
+                total: total as u64,
+            })
+        })
+}
+
+/// Used and total bytes of a `used / total` size like the memory and disk modules show
+fn parse_usage(usage: &str) -> Option<(f64, f64)> {
+    let (used, total) = usage.split_once('/')?;
+    // Anything after the total, like ` (cgroup)`, is left out
+    let total = total
+        .trim()
+        .splitn(3, ' ')
+        .take(2)
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some((parse_size(used)?, parse_size(&total)?))
+}
+
+/// `used` as a percentage of `total`, rounded to a tenth
+fn percentage(used: f64, total: f64) -> Option<f64> {
+    let percent = used / total * 100.0;
+    percent.is_finite().then(|| (percent * 10.0).round() / 10.0)
+}
+
+/// MQTT messages for Home Assistant as `(topic, payload)` pairs: a discovery config for each
+/// sensor that has a value, then the state of all of them on `state_topic`
+///
+/// The default state topic is `mirafetch/<hostname>/state`
+#[must_use]
+pub fn hass_messages(info: &Info, state_topic: Option<&str>) -> Vec<(String, Value)> {
+    let host = info.hostname.as_deref().unwrap_or("mirafetch");
+    let node = host
+        .chars()
+        .map(|x| {
+            if x.is_ascii_alphanumeric() {
+                x.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let state_topic = state_topic.map_or_else(|| format!("mirafetch/{node}/state"), String::from);
+    let disk = info
+        .disks
+        .iter()
+        .find(|(name, _)| name.as_str() == "Disk (/)")
+        .or_else(|| info.disks.first());
+    let state = serde_json::json!({
+        "memory": info.memory.and_then(|x| percentage(x.used as f64, x.total as f64)),
+        "disk": disk.and_then(|(_, x)| percentage(x.used as f64, x.total as f64)),
+        "battery": info
+            .battery
+            .as_deref()
+            .and_then(|x| x.split_once('%')?.0.trim().parse::<f64>().ok()),
+        "uptime": info.uptime.as_deref(),
+    });
+    let mut messages = HASS_SENSORS
+        .iter()
+        .filter(|(key, ..)| !state[key].is_null())
+        .map(|(key, name, unit, icon)| {
+            let mut config = serde_json::json!({
+                "name": name,
+                "unique_id": format!("mirafetch_{node}_{key}"),
+                "state_topic": state_topic,
+                "value_template": format!("{{{{ value_json.{key} }}}}"),
+                "icon": icon,
+                "device": {
+                    "identifiers": [format!("mirafetch_{node}")],
+                    "name": host,
+                    "sw_version": info.os.as_deref(),
+                    "model": info.machine.as_deref(),
+                },
+            });
+            if let Some(unit) = unit {
+                config["unit_of_measurement"] = Value::from(*unit);
+                config["state_class"] = Value::from("measurement");
+            }
+            if *key == "battery" {
+                config["device_class"] = Value::from("battery");
+            }
+            (
+                format!("homeassistant/sensor/mirafetch_{node}/{key}/config"),
+                config,
+            )
+        })
+        .collect::<Vec<_>>();
+    messages.push((state_topic, state));
+    messages
+}
+
+/// Home Assistant MQTT discovery messages as a JSON array of `topic` and `payload` objects
+///
+/// # Errors
+///
+/// This function will return an error if serialization fails
+pub fn to_hass(info: &Info) -> serde_json::Result<String> {
+    let messages = hass_messages(info, None)
+        .into_iter()
+        .map(|(topic, payload)| serde_json::json!({ "topic": topic, "payload": payload }))
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&messages)
+}
+
+/// OS names as Ansible calls them in `ansible_distribution`, by the start of the OS line
+const ANSIBLE_DISTRIBUTIONS: &[(&str, &str)] = &[
+    ("Alpine Linux", "Alpine"),
+    ("Amazon Linux", "Amazon"),
+    ("Arch Linux", "Archlinux"),
+    ("CentOS", "CentOS"),
+    ("Debian GNU/Linux", "Debian"),
+    ("Fedora Linux", "Fedora"),
+    ("Linux Mint", "Linux Mint"),
+    ("macOS", "MacOSX"),
+    ("openSUSE Leap", "openSUSE Leap"),
+    ("openSUSE Tumbleweed", "openSUSE Tumbleweed"),
+    ("Red Hat Enterprise Linux", "RedHat"),
+    ("Rocky Linux", "Rocky"),
+    ("Ubuntu", "Ubuntu"),
+];
+
+/// Distribution name as Ansible has it, with its version, from an OS line like
+/// `Debian GNU/Linux 12 x86_64`
+fn ansible_distribution(os: &str, arch: Option<&str>) -> (String, Option<String>) {
+    let mut words = os.split_whitespace().collect::<Vec<_>>();
+    if words.len() > 1 && arch.is_some_and(|arch| words.last() == Some(&arch)) {
+        words.pop();
+    }
+    // Some versions start with a `v`, like Alpine's
+    let version = words
+        .iter()
+        .rposition(|x| {
+            x.trim_start_matches('v')
+                .starts_with(|x: char| x.is_ascii_digit())
+        })
+        .filter(|idx| *idx > 0)
+        .map(|idx| words.remove(idx).trim_start_matches('v').to_string());
+    let name = words.join(" ");
+    let name = ANSIBLE_DISTRIBUTIONS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map_or_else(|| name.clone(), |(_, ansible)| (*ansible).to_string());
+    (name, version)
+}
+
+/// Ansible's `processor` fact, the index, vendor and model of every vCPU one after the other in a
+/// flat list, like `["0", "GenuineIntel", "Xeon", "1", "GenuineIntel", "Xeon"]`
+fn ansible_processor(model: &str, vcpus: u64) -> Value {
+    // The vendor is left out where /proc/cpuinfo has no `vendor_id`, as on ARM
+    let vendor = [("Intel", "GenuineIntel"), ("AMD", "AuthenticAMD")]
+        .iter()
+        .find(|(name, _)| model.contains(name))
+        .map(|(_, vendor)| *vendor);
+    Value::from(
+        (0..vcpus)
+            .flat_map(|idx| {
+                [
+                    Some(idx.to_string()),
+                    vendor.map(String::from),
+                    Some(model.to_string()),
+                ]
+            })
+            .flatten()
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// A subset of Ansible's facts with the same names and shapes, e.g. `ansible_distribution` and
+/// `ansible_memtotal_mb`, leaving out the ones that weren't collected
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn ansible_facts(info: &Info) -> serde_json::Map<String, Value> {
+    let mb = |bytes: u64| Value::from((bytes as f64 / 1024.0 / 1024.0).round() as u64);
+    let arch = info
+        .arch
+        .as_deref()
+        .and_then(|x| x.split(',').next())
+        .map(str::trim);
+    let mut facts = serde_json::Map::new();
+    let mut set = |key: &str, value: Option<Value>| {
+        if let Some(value) = value {
+            facts.insert(format!("ansible_{key}"), value);
+        }
+    };
+    set("hostname", info.hostname.as_deref().map(Value::from));
+    set("user_id", info.username.as_deref().map(Value::from));
+    if let Some(os) = &info.os {
+        let (name, version) = ansible_distribution(os, arch);
+        set("distribution", Some(Value::from(name)));
+        if let Some(version) = version {
+            let major = version.split('.').next().unwrap_or_default().to_string();
+            set("distribution_version", Some(Value::from(version)));
+            set("distribution_major_version", Some(Value::from(major)));
+        }
+    }
+    set("kernel", info.kernel.as_deref().map(Value::from));
+    set("architecture", arch.map(Value::from));
+    set("product_name", info.machine.as_deref().map(Value::from));
+    if let Some(cpu) = &info.cpu {
+        // A container's quota can be a fraction of a CPU, which isn't a vCPU count
+        let vcpus = cpu.cores.filter(|x| x.fract() == 0.0).map(|x| x as u64);
+        set("processor", vcpus.map(|x| ansible_processor(&cpu.model, x)));
+        set("processor_vcpus", vcpus.map(Value::from));
+    }
+    // Ansible's `memfree_mb` is MemFree, which leaves out the cache that our free memory counts
+    set("memtotal_mb", info.memory.map(|x| mb(x.total)));
+    set("swaptotal_mb", info.swap.map(|x| mb(x.total)));
+    set("swapfree_mb", info.swap.map(|x| mb(x.free())));
+    // Older snapshots can have several addresses in one
+    let addresses = |v6: bool| {
+        info.ip
+            .iter()
+            .flat_map(|(_, addr)| addr.split(", "))
+            .filter(|addr| addr.contains(':') == v6)
+            .map(Value::from)
+            .collect::<Vec<_>>()
+    };
+    set("all_ipv4_addresses", Some(Value::from(addresses(false))));
+    set("all_ipv6_addresses", Some(Value::from(addresses(true))));
+    facts
+}
+
+/// The facts of [`ansible_facts`] under an `ansible_facts` key, as Ansible modules return them
+///
+/// # Errors
+///
+/// This function will return an error if serialization fails
+pub fn to_ansible(info: &Info) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&serde_json::json!({ "ansible_facts": ansible_facts(info) }))
+}
@@ -0,0 +1,141 @@
+//! Colors of the labels, separators, values and logo, from the `palette` and `colors` settings
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use crossterm::style::Color;
+
+use crate::{
+    config::{Colors, PaletteName},
+    quirks::Quirks,
+};
+
+/// Colors of each part of the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub label: Color,
+    /// The `: ` between labels and values
+    pub separator: Color,
+    /// The terminal's own color when `None`
+    pub value: Option<Color>,
+    /// A single color for the whole logo in place of its own colors, unless a flag is set
+    pub logo: Option<Color>,
+}
+
+impl Palette {
+    /// Labels and separators in `color`, the rest in the terminal's and logo's own colors
+    #[must_use]
+    pub const fn accent(color: Color) -> Self {
+        Self {
+            label: color,
+            separator: color,
+            value: None,
+            logo: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn named(name: PaletteName) -> Self {
+        let [label, separator, value, logo] = match name {
+            PaletteName::Dracula => [
+                [0xbd, 0x93, 0xf9],
+                [0x62, 0x72, 0xa4],
+                [0xf8, 0xf8, 0xf2],
+                [0xff, 0x79, 0xc6],
+            ],
+            PaletteName::Nord => [
+                [0x88, 0xc0, 0xd0],
+                [0x4c, 0x56, 0x6a],
+                [0xec, 0xef, 0xf4],
+                [0x81, 0xa1, 0xc1],
+            ],
+            // The Mocha flavor
+            PaletteName::Catppuccin => [
+                [0xcb, 0xa6, 0xf7],
+                [0x6c, 0x70, 0x86],
+                [0xcd, 0xd6, 0xf4],
+                [0x89, 0xb4, 0xfa],
+            ],
+            PaletteName::Gruvbox => [
+                [0xfe, 0x80, 0x19],
+                [0x92, 0x83, 0x74],
+                [0xeb, 0xdb, 0xb2],
+                [0xfa, 0xbd, 0x2f],
+            ],
+        };
+        Self {
+            label: rgb(label),
+            separator: rgb(separator),
+            value: Some(rgb(value)),
+            logo: Some(rgb(logo)),
+        }
+    }
+
+    /// Replace the colors set in `colors`
+    #[must_use]
+    pub fn with_colors(self, colors: &Colors) -> Self {
+        let color = |x: Option<Hex>| x.map(Color::from);
+        Self {
+            label: color(colors.label).unwrap_or(self.label),
+            separator: color(colors.separator).unwrap_or(self.separator),
+            value: color(colors.value).or(self.value),
+            logo: color(colors.logo).or(self.logo),
+        }
+    }
+
+    /// The colors as the terminal can show them
+    #[must_use]
+    pub fn adapt(self, quirks: &Quirks) -> Self {
+        Self {
+            label: quirks.color(self.label),
+            separator: quirks.color(self.separator),
+            value: self.value.map(|x| quirks.color(x)),
+            logo: self.logo.map(|x| quirks.color(x)),
+        }
+    }
+}
+
+const fn rgb([r, g, b]: [u8; 3]) -> Color {
+    Color::Rgb { r, g, b }
+}
+
+/// A 24-bit color written as `#rrggbb`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Hex(pub [u8; 3]);
+
+impl TryFrom<String> for Hex {
+    type Error = anyhow::Error;
+
+    fn try_from(text: String) -> Result<Self> {
+        let invalid = || anyhow!("Expected a color like `#88c0d0`, not `{text}`");
+        let digits = text
+            .strip_prefix('#')
+            .filter(|x| x.len() == 6 && x.is_ascii())
+            .ok_or_else(invalid)?;
+        let channel = |idx: usize| u8::from_str_radix(&digits[idx..idx + 2], 16);
+        Ok(Self([
+            channel(0).map_err(|_| invalid())?,
+            channel(2).map_err(|_| invalid())?,
+            channel(4).map_err(|_| invalid())?,
+        ]))
+    }
+}
+
+impl From<Hex> for String {
+    fn from(hex: Hex) -> Self {
+        hex.to_string()
+    }
+}
+
+impl From<Hex> for Color {
+    fn from(Hex([r, g, b]): Hex) -> Self {
+        Self::Rgb { r, g, b }
+    }
+}
+
+impl fmt::Display for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b] = self.0;
+        write!(f, "#{r:02x}{g:02x}{b:02x}")
+    }
+}
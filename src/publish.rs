@@ -0,0 +1,174 @@
+//! Sending the JSON output to a webhook or an MQTT broker
+//!
+//! Targets are `http://` or `https://` URLs, which get a POST with the JSON as the body, or
+//! `mqtt://[user:password@]host[:port]/topic` URLs, which get a retained message on the topic
+//!
+//! Home Assistant discovery messages can be sent instead, see [`send_hass`]
+use std::{
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::{info::Info, output};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const MQTT_PORT: u16 = 1883;
+
+/// Send `json` to `target`
+///
+/// # Errors
+///
+/// This function will return an error if the target isn't a supported URL, can't be reached or
+/// refuses the message
+pub fn send(target: &str, json: &str) -> Result<()> {
+    target.strip_prefix("mqtt://").map_or_else(
+        || post(target, json),
+        |rest| mqtt_publish(rest, |topic| vec![(topic.to_string(), json.to_string())]),
+    )
+}
+
+/// Send Home Assistant discovery messages for `info` to `target`
+///
+/// MQTT brokers get each message on its own topic, with the state on the URL's topic, and
+/// webhooks get the JSON array of `--output hass`
+///
+/// # Errors
+///
+/// This function will return an error if the target isn't a supported URL, can't be reached or
+/// refuses the messages
+pub fn send_hass(target: &str, info: &Info) -> Result<()> {
+    target.strip_prefix("mqtt://").map_or_else(
+        || post(target, &output::to_hass(info)?),
+        |rest| {
+            mqtt_publish(rest, |topic| {
+                output::hass_messages(info, Some(topic))
+                    .into_iter()
+                    .map(|(topic, payload)| (topic, payload.to_string()))
+                    .collect()
+            })
+        },
+    )
+}
+
+fn post(target: &str, json: &str) -> Result<()> {
+    if target.starts_with("http://") || target.starts_with("https://") {
+        ureq::post(target)
+            .timeout(TIMEOUT)
+            .set(
+                "User-Agent",
+                concat!("mirafetch/", env!("CARGO_PKG_VERSION")),
+            )
+            .set("Content-Type", "application/json")
+            .send_string(json)
+            // ureq's errors start with the URL, password and all
+            .map_err(|err| anyhow!(redact(&err.to_string())))?;
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Can't publish to {}, expected an http://, https:// or mqtt:// URL",
+            redact(target)
+        ))
+    }
+}
+
+/// `text` with the user name and password left out of every URL in it, so a target can be
+/// printed without its credentials
+#[must_use]
+pub fn redact(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("://") {
+        let (scheme, after) = rest.split_at(start + 3);
+        res.push_str(scheme);
+        let end = after
+            .find(|x: char| x == '/' || x.is_whitespace())
+            .unwrap_or(after.len());
+        rest = after[..end].rfind('@').map_or(after, |at| &after[at + 1..]);
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Publish the messages for the URL's topic with a single MQTT 3.1.1 connection: connect, publish
+/// each with no acknowledgement and disconnect
+fn mqtt_publish(url: &str, messages: impl FnOnce(&str) -> Vec<(String, String)>) -> Result<()> {
+    let (server, topic) = url
+        .split_once('/')
+        .filter(|(_, topic)| !topic.is_empty())
+        .ok_or_else(|| anyhow!("mqtt:// URLs need a topic, e.g. mqtt://broker/mirafetch"))?;
+    let (login, host) = server
+        .rsplit_once('@')
+        .map_or((None, server), |(login, host)| (Some(login), host));
+    let addr = if host.contains(':') {
+        host.to_socket_addrs()
+    } else {
+        (host, MQTT_PORT).to_socket_addrs()
+    }?
+    .next()
+    .ok_or_else(|| anyhow!("Could not resolve {host}"))?;
+
+    let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    // Protocol name and level, then flags filled in below and a keep alive of a minute
+    let mut connect = vec![0, 4, b'M', b'Q', b'T', b'T', 4, 0x02, 0, 60];
+    write_string(&mut connect, &format!("mirafetch-{}", std::process::id()))?;
+    if let Some(login) = login {
+        let (user, password) = login
+            .split_once(':')
+            .map_or((login, None), |(user, password)| (user, Some(password)));
+        connect[7] |= 0x80;
+        write_string(&mut connect, user)?;
+        if let Some(password) = password {
+            connect[7] |= 0x40;
+            write_string(&mut connect, password)?;
+        }
+    }
+    stream.write_all(&packet(0x10, &connect))?;
+
+    let mut ack = [0; 4];
+    stream.read_exact(&mut ack)?;
+    match ack {
+        [0x20, 2, _, 0] => {}
+        [0x20, 2, _, 4 | 5] => return Err(anyhow!("The MQTT broker refused the login")),
+        [0x20, 2, _, code] => return Err(anyhow!("The MQTT broker refused to connect ({code})")),
+        _ => return Err(anyhow!("Unexpected reply from the MQTT broker")),
+    }
+
+    for (topic, payload) in messages(topic) {
+        let mut publish = Vec::new();
+        write_string(&mut publish, &topic)?;
+        publish.extend_from_slice(payload.as_bytes());
+        // Retained, so subscribers get the last summary as soon as they connect
+        stream.write_all(&packet(0x31, &publish))?;
+    }
+    stream.write_all(&[0xe0, 0])?;
+    Ok(())
+}
+
+fn write_string(buf: &mut Vec<u8>, text: &str) -> Result<()> {
+    buf.extend_from_slice(&u16::try_from(text.len())?.to_be_bytes());
+    buf.extend_from_slice(text.as_bytes());
+    Ok(())
+}
+
+/// An MQTT packet of `kind` with the body's length in 7 bit groups
+fn packet(kind: u8, body: &[u8]) -> Vec<u8> {
+    let mut buf = vec![kind];
+    let mut len = body.len();
+    loop {
+        let byte = u8::try_from(len % 128).unwrap_or_default();
+        len /= 128;
+        if len == 0 {
+            buf.push(byte);
+            break;
+        }
+        buf.push(byte | 0x80);
+    }
+    buf.extend_from_slice(body);
+    buf
+}
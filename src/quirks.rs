@@ -0,0 +1,148 @@
+//! What the running terminal can't do, so the output can work around it
+//!
+//! Most terminals say what they support through `COLORTERM` and by answering queries, this
+//! covers the ones that don't or that get it wrong
+use std::{env, process::Command};
+
+use crossterm::style::Color;
+
+use crate::config::TerminalQuirks;
+
+/// Terminals by `TERM_PROGRAM` or the start of `TERM`, with whether they have 24-bit colors
+/// when known, and whether they can show images with a graphics protocol
+const TERMINALS: &[(&str, Option<bool>, bool)] = &[
+    // Only the 256 color palette, and no image protocol
+    ("Apple_Terminal", Some(false), false),
+    // The Linux console has 16 colors at best
+    ("linux", Some(false), false),
+    ("iTerm.app", Some(true), true),
+    ("vscode", Some(true), true),
+    ("WezTerm", Some(true), true),
+];
+
+/// VTE, the terminal widget of GNOME Terminal and others, only has 24-bit colors since 0.36
+const VTE_TRUECOLOR: u32 = 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    /// Whether 24-bit colors can be used, rather than only the 256 color palette
+    pub truecolor: bool,
+    /// Whether the kitty, iTerm2 or sixel graphics protocols may be tried, or `None` if tmux has
+    /// to be asked first, see [`Self::images`]
+    images: Option<bool>,
+    /// Whether escape sequences for the terminal itself have to be wrapped for tmux to pass
+    /// them on, which needs `allow-passthrough` in tmux 3.3 and later
+    pub tmux_passthrough: bool,
+}
+
+impl Quirks {
+    /// Work out the running terminal's quirks, with those set in `overrides` taking precedence
+    #[must_use]
+    pub fn detect(overrides: Option<TerminalQuirks>) -> Self {
+        let var = |x| env::var(x).unwrap_or_default();
+        let term = var("TERM");
+        let program = var("TERM_PROGRAM");
+        let known = TERMINALS
+            .iter()
+            .find(|(name, ..)| program == *name || term.starts_with(name));
+        let vte = var("VTE_VERSION").parse::<u32>().ok();
+        let truecolor = known
+            .and_then(|(_, truecolor, _)| *truecolor)
+            .or_else(|| vte.map(|x| x >= VTE_TRUECOLOR))
+            .unwrap_or_else(|| {
+                matches!(var("COLORTERM").as_str(), "truecolor" | "24bit")
+                    // Windows Terminal doesn't set COLORTERM
+                    || env::var_os("WT_SESSION").is_some()
+            });
+        let overrides = overrides.unwrap_or_default();
+        let tmux = env::var_os("TMUX").is_some();
+        Self {
+            truecolor: overrides.truecolor.unwrap_or(truecolor),
+            images: overrides.images.or_else(|| {
+                let images = known.is_none_or(|(.., images)| *images);
+                (!tmux || !images).then_some(images)
+            }),
+            tmux_passthrough: overrides.tmux_passthrough.unwrap_or(tmux),
+        }
+    }
+
+    /// Whether the kitty, iTerm2 or sixel graphics protocols may be tried
+    ///
+    /// Inside tmux this runs `tmux show-options`, so it's only called when an image is about to be
+    /// shown
+    #[must_use]
+    pub fn images(&self) -> bool {
+        // tmux drops what it isn't allowed to pass on, so the logo would be missing
+        self.images.unwrap_or_else(tmux_allows_passthrough)
+    }
+
+    /// `color` as the terminal can show it, the closest palette color if it lacks 24-bit colors
+    #[must_use]
+    pub fn color(&self, color: Color) -> Color {
+        match color {
+            Color::Rgb { r, g, b } if !self.truecolor => {
+                Color::AnsiValue(nearest_ansi([r, g, b].map(f32::from)).0)
+            }
+            color => color,
+        }
+    }
+
+    /// Wrap an escape sequence meant for the terminal so it gets through tmux if needed
+    #[must_use]
+    pub fn passthrough(&self, escape: &str) -> String {
+        if self.tmux_passthrough {
+            format!("\x1bPtmux;{}\x1b\\", escape.replace('\x1b', "\x1b\x1b"))
+        } else {
+            escape.to_string()
+        }
+    }
+}
+
+/// Whether tmux passes escape sequences on to the terminal, which tmux 3.3 and later only do
+/// with `allow-passthrough`, while earlier versions don't have the option and always do
+fn tmux_allows_passthrough() -> bool {
+    Command::new("tmux")
+        .args(["show-options", "-Apv", "allow-passthrough"])
+        .output()
+        .map_or(true, |out| {
+            !out.status.success() || String::from_utf8_lossy(&out.stdout).trim() != "off"
+        })
+}
+
+#[must_use]
+pub fn distance(x: [u8; 3], y: [u8; 3]) -> u32 {
+    x.iter()
+        .zip(y)
+        .map(|(x, y)| u32::from(x.abs_diff(y)).pow(2))
+        .sum()
+}
+
+/// Closest color of the 256 color palette's color cube and grayscale ramp
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+#[must_use]
+pub fn nearest_ansi(rgb: [f32; 3]) -> (u8, [u8; 3]) {
+    const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |x: f32| {
+        (0..CUBE.len())
+            .min_by_key(|idx| (f32::from(CUBE[*idx]) - x).abs() as u32)
+            .unwrap_or_default()
+    };
+    let [r, g, b] = rgb.map(level);
+    let cube = ((16 + 36 * r + 6 * g + b) as u8, [CUBE[r], CUBE[g], CUBE[b]]);
+    let gray_level = ((rgb.iter().sum::<f32>() / 3.0 - 8.0) / 10.0)
+        .round()
+        .clamp(0.0, 23.0) as u8;
+    let gray_value = 8 + 10 * gray_level;
+    let gray = (232 + gray_level, [gray_value; 3]);
+
+    let clamped = rgb.map(|x| x.clamp(0.0, 255.0) as u8);
+    if distance(clamped, gray.1) < distance(clamped, cube.1) {
+        gray
+    } else {
+        cube
+    }
+}
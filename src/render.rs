@@ -0,0 +1,280 @@
+use std::{
+    cmp::max,
+    fmt::{Display, Write},
+};
+
+use anyhow::Result;
+use arcstr::ArcStr;
+use crossterm::{
+    cursor::{MoveTo, MoveToColumn, MoveToNextLine},
+    style::{ContentStyle, PrintStyledContent, StyledContent, Stylize},
+    terminal::{Clear, ClearType::All},
+    QueueableCommand,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{config::Direction, palette::Palette, util::AsciiArt};
+
+/// Queue the logo and system information into `frame` in the colors of `palette`, ready to be
+/// written to the terminal
+///
+/// # Errors
+///
+/// This function will return an error if a command cannot be queued
+pub fn render(
+    frame: &mut Vec<u8>,
+    icon: impl IntoIterator<Item = StyledContent<impl Display>>,
+    info: &[(ArcStr, ArcStr)],
+    icon_width: u16,
+    icon_height: usize,
+    direction: Direction,
+    palette: Palette,
+) -> Result<()> {
+    // Escape sequences add a few dozen bytes to each styled chunk
+    frame.clear();
+    frame.reserve(
+        usize::from(icon_width) * icon_height * 8
+            + info
+                .iter()
+                .map(|(x, y)| x.len() + y.len() + 64)
+                .sum::<usize>(),
+    );
+
+    if direction == Direction::Rtl {
+        return render_rtl(frame, icon, info, icon_height, palette);
+    }
+
+    frame.queue(Clear(All))?.queue(MoveTo(0, 0))?;
+    for line in icon {
+        frame.queue(PrintStyledContent(line))?;
+    }
+    frame.queue(MoveTo(0, 0))?;
+    for (x, y) in info {
+        frame
+            .queue(MoveToColumn(icon_width + gap(icon_width)))?
+            .queue(PrintStyledContent(x.as_str().bold().with(palette.label)))?;
+        if !x.is_empty() && !y.is_empty() {
+            frame.queue(PrintStyledContent(": ".bold().with(palette.separator)))?;
+        }
+        frame
+            .queue(PrintStyledContent(value(y.as_str(), palette)))?
+            .queue(MoveToNextLine(1))?;
+    }
+    let height = u16::try_from(max(icon_height, info.len())).unwrap_or(u16::MAX);
+    frame.queue(MoveTo(0, height.saturating_add(1)))?;
+    Ok(())
+}
+
+/// Mirror of [`render`] with the right aligned info column first and the logo after it
+///
+/// Each line ends with its label, and values are isolated so the terminal's bidi
+/// algorithm can't reorder them across the label
+fn render_rtl(
+    frame: &mut Vec<u8>,
+    icon: impl IntoIterator<Item = StyledContent<impl Display>>,
+    info: &[(ArcStr, ArcStr)],
+    icon_height: usize,
+    palette: Palette,
+) -> Result<()> {
+    let line_width = |(x, y): &(ArcStr, ArcStr)| {
+        let separator = if x.is_empty() || y.is_empty() { 0 } else { 2 };
+        visible_width(x) + separator + visible_width(y)
+    };
+    let info_width = info.iter().map(line_width).max().unwrap_or_default();
+    let icon_column = u16::try_from(info_width + 3).unwrap_or(u16::MAX);
+
+    frame.queue(Clear(All))?;
+    queue_icon(frame, icon, icon_column, 0)?;
+    frame.queue(MoveTo(0, 0))?;
+    for line @ (x, y) in info {
+        let padding = u16::try_from(info_width - line_width(line)).unwrap_or_default();
+        frame
+            .queue(MoveToColumn(padding))?
+            .queue(PrintStyledContent(value(
+                format!("\u{2068}{y}\u{2069}"),
+                palette,
+            )))?;
+        if !x.is_empty() && !y.is_empty() {
+            frame.queue(PrintStyledContent(" :".bold().with(palette.separator)))?;
+        }
+        frame
+            .queue(PrintStyledContent(x.as_str().bold().with(palette.label)))?
+            .queue(MoveToNextLine(1))?;
+    }
+    let height = u16::try_from(max(icon_height, info.len())).unwrap_or(u16::MAX);
+    frame.queue(MoveTo(0, height.saturating_add(1)))?;
+    Ok(())
+}
+
+/// Write the logo and system information line by line, for output that isn't a terminal
+///
+/// Nothing moves the cursor, and without `color` no escape codes are written at all, so the
+/// text reads the same in a pipe, a file or a CI log
+///
+/// # Errors
+///
+/// This function will return an error if a line cannot be formatted
+pub fn render_lines(
+    frame: &mut Vec<u8>,
+    icon: impl IntoIterator<Item = StyledContent<impl Display>>,
+    info: &[(ArcStr, ArcStr)],
+    icon_width: u16,
+    direction: Direction,
+    color: bool,
+    palette: Palette,
+) -> Result<()> {
+    let mut rows = vec![String::new()];
+    for chunk in icon {
+        let text = chunk.content().to_string();
+        for (idx, part) in text.split('\n').enumerate() {
+            if idx > 0 {
+                rows.push(String::new());
+            }
+            let row = rows.last_mut().expect("rows start with one line");
+            if color {
+                write!(row, "{}", StyledContent::new(*chunk.style(), part))?;
+            } else {
+                row.push_str(part);
+            }
+        }
+    }
+    while rows.last().is_some_and(|x| visible_width(x) == 0) {
+        rows.pop();
+    }
+
+    // Lines without a label are the color palette, which is nothing but escape codes
+    let info = info
+        .iter()
+        .filter(|(x, _)| color || !x.is_empty())
+        .collect::<Vec<_>>();
+    let paint = |text: &str, part: Part| match (color, part) {
+        (false, _) => text.to_string(),
+        (true, Part::Label) => text.bold().with(palette.label).to_string(),
+        (true, Part::Separator) => text.bold().with(palette.separator).to_string(),
+        (true, Part::Value) => value(text, palette).to_string(),
+    };
+    let line_width = |(x, y): &(ArcStr, ArcStr)| {
+        let separator = if x.is_empty() || y.is_empty() { 0 } else { 2 };
+        visible_width(x) + separator + visible_width(y)
+    };
+    let info_width = info.iter().map(|x| line_width(x)).max().unwrap_or_default();
+
+    frame.clear();
+    for idx in 0..max(rows.len(), info.len()) {
+        let icon = rows.get(idx).map_or("", String::as_str);
+        let mut line = String::new();
+        match (info.get(idx), direction) {
+            (Some(pair @ (x, y)), Direction::Rtl) => {
+                let separator = if x.is_empty() || y.is_empty() {
+                    ""
+                } else {
+                    " :"
+                };
+                let padding = info_width - line_width(pair);
+                write!(
+                    line,
+                    "{:padding$}{}{}{}   {icon}",
+                    "",
+                    paint(&format!("\u{2068}{y}\u{2069}"), Part::Value),
+                    paint(separator, Part::Separator),
+                    paint(x, Part::Label),
+                )?;
+            }
+            (Some((x, y)), Direction::Ltr) => {
+                let separator = if x.is_empty() || y.is_empty() {
+                    ""
+                } else {
+                    ": "
+                };
+                let padding = usize::from(icon_width + gap(icon_width))
+                    - visible_width(icon).min(icon_width.into());
+                write!(
+                    line,
+                    "{icon}{:padding$}{}{}{}",
+                    "",
+                    paint(x, Part::Label),
+                    paint(separator, Part::Separator),
+                    paint(y, Part::Value),
+                )?;
+            }
+            (None, Direction::Rtl) => write!(line, "{:width$}{icon}", "", width = info_width + 3)?,
+            (None, Direction::Ltr) => line.push_str(icon),
+        }
+        frame.extend_from_slice(line.trim_end_matches(' ').as_bytes());
+        frame.push(b'\n');
+    }
+    Ok(())
+}
+
+/// Columns between the logo and the info, none when there is no logo
+const fn gap(icon_width: u16) -> u16 {
+    if icon_width == 0 {
+        0
+    } else {
+        3
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Part {
+    Label,
+    Separator,
+    Value,
+}
+
+/// A value in the palette's color, or the terminal's own if it has none
+fn value<D: Display>(text: D, palette: Palette) -> StyledContent<D> {
+    let style = ContentStyle::new();
+    let style = palette
+        .value
+        .map_or_else(|| style.reset(), |color| style.with(color));
+    StyledContent::new(style, text)
+}
+
+/// Queue the icon with its top left corner at `column` and `row`
+///
+/// # Errors
+///
+/// This function will return an error if a command cannot be queued
+pub fn queue_icon(
+    frame: &mut Vec<u8>,
+    icon: impl IntoIterator<Item = StyledContent<impl Display>>,
+    column: u16,
+    row: u16,
+) -> Result<()> {
+    frame.queue(MoveTo(column, row))?;
+    for chunk in icon {
+        let text = chunk.content().to_string();
+        for (idx, part) in text.split('\n').enumerate() {
+            if idx > 0 {
+                frame
+                    .queue(MoveToNextLine(1))?
+                    .queue(MoveToColumn(column))?;
+            }
+            frame.queue(PrintStyledContent(StyledContent::new(*chunk.style(), part)))?;
+        }
+    }
+    Ok(())
+}
+
+/// Width of `text` in terminal columns, leaving out color escape sequences and hyperlinks
+#[must_use]
+pub fn visible_width(text: &str) -> usize {
+    text.split('\x1b')
+        .enumerate()
+        .map(|(idx, part)| match part.split_once('m') {
+            _ if idx == 0 => part.width(),
+            // Hyperlinks are OSC sequences, which end with `ESC \`
+            _ if part.starts_with(']') => 0,
+            _ if part.starts_with('\\') => part[1..].width(),
+            Some((_, rest)) => rest.width(),
+            None => 0,
+        })
+        .sum()
+}
+
+/// Lines the logo takes up
+#[must_use]
+pub fn icon_height(logo: &AsciiArt) -> usize {
+    logo.art.iter().map(|(_, x)| x.matches('\n').count()).sum()
+}
@@ -0,0 +1,233 @@
+#![cfg(target_os = "linux")]
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use seccompiler::{
+    BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompFilter,
+    SeccompRule,
+};
+
+/// Syscalls allowed with any arguments: reading files and directories, memory, threads,
+/// time, process and system details, and the socket calls used to list network interfaces
+/// and talk to the display server over the connections opened before the filter
+///
+/// `adjtimex` is left out, as it sets the clock as well as reading it, so `time_sync` finds
+/// nothing in hardened mode
+const ALLOWED: &[libc::c_long] = &[
+    libc::SYS_read,
+    libc::SYS_pread64,
+    libc::SYS_readv,
+    libc::SYS_preadv,
+    libc::SYS_lseek,
+    libc::SYS_close,
+    libc::SYS_fstat,
+    libc::SYS_newfstatat,
+    libc::SYS_statx,
+    libc::SYS_statfs,
+    libc::SYS_fstatfs,
+    libc::SYS_getdents64,
+    libc::SYS_readlinkat,
+    libc::SYS_faccessat,
+    libc::SYS_faccessat2,
+    libc::SYS_fcntl,
+    libc::SYS_getcwd,
+    libc::SYS_mmap,
+    libc::SYS_munmap,
+    libc::SYS_mremap,
+    libc::SYS_mprotect,
+    libc::SYS_madvise,
+    libc::SYS_brk,
+    libc::SYS_futex,
+    libc::SYS_sched_yield,
+    libc::SYS_sched_getaffinity,
+    libc::SYS_set_robust_list,
+    libc::SYS_rseq,
+    libc::SYS_sigaltstack,
+    libc::SYS_rt_sigaction,
+    libc::SYS_rt_sigprocmask,
+    libc::SYS_rt_sigreturn,
+    libc::SYS_exit,
+    libc::SYS_exit_group,
+    libc::SYS_gettid,
+    libc::SYS_getpid,
+    libc::SYS_getppid,
+    libc::SYS_getuid,
+    libc::SYS_geteuid,
+    libc::SYS_getgid,
+    libc::SYS_getegid,
+    libc::SYS_uname,
+    libc::SYS_sysinfo,
+    libc::SYS_prlimit64,
+    libc::SYS_getrandom,
+    libc::SYS_clock_gettime,
+    libc::SYS_clock_nanosleep,
+    libc::SYS_nanosleep,
+    libc::SYS_ppoll,
+    libc::SYS_bind,
+    libc::SYS_getsockname,
+    libc::SYS_getpeername,
+    libc::SYS_getsockopt,
+    libc::SYS_sendto,
+    libc::SYS_sendmsg,
+    libc::SYS_recvfrom,
+    libc::SYS_recvmsg,
+    // Refused by the first filter so threads are created with clone, whose flags can be checked
+    libc::SYS_clone3,
+    #[cfg(target_arch = "x86_64")]
+    libc::SYS_stat,
+    #[cfg(target_arch = "x86_64")]
+    libc::SYS_lstat,
+    #[cfg(target_arch = "x86_64")]
+    libc::SYS_access,
+    #[cfg(target_arch = "x86_64")]
+    libc::SYS_readlink,
+    #[cfg(target_arch = "x86_64")]
+    libc::SYS_poll,
+];
+
+fn arg(index: u8, op: SeccompCmpOp, value: u64) -> Result<SeccompCondition> {
+    Ok(SeccompCondition::new(
+        index,
+        SeccompCmpArgLen::Qword,
+        op,
+        value,
+    )?)
+}
+
+/// Rules that each allow a call when all their conditions hold
+fn rules(conditions: Vec<Vec<SeccompCondition>>) -> Result<Vec<SeccompRule>> {
+    Ok(conditions
+        .into_iter()
+        .map(SeccompRule::new)
+        .collect::<Result<_, _>>()?)
+}
+
+/// `DRM_IOCTL_MODE_*` requests, all `_IOWR('d', nr, size)`, by number and struct size
+const DRM_IOCTLS: &[(u64, u64)] = &[
+    // GETRESOURCES, drm_mode_card_res
+    (0xa0, 64),
+    // GETCRTC, drm_mode_crtc
+    (0xa1, 104),
+    // GETENCODER, drm_mode_get_encoder
+    (0xa6, 20),
+    // GETCONNECTOR, drm_mode_get_connector
+    (0xa7, 80),
+    // GETPROPERTY, drm_mode_get_property
+    (0xaa, 64),
+    // OBJ_GETPROPERTIES, drm_mode_obj_get_properties
+    (0xb9, 32),
+];
+
+const fn drm_ioctl(nr: u64, size: u64) -> u64 {
+    (3 << 30) | (size << 16) | ((b'd' as u64) << 8) | nr
+}
+
+/// Syscalls only allowed with some arguments
+fn conditional() -> Result<BTreeMap<i64, Vec<SeccompRule>>> {
+    let read_only = (libc::O_ACCMODE | libc::O_CREAT | libc::O_TRUNC) as u64;
+    let pid = u64::from(std::process::id());
+    let mut res = BTreeMap::new();
+    // Opening files for reading only
+    res.insert(
+        libc::SYS_openat,
+        rules(vec![vec![arg(2, SeccompCmpOp::MaskedEq(read_only), 0)?]])?,
+    );
+    #[cfg(target_arch = "x86_64")]
+    res.insert(
+        libc::SYS_open,
+        rules(vec![vec![arg(1, SeccompCmpOp::MaskedEq(read_only), 0)?]])?,
+    );
+    // Writing to stdout and stderr
+    for call in [libc::SYS_write, libc::SYS_writev] {
+        res.insert(
+            call,
+            rules(vec![
+                vec![arg(0, SeccompCmpOp::Eq, 1)?],
+                vec![arg(0, SeccompCmpOp::Eq, 2)?],
+            ])?,
+        );
+    }
+    // Netlink for network interfaces, as the display servers are connected to beforehand and
+    // no other socket may be
+    res.insert(
+        libc::SYS_socket,
+        rules(vec![vec![arg(
+            0,
+            SeccompCmpOp::Eq,
+            libc::AF_NETLINK as u64,
+        )?]])?,
+    );
+    // Threads, but not new processes
+    let thread = libc::CLONE_THREAD as u64;
+    res.insert(
+        libc::SYS_clone,
+        rules(vec![vec![arg(0, SeccompCmpOp::MaskedEq(thread), thread)?]])?,
+    );
+    // The terminal size and the DRM queries that are made
+    let mut ioctls = vec![
+        vec![arg(1, SeccompCmpOp::Eq, libc::TIOCGWINSZ)?],
+        vec![arg(1, SeccompCmpOp::Eq, libc::TCGETS)?],
+    ];
+    for (nr, size) in DRM_IOCTLS {
+        ioctls.push(vec![arg(1, SeccompCmpOp::Eq, drm_ioctl(*nr, *size))?]);
+    }
+    res.insert(libc::SYS_ioctl, rules(ioctls)?);
+    // Naming threads, and aborting this process after a panic
+    res.insert(
+        libc::SYS_prctl,
+        rules(vec![vec![arg(
+            0,
+            SeccompCmpOp::Eq,
+            libc::PR_SET_NAME as u64,
+        )?]])?,
+    );
+    res.insert(
+        libc::SYS_tgkill,
+        rules(vec![vec![arg(0, SeccompCmpOp::Eq, pid)?]])?,
+    );
+    Ok(res)
+}
+
+/// Restrict every thread of mirafetch to reading files and listing system details
+///
+/// Anything else, such as writing files other than stdout and stderr, running programs or
+/// connecting to sockets, fails with `EPERM` from then on. The display servers are connected
+/// to before the filter is applied. `io_uring` is refused too as its operations aren't
+/// checked by seccomp
+///
+/// # Errors
+///
+/// This function will return an error if the kernel doesn't support seccomp filters or the
+/// architecture isn't supported
+pub fn apply() -> Result<()> {
+    let arch = std::env::consts::ARCH.try_into().map_err(|_| {
+        anyhow!(
+            "Hardened mode isn't supported on {}",
+            std::env::consts::ARCH
+        )
+    })?;
+
+    // glibc falls back to clone when clone3 isn't implemented
+    let clone3: BpfProgram = SeccompFilter::new(
+        BTreeMap::from([(libc::SYS_clone3, Vec::new())]),
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::ENOSYS as u32),
+        arch,
+    )?
+    .try_into()?;
+
+    let mut allowed = conditional()?;
+    allowed.extend(ALLOWED.iter().map(|x| (*x, Vec::new())));
+    let allowed: BpfProgram = SeccompFilter::new(
+        allowed,
+        SeccompAction::Errno(libc::EPERM as u32),
+        SeccompAction::Allow,
+        arch,
+    )?
+    .try_into()?;
+
+    crate::info::preconnect();
+    seccompiler::apply_filter_all_threads(&clone3)?;
+    seccompiler::apply_filter_all_threads(&allowed)?;
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, Result};
+use minisign_verify::{PublicKey, Signature};
+
+/// Minisign public keys whose signatures are accepted for logo packs
+pub struct TrustedKeys(Vec<PublicKey>);
+
+impl TrustedKeys {
+    /// Read the keys in `path`, one base64 key per line
+    ///
+    /// Blank lines, `#` comments and the `untrusted comment:` line of a `minisign.pub` file
+    /// are skipped, so public key files can be appended as they are
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read or contains an invalid key
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .map_err(|err| anyhow!("Could not read trusted keys {}: {err}", path.display()))?;
+        let keys = data
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx, line.trim()))
+            .filter(|(_, line)| {
+                !(line.is_empty()
+                    || line.starts_with('#')
+                    || line.starts_with("untrusted comment:"))
+            })
+            .map(|(idx, line)| {
+                PublicKey::from_base64(line)
+                    .map_err(|_| anyhow!("Invalid key on line {} of {}", idx + 1, path.display()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self(keys))
+    }
+
+    /// Check that `data`, the contents of `path`, is signed by a trusted key in `<path>.minisig`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the signature is missing, invalid, or made by a key
+    /// that isn't trusted
+    pub fn verify(&self, path: &Path, data: &[u8]) -> Result<()> {
+        let mut sig_path = path.as_os_str().to_owned();
+        sig_path.push(".minisig");
+        let signature = fs::read_to_string(&sig_path)
+            .map_err(|_| anyhow!("no signature in {}", Path::new(&sig_path).display()))?;
+        let signature =
+            Signature::decode(&signature).map_err(|_| anyhow!("signature could not be decoded"))?;
+        if self
+            .0
+            .iter()
+            .any(|key| key.verify(data, &signature, false).is_ok())
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("not signed by a trusted key"))
+        }
+    }
+}
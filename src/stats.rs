@@ -0,0 +1,117 @@
+use std::{collections::BTreeMap, fmt::Write, fs, io::ErrorKind, path::Path, time::Duration};
+
+use anyhow::Result;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+/// Number of timings kept for the whole run and for each module
+const HISTORY: usize = 20;
+
+/// How often mirafetch ran and how long it took, kept on this machine only
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub runs: u64,
+    /// Recent collection times of the whole run, in microseconds
+    pub total: Vec<u64>,
+    /// Recent times of each module in microseconds, keyed by their label
+    pub modules: BTreeMap<String, Vec<u64>>,
+}
+
+impl Stats {
+    /// Read the stats in `path`, or empty stats if nothing was recorded yet
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read or parsed
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(data) => Ok(serde_json::from_str(&data)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Write the stats to `path`, creating its directory if needed
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be written
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Add a run that took `total`, with the time of each module
+    pub fn record(&mut self, total: Duration, timings: &FxHashMap<&str, Duration>) {
+        self.runs += 1;
+        push(&mut self.total, total);
+        for (label, time) in timings {
+            push(self.modules.entry((*label).to_string()).or_default(), *time);
+        }
+    }
+
+    /// Summary of the recorded timings, slowest modules first
+    #[must_use]
+    pub fn report(&self) -> String {
+        let mut out = format!("Runs: {}\n", self.runs);
+        if let Some((median, slowest)) = summary(&self.total) {
+            let _ = writeln!(
+                out,
+                "Collection: {} median, {} slowest of the last {}",
+                millis(median),
+                millis(slowest),
+                self.total.len()
+            );
+        }
+
+        let mut modules = self
+            .modules
+            .iter()
+            .filter_map(|(label, history)| Some((label, summary(history)?)))
+            .collect::<Vec<_>>();
+        if modules.is_empty() {
+            return out;
+        }
+        modules.sort_by_key(|(_, (median, _))| std::cmp::Reverse(*median));
+        let width = modules
+            .iter()
+            .map(|(x, _)| x.len())
+            .max()
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "\n{:width$}  {:>10}  {:>10}",
+            "Module", "Median", "Slowest"
+        );
+        for (label, (median, slowest)) in modules {
+            let _ = writeln!(
+                out,
+                "{label:width$}  {:>10}  {:>10}",
+                millis(median),
+                millis(slowest)
+            );
+        }
+        out
+    }
+}
+
+fn push(history: &mut Vec<u64>, time: Duration) {
+    history.push(u64::try_from(time.as_micros()).unwrap_or(u64::MAX));
+    if history.len() > HISTORY {
+        history.drain(..history.len() - HISTORY);
+    }
+}
+
+/// Median and slowest of `history`
+fn summary(history: &[u64]) -> Option<(u64, u64)> {
+    let mut sorted = history.to_vec();
+    sorted.sort_unstable();
+    Some((*sorted.get(sorted.len() / 2)?, *sorted.last()?))
+}
+
+fn millis(micros: u64) -> String {
+    format!("{:.2} ms", micros as f64 / 1000.0)
+}
@@ -70,7 +70,7 @@ mod tests {
     fn test_cpu() {
         assert_eq!(
             &AsRef::<str>::as_ref(FF_INFO.get("CPU").unwrap()).trim(),
-            &(MIRA_INFO.cpu.as_ref().unwrap())
+            &MIRA_INFO.cpu.as_ref().unwrap().to_string()
         );
     }
 
@@ -102,4 +102,592 @@ mod tests {
             &(MIRA_INFO.wm.as_ref().unwrap())
         );
     }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_cpuinfo() {
+        use crate::info::linuxinfo::parse_cpuinfo;
+        for (cpuinfo, expected) in [
+            (
+                include_str!("fixtures/cpuinfo/x86_64.txt"),
+                "13th Gen Intel(R) Core(TM) i7-1360P (16) @ 2.60 GHz",
+            ),
+            (
+                include_str!("fixtures/cpuinfo/armv7.txt"),
+                "ARMv7 Processor rev 4 (v7l) (2)",
+            ),
+            (
+                include_str!("fixtures/cpuinfo/s390x.txt"),
+                "IBM/S390 8561 (4) @ 5.20 GHz",
+            ),
+            (
+                include_str!("fixtures/cpuinfo/ppc64le.txt"),
+                "POWER9 (architected) (4) @ 2.17 GHz",
+            ),
+            (
+                include_str!("fixtures/cpuinfo/loongarch64.txt"),
+                "Loongson-3A5000 (2) @ 2.50 GHz",
+            ),
+            (
+                include_str!("fixtures/cpuinfo/mips.txt"),
+                "MIPS 1004Kc V2.15 (2)",
+            ),
+        ] {
+            assert_eq!(
+                parse_cpuinfo(cpuinfo).map(|x| x.to_string()).as_deref(),
+                Some(expected)
+            );
+        }
+        let cpu = parse_cpuinfo(include_str!("fixtures/cpuinfo/x86_64.txt")).unwrap();
+        assert_eq!((cpu.cores, cpu.freq_mhz), (Some(16.0), Some(2600.0)));
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_if_inet6() {
+        let if_inet6 = "\
+fe8000000000000000fc00fffe000001 04 40 20 80     eth0
+00000000000000000000000000000001 01 80 10 80       lo
+20010db8000000000000000000000002 04 40 00 80     eth0
+20010db80000000011223344556677ff 04 40 00 01     eth0
+20010db8000000000000000000000003 04 40 00 20     eth0
+fd000000000000000000000000000004 04 40 00 80     eth0
+";
+        let addrs = crate::info::linuxinfo::parse_if_inet6(if_inet6);
+        let addrs = crate::info::ip_addresses(
+            crate::config::IpVersion::Both,
+            [(arcstr::literal!("eth0"), "192.168.1.2".parse().unwrap())],
+            addrs,
+        );
+        assert_eq!(
+            addrs,
+            [
+                (arcstr::literal!("eth0"), arcstr::literal!("192.168.1.2")),
+                (arcstr::literal!("eth0"), arcstr::literal!("2001:db8::2")),
+            ]
+        );
+        let patterns = [String::from("!docker*"), String::from("e*")];
+        assert!(crate::info::interface_shown(&patterns, "eth0"));
+        assert!(!crate::info::interface_shown(&patterns, "docker0"));
+        assert!(!crate::info::interface_shown(&patterns, "wlan0"));
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_drm_fdinfo() {
+        use crate::info::linuxinfo::parse_drm_fdinfo;
+        let amdgpu = "\
+pos:\t0
+drm-driver:\tamdgpu
+drm-pdev:\t0000:03:00.0
+drm-client-id:\t42
+drm-memory-vram:\t1024 KiB
+drm-memory-gtt:\t\t512 KiB
+drm-engine-gfx:\t123456 ns
+";
+        let client = (String::from("0000:03:00.0"), 42);
+        assert_eq!(parse_drm_fdinfo(amdgpu), Some((client.clone(), 1024 << 10)));
+        let resident = format!("{amdgpu}drm-resident-vram:\t2 MiB\ndrm-resident-gtt:\t1 MiB\n");
+        assert_eq!(parse_drm_fdinfo(&resident), Some((client, 2 << 20)));
+        let xe = "drm-pdev:\t0000:04:00.0\ndrm-client-id:\t7\ndrm-resident-system0:\t4 MiB\n\
+                  drm-resident-vram0:\t8 MiB\n";
+        assert_eq!(
+            parse_drm_fdinfo(xe),
+            Some(((String::from("0000:04:00.0"), 7), 8 << 20))
+        );
+        assert_eq!(parse_drm_fdinfo("pos:\t0\nflags:\t02\n"), None);
+        let users = [("firefox", 300 << 20), ("steam", 2 << 30), ("idle", 0)];
+        let users = users.map(|(name, bytes)| (name.to_string(), bytes));
+        assert_eq!(
+            crate::info::top_gpu_users(users).as_deref(),
+            Some("steam 2.0 GiB, firefox 300.0 MiB")
+        );
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_pw_dump() {
+        use crate::info::linuxinfo::parse_pw_dump;
+        let dump = include_str!("fixtures/pw-dump.json");
+        assert_eq!(
+            parse_pw_dump(dump).as_deref(),
+            Some("1.2.7, 48 kHz, quantum 1024 (21.3 ms), 1 active stream")
+        );
+        let forced = dump.replace(
+            r#""key": "clock.force-quantum", "type": "", "value": 0"#,
+            r#""key": "clock.force-quantum", "type": "", "value": 64"#,
+        );
+        assert_eq!(
+            parse_pw_dump(&forced).as_deref(),
+            Some("1.2.7, 48 kHz, quantum 64 (1.3 ms), 1 active stream")
+        );
+        assert_eq!(parse_pw_dump("[]"), None);
+    }
+    #[test]
+    fn test_accessibility_summary() {
+        use crate::info::accessibility_summary;
+        assert_eq!(
+            accessibility_summary(Some("Orca"), true, false),
+            "Orca, high contrast"
+        );
+        assert_eq!(accessibility_summary(None, false, true), "reduced motion");
+        assert_eq!(accessibility_summary(None, false, false), "none enabled");
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_realtime_summary() {
+        use crate::info::linuxinfo::realtime_summary;
+        let limits =
+            "Limit                     Soft Limit           Hard Limit           Units     \n\
+                      Max realtime priority     95                   95                   \n";
+        assert_eq!(
+            realtime_summary(false, "#1 SMP PREEMPT_RT Thu Jan 1", limits, "quiet"),
+            "PREEMPT_RT, rtprio 95, threaded IRQs"
+        );
+        assert_eq!(
+            realtime_summary(
+                false,
+                "#1 SMP PREEMPT_DYNAMIC",
+                limits,
+                "quiet preempt=full threadirqs"
+            ),
+            "PREEMPT_DYNAMIC (full), rtprio 95, threaded IRQs"
+        );
+        assert_eq!(
+            realtime_summary(false, "#1 SMP", "", "quiet"),
+            "not preemptible, rtprio unknown, IRQs not threaded"
+        );
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_edid() {
+        let mut edid = vec![0, 255, 255, 255, 255, 255, 255, 0];
+        edid.resize(54, 0);
+        // 2560x1440 with a 241.5 MHz pixel clock and 160/41 blanking, about 60Hz
+        let [lo, hi] = 24150u16.to_le_bytes();
+        edid.extend([lo, hi, 0x00, 0xa0, 0xa0, 0xa0, 0x29, 0x50]);
+        edid.resize(72, 0);
+        edid.extend([0, 0, 0, 0xfc, 0]);
+        edid.extend(b"DELL U2719D\n ");
+        edid.resize(128, 0);
+        let edid = crate::info::linuxinfo::parse_edid(&edid).unwrap();
+        assert_eq!(edid.name.as_deref(), Some("DELL U2719D"));
+        let (width, height, refresh) = edid.preferred.unwrap();
+        assert_eq!((width, height), (2560, 1440));
+        assert!((refresh - 59.95).abs() < 0.01, "{refresh}");
+    }
+    #[test]
+    fn test_hostname_accent() {
+        use crate::config::Accent;
+        use crossterm::style::Color;
+        let color = |host| Accent::Hostname.color(Some(host));
+        assert_eq!(color("web-01"), color("web-01"));
+        assert_ne!(color("web-01"), color("web-02"));
+        assert!(matches!(color("db"), Color::Rgb { .. }));
+        assert_eq!(Accent::Red.color(Some("web-01")), Color::Red);
+        assert_eq!(Accent::Hostname.color(None), Color::Red);
+    }
+    #[test]
+    fn test_palette_colors() {
+        use crate::{
+            config::{Colors, PaletteName},
+            palette::{Hex, Palette},
+        };
+        use crossterm::style::Color;
+        let colors: Colors = toml::from_str("label = \"#FF8000\"\nvalue = \"#0a0b0c\"").unwrap();
+        assert_eq!(colors.label, Some(Hex([0xff, 0x80, 0x00])));
+        let palette = Palette::named(PaletteName::Nord).with_colors(&colors);
+        assert_eq!(
+            palette.label,
+            Color::Rgb {
+                r: 255,
+                g: 128,
+                b: 0
+            }
+        );
+        assert_eq!(
+            palette.separator,
+            Palette::named(PaletteName::Nord).separator
+        );
+        assert_eq!(
+            palette.value,
+            Some(Color::Rgb {
+                r: 10,
+                g: 11,
+                b: 12
+            })
+        );
+        for invalid in ["88c0d0", "#88c0d", "#88c0dg", "#ééé"] {
+            assert!(Hex::try_from(invalid.to_string()).is_err(), "{invalid}");
+        }
+    }
+    #[test]
+    fn test_gradient() {
+        use crate::colorizer::gradient;
+        use crossterm::style::Color;
+        let (black, white) = (
+            Color::Rgb { r: 0, g: 0, b: 0 },
+            Color::Rgb {
+                r: 255,
+                g: 255,
+                b: 255,
+            },
+        );
+        let colors = gradient(&[black, white, black], 5);
+        assert_eq!(colors.len(), 5);
+        assert_eq!((colors[0], colors[2], colors[4]), (black, white, black));
+        assert_eq!(
+            colors[1],
+            Color::Rgb {
+                r: 128,
+                g: 128,
+                b: 128
+            }
+        );
+        assert_eq!(gradient(&[white], 3), [white; 3]);
+    }
+    #[test]
+    fn test_logo_color_index() {
+        use crate::util::load_logo_file;
+        let path = std::env::temp_dir().join(format!("mirafetch-logo-{}.toml", std::process::id()));
+        let load = |art: &str| {
+            let logo = format!("name = [\"test\"]\ncolors = [\"Red\", \"Blue\"]\nart = \"{art}\"");
+            std::fs::write(&path, logo).unwrap();
+            load_logo_file(&path, &[], &|_, _| Ok(()))
+        };
+        assert!(load("${c1}/\\\\${c2}/\\\\").is_ok());
+        for art in ["${c0}/\\\\", "${c3}/\\\\", "${c300}/\\\\", "${c}/\\\\"] {
+            assert!(load(art).is_err(), "{art}");
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    fn test_json_schema() {
+        let output: serde_json::Value =
+            serde_json::from_str(&crate::output::to_json(&MIRA_INFO).unwrap()).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(crate::output::SCHEMA).unwrap();
+        let fields = output.as_object().unwrap().keys().collect::<Vec<_>>();
+        let properties = schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>();
+        assert_eq!(fields, properties);
+    }
+    #[test]
+    fn test_json_snapshot() {
+        let json = crate::output::to_json(&MIRA_INFO).unwrap();
+        let snapshot = crate::output::from_json(&json).unwrap();
+        assert_eq!(json, crate::output::to_json(&snapshot).unwrap());
+    }
+
+    #[test]
+    fn test_typed_values_from_text() {
+        // Snapshots from before the typed values only have their text
+        let info = crate::output::from_json(include_str!("fixtures/desktop.json")).unwrap();
+        let cpu = info.cpu.unwrap();
+        assert_eq!(cpu.model, "13th Gen Intel(R) Core(TM) i7-1360P");
+        assert_eq!((cpu.cores, cpu.freq_mhz), (Some(16.0), Some(5000.0)));
+        let memory = info.memory.unwrap();
+        assert_eq!(memory.to_string(), "7.43 GiB / 31.05 GiB");
+        assert_eq!(memory.total / (1 << 20), 31795);
+    }
+
+    #[test]
+    fn test_csv_fields() {
+        let info = crate::output::from_json(include_str!("fixtures/desktop.json")).unwrap();
+        let fields = ["hostname", "disks", "gpus.5"].map(String::from);
+        assert_eq!(
+            crate::output::to_csv(&info, &fields).unwrap(),
+            "hostname,disks,gpus.5\nframework,Disk (/) 212 GiB/ 931 GiB,\n"
+        );
+        let all = crate::output::to_csv(&info, &[]).unwrap();
+        assert!(all.starts_with("schema,username,hostname,os,"));
+        assert!(crate::output::to_csv(&info, &[String::from("nope")]).is_err());
+    }
+
+    #[test]
+    fn test_ansible_facts() {
+        let info = crate::output::from_json(include_str!("fixtures/server.json")).unwrap();
+        let facts = crate::output::ansible_facts(&info);
+        assert_eq!(facts["ansible_distribution"], "Alpine");
+        assert_eq!(facts["ansible_distribution_version"], "3.19");
+        assert_eq!(facts["ansible_distribution_major_version"], "3");
+        assert_eq!(facts["ansible_memtotal_mb"], 1987);
+        assert_eq!(facts["ansible_all_ipv6_addresses"][0], "fd00::4");
+        assert!(!facts.contains_key("ansible_product_name"));
+        assert!(!facts.contains_key("ansible_memfree_mb"));
+        let info = crate::output::from_json(include_str!("fixtures/desktop.json")).unwrap();
+        let processor = &crate::output::ansible_facts(&info)["ansible_processor"];
+        assert_eq!(processor.as_array().unwrap().len(), 16 * 3);
+        assert_eq!(processor[3], "1");
+        assert_eq!(processor[4], "GenuineIntel");
+        assert_eq!(processor[5], "13th Gen Intel(R) Core(TM) i7-1360P");
+    }
+
+    #[test]
+    fn test_transform_pipeline() {
+        use crate::transform::Pipeline;
+        let pipeline = Pipeline::try_from(String::from(
+            r#"value | replace("Intel(R) ", "") | replace("(R)", "") | upper | truncate(20)"#,
+        ))
+        .unwrap();
+        assert_eq!(
+            pipeline.apply("Intel(R) Xeon(R) Processor (1) @ 2.00 GHz"),
+            "XEON PROCESSOR (1) …"
+        );
+        for invalid in [
+            "",
+            "upper |",
+            "truncate(0)",
+            "shout",
+            r#"replace("a")"#,
+            r#"append("x"#,
+        ] {
+            assert!(
+                Pipeline::try_from(invalid.to_string()).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_hass_messages() {
+        let info = crate::output::from_json(
+            r#"{"schema": 1, "hostname": "Office-PC", "memory": "4.00 GiB / 16.00 GiB (cgroup)",
+                "disks": [{"name": "Disk (/)", "usage": "512 MiB/ 2 GiB"}], "battery": "85% (Charging)"}"#,
+        )
+        .unwrap();
+        let messages = crate::output::hass_messages(&info, None);
+        let topics = messages.iter().map(|(x, _)| x.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            topics,
+            [
+                "homeassistant/sensor/mirafetch_office_pc/memory/config",
+                "homeassistant/sensor/mirafetch_office_pc/disk/config",
+                "homeassistant/sensor/mirafetch_office_pc/battery/config",
+                "mirafetch/office_pc/state",
+            ]
+        );
+        assert_eq!(
+            messages[3].1,
+            serde_json::json!({"memory": 25.0, "disk": 25.0, "battery": 85.0, "uptime": null})
+        );
+        // The bytes are used over the rounded text when a snapshot has them
+        let info = crate::output::from_json(
+            r#"{"schema": 1, "disks": [{"name": "Disk (/)", "usage": "1 GiB/ 3 GiB"}],
+                "disk_usage": [{"name": "Disk (/)", "used": 1610612736, "total": 3221225472}]}"#,
+        )
+        .unwrap();
+        let messages = crate::output::hass_messages(&info, None);
+        assert_eq!(messages.last().unwrap().1["disk"], 50.0);
+    }
+    #[cfg(feature = "publish")]
+    #[test]
+    fn test_redact() {
+        use crate::publish::redact;
+        assert_eq!(
+            redact("mqtt://ha:s3cr@t@broker:1883/mirafetch"),
+            "mqtt://broker:1883/mirafetch"
+        );
+        assert_eq!(
+            redact("https://user:pw@example.com/hook?a=b@c: status code 500"),
+            "https://example.com/hook?a=b@c: status code 500"
+        );
+        assert_eq!(
+            redact("https://example.com/hook"),
+            "https://example.com/hook"
+        );
+    }
+
+    /// Render a fixture with the logo for `icon`
+    ///
+    /// Escape codes and newlines are spelled out as `\e` and `\n`, with a line break after
+    /// each newline and move to the next line so snapshot diffs point at the changed row
+    fn render_fixture(
+        fixture: &str,
+        icon: &str,
+        colorizer: &dyn crate::colorizer::Colorizer,
+        direction: crate::config::Direction,
+    ) -> String {
+        let info = crate::output::from_json(fixture).unwrap();
+        let logo = crate::util::get_icon(icon).unwrap();
+        let mut frame = Vec::new();
+        crate::render::render(
+            &mut frame,
+            colorizer.colorize(&logo),
+            &info.as_vec(),
+            logo.width,
+            crate::render::icon_height(&logo),
+            direction,
+            crate::palette::Palette::accent(crossterm::style::Color::Red),
+        )
+        .unwrap();
+        String::from_utf8(frame)
+            .unwrap()
+            .replace('\x1b', "\\e")
+            .replace('\n', "\\n\n")
+            .replace("\\e[1E", "\\e[1E\n")
+    }
+    #[test]
+    fn test_render_snapshots() {
+        use crate::{
+            colorizer::{Colorizer, Default, Flag},
+            config::{Direction, Orientation},
+            util::get_colorscheme,
+        };
+        let flag = |orientation| Flag {
+            color_scheme: get_colorscheme("transgender").unwrap(),
+            orientation,
+        };
+        let colorizers: [(&str, Box<dyn Colorizer>); 3] = [
+            ("default", Box::new(Default {})),
+            ("horizontal", Box::new(flag(Orientation::Horizontal))),
+            ("vertical", Box::new(flag(Orientation::Vertical))),
+        ];
+        let fixtures = [
+            ("desktop", include_str!("fixtures/desktop.json"), "arch"),
+            (
+                "server",
+                include_str!("fixtures/server.json"),
+                "alpine_small",
+            ),
+        ];
+        for (fixture, json, icon) in fixtures {
+            for (theme, colorizer) in &colorizers {
+                for direction in [Direction::Ltr, Direction::Rtl] {
+                    insta::assert_snapshot!(
+                        format!("{fixture}_{theme}_{direction:?}").to_lowercase(),
+                        render_fixture(json, icon, colorizer.as_ref(), direction)
+                    );
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_visible_width_hyperlink() {
+        use crate::render::visible_width;
+        let url = "https://www.example.com/";
+        let link = format!("\x1b]8;;{url}\x1b\\{url}\x1b]8;;\x1b\\");
+        assert_eq!(visible_width(&link), url.len());
+        assert_eq!(visible_width("\x1b[1mCPU\x1b[0m: x"), 6);
+    }
+    #[test]
+    fn test_render_lines_snapshots() {
+        use crate::{colorizer::Colorizer, config::Direction};
+        let fixtures = [
+            ("desktop", include_str!("fixtures/desktop.json"), "arch"),
+            (
+                "server",
+                include_str!("fixtures/server.json"),
+                "alpine_small",
+            ),
+        ];
+        for (fixture, json, icon) in fixtures {
+            let info = crate::output::from_json(json).unwrap().as_vec();
+            let logo = crate::util::get_icon(icon).unwrap();
+            for direction in [Direction::Ltr, Direction::Rtl] {
+                let mut frame = Vec::new();
+                crate::render::render_lines(
+                    &mut frame,
+                    crate::colorizer::Default {}.colorize(&logo),
+                    &info,
+                    logo.width,
+                    direction,
+                    false,
+                    crate::palette::Palette::accent(crossterm::style::Color::Red),
+                )
+                .unwrap();
+                insta::assert_snapshot!(
+                    format!("{fixture}_lines_{direction:?}").to_lowercase(),
+                    String::from_utf8(frame).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixture_rows() {
+        // Modules a fixture leaves out get no row, so new modules don't change the snapshots
+        let info = crate::output::from_json(include_str!("fixtures/server.json")).unwrap();
+        let rows = info.as_vec();
+        let labels = rows[2..rows.len() - 2]
+            .iter()
+            .map(|(x, _)| x.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                "OS", "Kernel", "Uptime", "Packages", "Shell", "Memory", "Locale", "Disk (/)",
+                "IP", "IP"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_lines_without_logo() {
+        let info = [
+            (
+                arcstr::ArcStr::from("OS"),
+                arcstr::ArcStr::from("Arch Linux"),
+            ),
+            (arcstr::ArcStr::from("Shell"), arcstr::ArcStr::from("zsh")),
+        ];
+        let mut frame = Vec::new();
+        crate::render::render_lines(
+            &mut frame,
+            [crossterm::style::Stylize::stylize(String::new())],
+            &info,
+            0,
+            crate::config::Direction::Ltr,
+            false,
+            crate::palette::Palette::accent(crossterm::style::Color::Red),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(frame).unwrap(),
+            "OS: Arch Linux\nShell: zsh\n"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_audit_sources() {
+        use crate::audit::Sources;
+
+        let config = crate::config::Config {
+            cpu_temp: Some(true),
+            ..Default::default()
+        };
+        let lines = Sources::for_collection(&config).lines();
+        assert!(lines.contains(&String::from("file     /proc/meminfo (Memory, Swap)")));
+        assert!(lines.iter().any(|x| x.ends_with("(cpu_temp)")));
+        assert!(!lines.iter().any(|x| x.starts_with("network")));
+    }
+
+    /// The filter applies to the whole process, so the test runs itself again in a child
+    #[test]
+    #[cfg(all(feature = "seccomp", target_os = "linux"))]
+    fn test_sandbox() {
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        // The socket is made here, and removed once the child is done as it can't do it itself
+        let Some(socket) = std::env::var_os("MIRAFETCH_SANDBOX_TEST") else {
+            let socket =
+                std::env::temp_dir().join(format!("mirafetch-sandbox-{}", std::process::id()));
+            let listener = UnixListener::bind(&socket).unwrap();
+            let status = Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "tests::tests::test_sandbox", "--test-threads=1"])
+                .env("MIRAFETCH_SANDBOX_TEST", &socket)
+                .status();
+            drop(listener);
+            std::fs::remove_file(&socket).unwrap();
+            assert!(status.unwrap().success());
+            return;
+        };
+        crate::sandbox::apply().unwrap();
+
+        assert!(std::fs::read_to_string("/proc/self/status").is_ok());
+        let write =
+            std::fs::write(std::env::temp_dir().join("mirafetch-written"), "x").unwrap_err();
+        assert_eq!(write.raw_os_error(), Some(libc::EPERM));
+        let connect = UnixStream::connect(&socket).unwrap_err();
+        assert_eq!(connect.raw_os_error(), Some(libc::EPERM));
+    }
 }
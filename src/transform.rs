@@ -0,0 +1,192 @@
+//! Pipelines of small transforms applied to module values, set with the `transforms` setting
+//!
+//! A pipeline is a list of steps separated by `|`, such as
+//! `value | replace("Intel(R)", "Intel") | truncate(40)`, where `value` is optional and stands
+//! for the value itself
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Upper,
+    Lower,
+    Trim,
+    Truncate(usize),
+    Replace(String, String),
+    Prepend(String),
+    Append(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Arg {
+    Text(String),
+    Number(usize),
+}
+
+/// A parsed pipeline, which keeps its source to be saved back to the configuration file
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Pipeline {
+    source: String,
+    steps: Vec<Step>,
+}
+
+impl Pipeline {
+    /// Run `value` through each step in turn
+    #[must_use]
+    pub fn apply(&self, value: &str) -> String {
+        self.steps
+            .iter()
+            .fold(value.to_string(), |value, step| match step {
+                Step::Upper => value.to_uppercase(),
+                Step::Lower => value.to_lowercase(),
+                Step::Trim => value.trim().to_string(),
+                Step::Truncate(len) if value.chars().count() > *len => {
+                    let mut res = value
+                        .chars()
+                        .take(len.saturating_sub(1))
+                        .collect::<String>();
+                    res.push('…');
+                    res
+                }
+                Step::Truncate(_) => value,
+                Step::Replace(from, to) => value.replace(from.as_str(), to),
+                Step::Prepend(text) => format!("{text}{value}"),
+                Step::Append(text) => format!("{value}{text}"),
+            })
+    }
+}
+
+impl TryFrom<String> for Pipeline {
+    type Error = anyhow::Error;
+
+    fn try_from(source: String) -> Result<Self> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            pos: 0,
+        };
+        let mut steps = Vec::new();
+        loop {
+            let name = parser.name();
+            let args = parser.args()?;
+            let step = match (name.as_str(), args.as_slice()) {
+                ("value", []) if steps.is_empty() => None,
+                ("upper", []) => Some(Step::Upper),
+                ("lower", []) => Some(Step::Lower),
+                ("trim", []) => Some(Step::Trim),
+                ("truncate", [Arg::Number(len)]) if *len > 0 => Some(Step::Truncate(*len)),
+                ("replace", [Arg::Text(from), Arg::Text(to)]) if !from.is_empty() => {
+                    Some(Step::Replace(from.clone(), to.clone()))
+                }
+                ("prepend", [Arg::Text(text)]) => Some(Step::Prepend(text.clone())),
+                ("append", [Arg::Text(text)]) => Some(Step::Append(text.clone())),
+                ("", _) => bail!("Missing a step in `{source}`"),
+                _ => bail!(
+                    "Unknown step `{name}` in `{source}`, expected upper, lower, trim, \
+                     truncate(length), replace(\"from\", \"to\"), prepend(\"text\") or append(\"text\")"
+                ),
+            };
+            steps.extend(step);
+            parser.skip_spaces();
+            match parser.bump() {
+                Some('|') => {}
+                None => break,
+                Some(x) => bail!("Unexpected `{x}` in `{source}`"),
+            }
+        }
+        Ok(Self { source, steps })
+    }
+}
+
+impl From<Pipeline> for String {
+    fn from(pipeline: Pipeline) -> Self {
+        pipeline.source
+    }
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let res = self.peek();
+        self.pos += 1;
+        res
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn name(&mut self) -> String {
+        self.skip_spaces();
+        let mut res = String::new();
+        while let Some(x) = self
+            .peek()
+            .filter(|x| x.is_ascii_alphanumeric() || *x == '_')
+        {
+            res.push(x);
+            self.pos += 1;
+        }
+        res
+    }
+
+    /// The arguments in parentheses after a step's name, if there are any
+    fn args(&mut self) -> Result<Vec<Arg>> {
+        self.skip_spaces();
+        if self.peek() != Some('(') {
+            return Ok(Vec::new());
+        }
+        self.pos += 1;
+        let mut args = Vec::new();
+        loop {
+            self.skip_spaces();
+            match self.bump() {
+                Some(')') if args.is_empty() => return Ok(args),
+                Some('"') => args.push(Arg::Text(self.string()?)),
+                Some(x) if x.is_ascii_digit() => {
+                    let mut number = x.to_string();
+                    while let Some(x) = self.peek().filter(char::is_ascii_digit) {
+                        number.push(x);
+                        self.pos += 1;
+                    }
+                    args.push(Arg::Number(number.parse()?));
+                }
+                _ => bail!("Expected a number or a quoted string"),
+            }
+            self.skip_spaces();
+            match self.bump() {
+                Some(',') => {}
+                Some(')') => return Ok(args),
+                _ => bail!("Expected `,` or `)` after an argument"),
+            }
+        }
+    }
+
+    /// A string after its opening quote, with `\"` and `\\` escapes
+    fn string(&mut self) -> Result<String> {
+        let mut res = String::new();
+        loop {
+            match self.bump().ok_or_else(|| anyhow!("Unclosed string"))? {
+                '"' => return Ok(res),
+                '\\' => res.push(self.bump().ok_or_else(|| anyhow!("Unclosed string"))?),
+                x => res.push(x),
+            }
+        }
+    }
+}
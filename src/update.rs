@@ -0,0 +1,93 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::version::VERSION;
+
+pub const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/ArgentumCation/mirafetch/releases/latest";
+
+/// Fetch the version number of the newest release
+///
+/// # Errors
+///
+/// This function will return an error if the release could not be fetched or has no tag
+pub fn latest_version() -> Result<String> {
+    let body = ureq::get(LATEST_RELEASE_URL)
+        .timeout(Duration::from_secs(2))
+        .set(
+            "User-Agent",
+            concat!("mirafetch/", env!("CARGO_PKG_VERSION")),
+        )
+        .call()?
+        .into_string()?;
+    let release: serde_json::Value = serde_json::from_str(&body)?;
+    release["tag_name"]
+        .as_str()
+        .map(|x| x.trim_start_matches('v').to_string())
+        .ok_or_else(|| anyhow!("Latest release has no tag"))
+}
+
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split(['.', '-', '+'])
+        .map_while(|x| x.parse().ok())
+        .collect()
+}
+
+/// Whether `latest` is a newer version than this build
+#[must_use]
+pub fn is_newer(latest: &str) -> bool {
+    parse_version(latest) > parse_version(VERSION)
+}
+
+#[must_use]
+pub fn notice(latest: &str) -> String {
+    format!("mirafetch {latest} is available (installed: {VERSION})")
+}
+
+/// Check for a new release now, remembering the result in `cache_dir`
+///
+/// # Errors
+///
+/// This function will return an error if the latest release could not be fetched
+pub fn check_now(cache_dir: &Path) -> Result<Option<String>> {
+    let latest = latest_version()?;
+    fs::create_dir_all(cache_dir)?;
+    fs::write(cache_dir.join("latest_version"), &latest)?;
+    Ok(is_newer(&latest).then_some(latest))
+}
+
+/// Check for a new release at most once per `interval`, otherwise reuse the last result
+///
+/// Any failure is treated as there being no update, so this never gets in the way, and is
+/// remembered like a success so an unreachable server isn't asked again on every run
+#[must_use]
+pub fn check_periodically(cache_dir: &Path, interval: Duration) -> Option<String> {
+    let cached = cache_dir.join("latest_version");
+    let fresh = fs::metadata(&cached)
+        .and_then(|x| x.modified())
+        .ok()
+        .and_then(|x| x.elapsed().ok())
+        .is_some_and(|age| age < interval);
+    if fresh {
+        let latest = fs::read_to_string(cached).ok()?;
+        let latest = latest.trim();
+        return is_newer(latest).then(|| latest.to_string());
+    }
+    check_now(cache_dir).unwrap_or_else(|_| {
+        // Keep the last known version, if any, but start the interval over
+        let _ = fs::create_dir_all(cache_dir).and_then(|()| {
+            fs::File::options()
+                .append(true)
+                .create(true)
+                .open(&cached)?
+                .set_modified(SystemTime::now())
+        });
+        None
+    })
+}
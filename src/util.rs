@@ -5,7 +5,15 @@ use regex::Regex;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DeserializeAs};
-use std::{iter::zip, num::ParseIntError, str::FromStr, sync::Arc};
+use std::{
+    fs,
+    iter::zip,
+    num::ParseIntError,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+use unicode_width::UnicodeWidthStr;
 
 const ICON_FILE: &str = include_str!("../data/icons.yaml");
 const FLAGS_FILE: &str = include_str!("../data/flags.toml");
@@ -14,7 +22,6 @@ const FLAGS_FILE: &str = include_str!("../data/flags.toml");
 /// # Errors
 ///
 /// This function will return an error if the icon cannot be found
-#[allow(dead_code)]
 pub fn get_icon<'a>(icon_name: impl Into<&'a str>) -> anyhow::Result<AsciiArt> {
     let icon_name = &icon_name.into().to_ascii_lowercase();
     let icons = serde_yaml::from_str::<Vec<AsciiArtUnprocessed>>(ICON_FILE)
@@ -28,26 +35,146 @@ pub fn get_icon<'a>(icon_name: impl Into<&'a str>) -> anyhow::Result<AsciiArt> {
         .ok_or_else(|| anyhow!(format!("Could not find an icon for {icon_name}")))
 }
 
-/// TODO
+/// Find an icon in the logo packs in `dirs`, falling back to the built in icons
+///
+/// Logos from packs take precedence, so a pack can also replace a built in icon. Packs and logo
+/// files that can't be read are skipped with a warning, and the logo found is passed to `verify`
+/// before it is used
 ///
 /// # Errors
 ///
-/// This function will return an error if the colorscheme cannot be found
-#[allow(dead_code)]
-pub fn get_colorscheme<'a>(scheme_name: impl Into<&'a str>) -> Arc<[Color]> {
-    let schemes: FxHashMap<String, Vec<(u8, u8, u8)>> =
-        toml::from_str(FLAGS_FILE).expect("Failed to parse flags.toml");
-    schemes
-        .get(scheme_name.into())
-        .expect("Failed to find scheme")
+/// This function will return an error if the logo found is unverified, or if the icon cannot be
+/// found
+pub fn find_icon<'a>(
+    icon_name: impl Into<&'a str>,
+    dirs: &[PathBuf],
+    verify: &dyn Fn(&Path, &[u8]) -> anyhow::Result<()>,
+) -> anyhow::Result<AsciiArt> {
+    let icon_name = icon_name.into();
+    let lowercase = icon_name.to_ascii_lowercase();
+    for dir in dirs.iter().filter(|x| x.is_dir()) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("Skipping logo pack {}: {err}", dir.display());
+                continue;
+            }
+        };
+        for path in entries.filter_map(Result::ok).map(|x| x.path()) {
+            if path.extension() != Some("toml".as_ref()) {
+                continue;
+            }
+            let (data, logo) = match read_logo(&path) {
+                Ok(x) => x,
+                Err(err) => {
+                    // TOML errors go on to quote the file, so only their first line is shown
+                    let err = err.to_string();
+                    let reason = err.lines().next().unwrap_or_default();
+                    eprintln!("Skipping invalid logo {}: {reason}", path.display());
+                    continue;
+                }
+            };
+            if logo.name.contains(&lowercase) {
+                verify(&path, &data)
+                    .map_err(|err| anyhow!("Invalid logo {}: {err}", path.display()))?;
+                return Ok(logo);
+            }
+        }
+    }
+    get_icon(icon_name)
+}
+
+/// Read a logo from a logo pack, one `.toml` file per logo
+fn read_logo(path: &Path) -> anyhow::Result<(Vec<u8>, AsciiArt)> {
+    let data = fs::read(path)?;
+    let logo = toml::from_str::<AsciiArtUnprocessed>(std::str::from_utf8(&data)?)?;
+    Ok((data, AsciiArt::try_from(logo)?))
+}
+
+/// Load a logo from a text file, where `${c1}`, `${c2}` and so on switch colors like in the
+/// built in icons
+///
+/// Each number picks that color of `colors`, usually those of the logo it replaces, or the
+/// terminal's own color with that number past them. A `.toml` file is read like a logo from
+/// a logo pack instead. The file is passed to `verify` before it is used
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read, is rejected by `verify`,
+/// or isn't a valid logo
+pub fn load_logo_file(
+    path: &Path,
+    colors: &[Color],
+    verify: &dyn Fn(&Path, &[u8]) -> anyhow::Result<()>,
+) -> anyhow::Result<AsciiArt> {
+    let load = || -> anyhow::Result<AsciiArt> {
+        let data = fs::read(path)?;
+        verify(path, &data)?;
+        let data = String::from_utf8(data)?;
+        if path.extension() == Some("toml".as_ref()) {
+            return AsciiArt::try_from(toml::from_str::<AsciiArtUnprocessed>(&data)?);
+        }
+        let regex = Regex::new(r"\$\{c(\d*)\}")?;
+        let numbers = regex
+            .captures_iter(&data)
+            .map(|x| x[1].parse::<u8>().ok().filter(|x| *x > 0))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| anyhow!("colors are numbered from ${{c1}} up to ${{c255}}"))?;
+        // Text before the first placeholder is drawn in the first color
+        let art = if regex.find(&data).is_some_and(|x| x.start() == 0) {
+            data
+        } else {
+            format!("${{c1}}{data}")
+        };
+        let count = numbers.into_iter().max().unwrap_or(1);
+        AsciiArt::try_from(AsciiArtUnprocessed {
+            name: vec![path.display().to_string()],
+            colors: (1..=count)
+                .map(|idx| {
+                    colors
+                        .get(usize::from(idx) - 1)
+                        .copied()
+                        .unwrap_or(Color::AnsiValue(idx))
+                })
+                .collect(),
+            width: 0,
+            art,
+        })
+    };
+    load().map_err(|err| anyhow!("Invalid logo {}: {err}", path.display()))
+}
+
+/// Colors of the flag pattern `scheme_name`, top to bottom
+///
+/// # Errors
+///
+/// This function will return an error if there is no flag pattern by that name
+pub fn get_colorscheme<'a>(scheme_name: impl Into<&'a str>) -> anyhow::Result<Arc<[Color]>> {
+    let scheme_name = scheme_name.into();
+    let schemes: FxHashMap<String, Vec<(u8, u8, u8)>> = toml::from_str(FLAGS_FILE)?;
+    Ok(schemes
+        .get(scheme_name)
+        .ok_or_else(|| {
+            anyhow!("Unknown flag `{scheme_name}`, see `mirafetch list themes` for the flags")
+        })?
         .iter()
         .map(|(r, g, b)| Color::Rgb {
             r: r.to_owned(),
             g: g.to_owned(),
             b: b.to_owned(),
         })
-        .collect()
+        .collect())
 }
+/// Names of the flag patterns that can be used as `scheme_name`, sorted alphabetically
+#[must_use]
+pub fn colorscheme_names() -> Vec<String> {
+    let schemes: FxHashMap<String, toml::Value> =
+        toml::from_str(FLAGS_FILE).expect("Failed to parse flags.toml");
+    let mut names = schemes.into_keys().collect::<Vec<_>>();
+    names.sort_unstable();
+    names
+}
+
 pub struct AsciiArt {
     pub name: Vec<String>,
     pub colors: Vec<Color>,
@@ -61,6 +188,8 @@ struct AsciiArtUnprocessed {
     pub name: Vec<String>,
     #[serde_as(as = "Vec<ColorRemote>")]
     pub colors: Vec<Color>,
+    /// Worked out from the art when left out
+    #[serde(default)]
     pub width: u16,
     pub art: String,
 }
@@ -70,21 +199,38 @@ impl TryFrom<AsciiArtUnprocessed> for AsciiArt {
         let color_idx: Vec<u8> = regex
             .captures_iter(&val.art)
             .map(|x| -> anyhow::Result<u8> {
-                str::parse(
+                let idx = str::parse(
                     x.get(1)
                         .ok_or_else(|| anyhow!("Invalid Ascii Art"))?
                         .as_str(),
                 )
-                .map_err(|op: ParseIntError| anyhow!(op))
+                .map_err(|op: ParseIntError| anyhow!(op))?;
+                // Colors are numbered from 1, and each needs an entry in `colors`
+                if idx == 0 || usize::from(idx) > val.colors.len() {
+                    return Err(anyhow!(
+                        "${{c{idx}}} is out of range, the logo has {} colors",
+                        val.colors.len()
+                    ));
+                }
+                Ok(idx)
             })
-            .map(std::result::Result::unwrap)
-            .collect();
+            .collect::<anyhow::Result<_>>()?;
         let chunks = regex
             .split(&val.art)
             .map(std::borrow::ToOwned::to_owned)
             .skip(1)
             .collect::<Vec<String>>();
         let ascii_art = (zip(color_idx, chunks)).collect();
+        let width = if val.width == 0 {
+            regex
+                .replace_all(&val.art, "")
+                .lines()
+                .map(UnicodeWidthStr::width)
+                .max()
+                .map_or(0, |x| u16::try_from(x).unwrap_or(u16::MAX))
+        } else {
+            val.width
+        };
         Ok(Self {
             name: val
                 .name
@@ -93,14 +239,14 @@ impl TryFrom<AsciiArtUnprocessed> for AsciiArt {
                 .map(|x| x.to_lowercase())
                 .collect(),
             colors: val.colors.clone(),
-            width: val.width,
+            width,
             art: ascii_art,
         })
     }
 
     type Error = anyhow::Error;
 }
-#[allow(dead_code, clippy::cast_precision_loss)]
+#[allow(clippy::cast_precision_loss)]
 #[must_use]
 pub fn bytecount_format<T>(i: T, precision: usize) -> String
 where
@@ -133,6 +279,69 @@ where
     panic!("bytes: {i}, precision: {precision}")
 }
 
+/// A color of its own for a machine, from a hash of its hostname
+///
+/// The hash is FNV-1a rather than the standard library's, which may change between Rust
+/// versions, so a machine keeps its color across updates. The hue is all that changes, with the
+/// saturation and lightness picked to read on dark and light backgrounds
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+#[must_use]
+pub fn hostname_color(hostname: &str) -> Color {
+    const SATURATION: f32 = 0.65;
+    const LIGHTNESS: f32 = 0.55;
+    let hash = hostname.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, x| {
+        (hash ^ u64::from(x)).wrapping_mul(0x0100_0000_01b3)
+    });
+    let hue = (hash % 360) as f32 / 60.0;
+    let chroma = (1.0 - (2.0f32.mul_add(LIGHTNESS, -1.0)).abs()) * SATURATION;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let channel = |x: f32| ((x + LIGHTNESS - chroma / 2.0) * 255.0).round() as u8;
+    Color::Rgb {
+        r: channel(r),
+        g: channel(g),
+        b: channel(b),
+    }
+}
+
+/// Format an uptime in seconds like `2 days, 4 hours, 13 mins`, leaving out parts that are zero
+#[must_use]
+pub fn uptime_format(seconds: u64) -> String {
+    let plural = |count: u64, unit: &str| {
+        if count == 1 {
+            format!("{count} {unit}")
+        } else {
+            format!("{count} {unit}s")
+        }
+    };
+    let parts = [
+        (seconds / 86400, "day"),
+        (seconds / 3600 % 24, "hour"),
+        (seconds / 60 % 60, "min"),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, unit)| plural(count, unit))
+    .collect::<Vec<_>>();
+    // Right after booting there is nothing but seconds to show
+    if parts.is_empty() {
+        plural(seconds, "sec")
+    } else {
+        parts.join(", ")
+    }
+}
+
 // TODO move all this stuff into a private module or something
 #[derive(Serialize, Deserialize)]
 #[serde(remote = "Color")]
@@ -0,0 +1,75 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use arcstr::ArcStr;
+
+/// Version of this build of mirafetch
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Describe this build of mirafetch, e.g. `0.1.0 (1a2b3c4, release, x86_64-unknown-linux-gnu, cargo install)`
+#[must_use]
+pub fn self_info() -> ArcStr {
+    let mut details = vec![
+        env!("MIRAFETCH_GIT_COMMIT"),
+        env!("MIRAFETCH_PROFILE"),
+        env!("MIRAFETCH_TARGET"),
+    ];
+    let features = env!("MIRAFETCH_FEATURES");
+    if !features.is_empty() {
+        details.push(features);
+    }
+    if let Some(method) = install_method() {
+        details.push(method);
+    }
+    arcstr::format!("{VERSION} ({})", details.join(", "))
+}
+
+/// Guess how mirafetch was installed from where the running binary lives
+#[must_use]
+pub fn install_method() -> Option<&'static str> {
+    if env::var_os("APPIMAGE").is_some() {
+        return Some("AppImage");
+    }
+    let exe = env::current_exe().ok()?;
+    let exe = fs::canonicalize(&exe).unwrap_or(exe);
+    let owned_by_package = |db: &str, suffix: &str| {
+        fs::read_dir(db).is_ok_and(|entries| {
+            entries.filter_map(Result::ok).any(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|x| x.starts_with("mirafetch") && x.ends_with(suffix))
+            })
+        })
+    };
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|x| Path::new(&x).join(".cargo")));
+
+    if exe.starts_with("/nix/store") {
+        Some("nix")
+    } else if [
+        "/opt/homebrew",
+        "/usr/local/Cellar",
+        "/home/linuxbrew/.linuxbrew",
+    ]
+    .iter()
+    .any(|x| exe.starts_with(x))
+    {
+        Some("brew")
+    } else if cargo_home.is_some_and(|x| exe.starts_with(x.join("bin"))) {
+        Some("cargo install")
+    } else if exe.components().any(|x| x.as_os_str() == "target") {
+        Some("source build")
+    } else if !exe.starts_with("/usr") {
+        None
+    } else if owned_by_package("/var/lib/pacman/local", "") {
+        Some("pacman")
+    } else if owned_by_package("/var/lib/dpkg/info", ".list") {
+        Some("dpkg")
+    } else {
+        None
+    }
+}